@@ -6,10 +6,26 @@
 //! ## Functions
 //!
 //! - [`set_hanzi_onsets`]: Analyzes and sets onset (initial consonant) information
+//! - [`set_hanzi_onsets_with_candidates`]: Sets onsets using a caller-provided onset list
 //! - [`set_hanzi_rime`]: Analyzes and sets rime (vowel + final consonant) information
+//! - [`refresh_phonetics`]: Recomputes onset and rime, discarding existing values
+//! - [`analyze_verbose`]: Reports onset, stripped prefix length and rime of one record
+//! - [`longest_onset_match`]: Finds the longest onset at the start of a toneless pinyin
+//! - [`parse_syllable`]: Splits a toneless syllable into onset and rime
+//! - [`parse_marked_syllable`]: Splits a tone-marked syllable into onset, rime and tone
+//! - [`is_valid_syllable`]: Checks whether an onset and rime form a standard syllable
+//! - [`is_known_pinyin`]: Checks whether a toneless string is a standard syllable
+//! - [`is_known_pinyin_with_syllables`]: Checks a toneless string against a custom syllable list
+//! - [`valid_onsets_for`]: Lists the onsets that combine with a rime
+//! - [`parse_tone_query`]: Splits a pinyin query into toneless pinyin and optional tone
+//! - [`tone_contour`]: Names the pitch contour of a tone
+//! - [`syllable_structure`]: Classifies the consonant-vowel structure of a syllable
 
-use crate::types::{HanziOnset, HanziRecord, HanziRime};
+use crate::pinyin::{marked_tone, strip_tones, STANDARD_SYLLABLES};
+use crate::types::{HanziOnset, HanziRecord, HanziRime, ONSET_CANDIDATES};
+use std::collections::HashSet;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
 /// Analyzes and sets the onset (initial consonant) for each character's pinyin
 ///
@@ -17,9 +33,9 @@ use std::str::FromStr;
 /// determines the appropriate onset classification based on the initial consonant(s).
 /// The onset field is updated in-place for each record.
 ///
-/// The function uses `HanziOnset::from_str()` internally for efficient onset detection,
-/// checking possible onsets in order of decreasing length to ensure proper matching
-/// (e.g., "zh" before "z").
+/// The function uses [`HanziOnset::strip_from`] internally for onset detection,
+/// which checks possible onsets in order of decreasing length to ensure proper
+/// matching (e.g., "zh" before "z").
 ///
 /// # Arguments
 ///
@@ -27,6 +43,7 @@ use std::str::FromStr;
 ///
 /// # Onset Detection Rules
 ///
+/// - Syllabic nasal interjections (m, n, ng) have no onset: `HanziOnset::None`
 /// - Multi-character onsets (zh, ch, sh) are checked first
 /// - Single-character onsets are checked next
 /// - If no onset matches, `HanziOnset::None` is assigned (vowel-initial syllables)
@@ -36,27 +53,60 @@ use std::str::FromStr;
 /// - "zhong" → `HanziOnset::Zh`
 /// - "ma" → `HanziOnset::M`  
 /// - "an" → `HanziOnset::None`
+/// - "ng" → `HanziOnset::None`
+/// - "hng" → `HanziOnset::H`
 pub fn set_hanzi_onsets(records: &mut [HanziRecord]) {
-    // Define onset candidates in order of decreasing length to ensure proper matching
-    // (e.g., "zh" must be checked before "z")
-    const ONSET_CANDIDATES: &[&str] = &[
-        "zh", "ch", "sh", // Multi-character onsets first
-        "b", "p", "m", "f", "d", "t", "n", "z", "c", "s", "l", "r", "j", "q", "x", "g", "k", "h",
-        "y", "w",
-    ];
+    set_hanzi_onsets_with_candidates(records, ONSET_CANDIDATES);
+}
 
+/// Sets the onset of each record using a caller-provided list of onsets
+///
+/// Works like [`set_hanzi_onsets`], but the onsets are matched against
+/// `candidates` instead of the standard list. This allows analyzing dialect
+/// variants, e.g. Taiwanese Mandarin where the retroflex onsets zh, ch and sh
+/// merge into z, c and s. The first candidate that prefixes the pinyin wins,
+/// so a longer onset must come before any onset that is a prefix of it.
+/// Candidates that are not onset spellings are ignored.
+///
+/// # Arguments
+///
+/// * `records` - Mutable slice of HanziRecord to analyze
+/// * `candidates` - Onset spellings in matching order
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{set_hanzi_onsets_with_candidates, HanziOnset, HanziRecord};
+/// let mut records = vec![HanziRecord::builder("中", "zhōng", 1).build()];
+/// set_hanzi_onsets_with_candidates(&mut records, &["z", "c", "s"]);
+/// assert_eq!(records[0].onset, HanziOnset::Z);
+/// ```
+pub fn set_hanzi_onsets_with_candidates(records: &mut [HanziRecord], candidates: &[&str]) {
     for record in records.iter_mut() {
-        let pinyin = &record.pinyin_without_tone;
-
-        // Try to find the first matching onset
-        record.onset = ONSET_CANDIDATES
-            .iter()
-            .find(|&&onset_str| pinyin.starts_with(onset_str))
-            .and_then(|&onset_str| HanziOnset::from_str(onset_str).ok())
-            .unwrap_or(HanziOnset::None);
+        record.onset = HanziOnset::strip_from_candidates(&record.pinyin_without_tone, candidates).0;
     }
 }
 
+/// Finds the onset of a toneless pinyin and the byte length of its prefix
+///
+/// This is the onset matcher used by every analysis function. It is built on
+/// [`HanziOnset::strip_from`], so the longest onset wins: "chi" has the onset
+/// `Ch`, not `C`. Syllabic nasals and vowel-initial syllables yield
+/// `(HanziOnset::None, 0)`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{longest_onset_match, HanziOnset};
+/// assert_eq!(longest_onset_match("chi"), (HanziOnset::Ch, 2));
+/// assert_eq!(longest_onset_match("san"), (HanziOnset::S, 1));
+/// assert_eq!(longest_onset_match("an"), (HanziOnset::None, 0));
+/// ```
+pub fn longest_onset_match(pinyin: &str) -> (HanziOnset, usize) {
+    let (onset, rest) = HanziOnset::strip_from(pinyin);
+    (onset, pinyin.len() - rest.len())
+}
+
 /// Analyzes and sets the rime (vowel + final consonant) for each character's pinyin
 ///
 /// This function determines the rime part of each character's pronunciation by
@@ -84,6 +134,9 @@ pub fn set_hanzi_onsets(records: &mut [HanziRecord]) {
 /// - "ma" (onset: M) → rime part "a" → `HanziRime::A`
 /// - "zhong" (onset: Zh) → rime part "ong" → `HanziRime::Ong`
 /// - "nü" (onset: N) → rime part "ü" → `HanziRime::V`
+/// - "jue" (onset: J) → rime part "ue" → `HanziRime::Ve`
+/// - "ng" (onset: None) → rime part "ng" → `HanziRime::Ng`
+/// - "hm" (onset: H) → rime part "m" → `HanziRime::M`
 pub fn set_hanzi_rime(records: &mut [HanziRecord]) {
     for record in records.iter_mut() {
         let pinyin = &record.pinyin_without_tone;
@@ -101,8 +154,402 @@ pub fn set_hanzi_rime(records: &mut [HanziRecord]) {
             pinyin.as_str()
         };
 
-        // Try to parse rime part using HanziRime::from_str()
-        record.rime = HanziRime::from_str(rime_part).unwrap_or(HanziRime::None);
+        record.rime = classify_rime(&record.onset, rime_part);
+    }
+}
+
+/// Parses the rime part that follows an onset
+///
+/// A written "ue" after j, q, x, y, n and l is the rime "üe", so it is
+/// classified as `HanziRime::Ve` rather than `HanziRime::Ue`. Likewise a
+/// written "u" after j, q, x and y is the rime "ü" (`HanziRime::V`), the
+/// spelling [`render_pinyin`](crate::render_pinyin) writes back. Returns
+/// `HanziRime::None` if the rime part is not a known rime.
+fn classify_rime(onset: &HanziOnset, rime_part: &str) -> HanziRime {
+    match HanziRime::from_str(rime_part) {
+        Ok(HanziRime::U)
+            if matches!(
+                onset,
+                HanziOnset::J | HanziOnset::Q | HanziOnset::X | HanziOnset::Y
+            ) =>
+        {
+            HanziRime::V
+        }
+        Ok(HanziRime::Ue)
+            if matches!(
+                onset,
+                HanziOnset::J
+                    | HanziOnset::Q
+                    | HanziOnset::X
+                    | HanziOnset::Y
+                    | HanziOnset::N
+                    | HanziOnset::L
+            ) =>
+        {
+            HanziRime::Ve
+        }
+        Ok(rime) => rime,
+        Err(_) => HanziRime::None,
+    }
+}
+
+/// Recomputes the onset and rime of every record from its pinyin
+///
+/// Runs [`set_hanzi_onsets`] and then [`set_hanzi_rime`], in the order the rime
+/// analysis requires. Any onset and rime already stored in the records, such as
+/// a stale column from an older analysis, is discarded and overwritten.
+///
+/// # Arguments
+///
+/// * `records` - Mutable slice of HanziRecord to analyze
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{refresh_phonetics, HanziOnset, HanziRecord, HanziRime};
+/// let mut records = vec![HanziRecord {
+///     onset: HanziOnset::B, // Stale value
+///     rime: HanziRime::Ang,
+///     ..HanziRecord::builder("马", "mǎ", 3).build()
+/// }];
+/// refresh_phonetics(&mut records);
+/// assert_eq!(records[0].onset, HanziOnset::M);
+/// assert_eq!(records[0].rime, HanziRime::A);
+/// ```
+pub fn refresh_phonetics(records: &mut [HanziRecord]) {
+    set_hanzi_onsets(records);
+    set_hanzi_rime(records);
+}
+
+/// Analyzes a single record and reports the matched onset prefix length
+///
+/// This is a debugging aid for onset/rime misclassifications. It performs the
+/// same onset detection as [`set_hanzi_onsets`] and the same rime detection as
+/// [`set_hanzi_rime`] on the record's `pinyin_without_tone`, and additionally
+/// returns how many bytes were stripped as the onset. The record itself is not
+/// modified.
+///
+/// # Arguments
+///
+/// * `record` - The HanziRecord to analyze
+///
+/// # Returns
+///
+/// A tuple of:
+/// - The detected onset
+/// - The byte length of the onset prefix stripped from the pinyin
+/// - The rime detected from the remaining part
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{analyze_verbose, HanziOnset, HanziRecord, HanziRime};
+/// let record = HanziRecord {
+///     frequency: 1,
+///     simplified: "知".to_string(),
+///     traditional: "知".to_string(),
+///     pinyin: "zhī".to_string(),
+///     pinyin_without_tone: "zhi".to_string(),
+///     tone: 1,
+///     onset: HanziOnset::None,
+///     rime: HanziRime::None,
+/// };
+/// assert_eq!(analyze_verbose(&record), (HanziOnset::Zh, 2, HanziRime::I));
+/// ```
+pub fn analyze_verbose(record: &HanziRecord) -> (HanziOnset, usize, HanziRime) {
+    let pinyin = record.pinyin_without_tone.as_str();
+    let (onset, stripped_len) = longest_onset_match(pinyin);
+    let rime = classify_rime(&onset, &pinyin[stripped_len..]);
+    (onset, stripped_len, rime)
+}
+
+/// Splits a toneless pinyin syllable into its onset and rime
+///
+/// The onset is detected in the same way as [`set_hanzi_onsets`]. After j, q,
+/// x and y the written "u" is the rime "ü", so "qü" and "qu" both give
+/// `HanziRime::V`. A "ue" after j, q, x, y, n and l is the rime "üe".
+///
+/// # Arguments
+///
+/// * `syllable` - Pinyin syllable without tone marks
+///
+/// # Returns
+///
+/// * `Some((onset, rime))` - If the part after the onset is a known rime
+/// * `None` - If no rime matches
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{parse_syllable, HanziOnset, HanziRime};
+/// assert_eq!(parse_syllable("zhuang"), Some((HanziOnset::Zh, HanziRime::Uang)));
+/// assert_eq!(parse_syllable("xüe"), Some((HanziOnset::X, HanziRime::Ve)));
+/// assert_eq!(parse_syllable("ju"), Some((HanziOnset::J, HanziRime::V)));
+/// assert_eq!(parse_syllable("xq"), None);
+/// ```
+pub fn parse_syllable(syllable: &str) -> Option<(HanziOnset, HanziRime)> {
+    let (onset, stripped_len) = longest_onset_match(syllable);
+    let rime_part = &syllable[stripped_len..];
+
+    // "ü" is written "u" after j, q, x and y
+    let rime_part = match onset {
+        HanziOnset::J | HanziOnset::Q | HanziOnset::X | HanziOnset::Y => {
+            rime_part.replacen('ü', "u", 1)
+        }
+        _ => rime_part.to_string(),
+    };
+
+    match classify_rime(&onset, &rime_part) {
+        HanziRime::None => None,
+        rime => Some((onset, rime)),
+    }
+}
+
+/// Splits a tone-marked pinyin syllable into its onset, rime and tone
+///
+/// The tone marks are removed and the toneless syllable is split with
+/// [`parse_syllable`]. The tone is taken from the tone mark; a syllable
+/// without one is in the neutral tone (5).
+///
+/// # Arguments
+///
+/// * `marked` - Pinyin syllable with a tone mark, e.g. "mǎ"
+///
+/// # Returns
+///
+/// A tuple of the onset, the rime and the tone. If the syllable cannot be
+/// split, the onset and rime are both `None`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{parse_marked_syllable, HanziOnset, HanziRime};
+/// assert_eq!(parse_marked_syllable("mǎ"), (HanziOnset::M, HanziRime::A, 3));
+/// assert_eq!(parse_marked_syllable("ma"), (HanziOnset::M, HanziRime::A, 5));
+/// ```
+pub fn parse_marked_syllable(marked: &str) -> (HanziOnset, HanziRime, u32) {
+    let (onset, rime) = parse_syllable(&strip_tones(marked)).unwrap_or_default();
+    (onset, rime, marked_tone(marked).unwrap_or(5))
+}
+
+/// Checks whether an onset and rime combine into a standard Mandarin syllable
+///
+/// # Arguments
+///
+/// * `onset` - The onset of the syllable
+/// * `rime` - The rime of the syllable
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{is_valid_syllable, HanziOnset, HanziRime};
+/// assert!(is_valid_syllable(&HanziOnset::Zh, &HanziRime::Uang));
+/// assert!(!is_valid_syllable(&HanziOnset::B, &HanziRime::Uang));
+/// ```
+pub fn is_valid_syllable(onset: &HanziOnset, rime: &HanziRime) -> bool {
+    static VALID_SYLLABLES: OnceLock<HashSet<(HanziOnset, HanziRime)>> = OnceLock::new();
+
+    VALID_SYLLABLES
+        .get_or_init(|| {
+            STANDARD_SYLLABLES
+                .iter()
+                .filter_map(|syllable| parse_syllable(syllable))
+                .collect()
+        })
+        .contains(&(onset.clone(), rime.clone()))
+}
+
+/// Lists the onsets that combine with a rime in standard Mandarin
+///
+/// Every onset is checked with [`is_valid_syllable`]. Since "ju" and "xue"
+/// are analyzed as the rimes "ü" and "üe", j, q, x and y are listed for those
+/// rimes rather than for "u" and "ue".
+///
+/// # Arguments
+///
+/// * `rime` - The rime to look up
+///
+/// # Returns
+///
+/// The onsets in onset chart order, with `None` last if the rime also forms
+/// a syllable on its own
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{valid_onsets_for, HanziOnset, HanziRime};
+/// let onsets = valid_onsets_for(&HanziRime::Ong);
+/// assert!(onsets.contains(&HanziOnset::Zh));
+/// assert!(!onsets.contains(&HanziOnset::B));
+/// assert!(valid_onsets_for(&HanziRime::V).contains(&HanziOnset::J));
+/// ```
+pub fn valid_onsets_for(rime: &HanziRime) -> Vec<HanziOnset> {
+    HanziOnset::all()
+        .iter()
+        .filter(|onset| is_valid_syllable(onset, rime))
+        .cloned()
+        .collect()
+}
+
+/// Checks whether a toneless pinyin string is a standard Mandarin syllable
+///
+/// This is meant for validating user input before querying. The input is
+/// lowercased and "v" is accepted in place of "ü".
+///
+/// # Arguments
+///
+/// * `pinyin` - Pinyin syllable without tone marks
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::is_known_pinyin;
+/// assert!(is_known_pinyin("zhuang"));
+/// assert!(is_known_pinyin("lv"));
+/// assert!(!is_known_pinyin("xq"));
+/// ```
+pub fn is_known_pinyin(pinyin: &str) -> bool {
+    let normalized = pinyin.to_lowercase().replace('v', "ü");
+    parse_syllable(&normalized).is_some_and(|(onset, rime)| is_valid_syllable(&onset, &rime))
+}
+
+/// Checks whether a toneless pinyin string is in a custom syllable list
+///
+/// Works like [`is_known_pinyin`], but validates against `syllables`, e.g. a
+/// list loaded with [`load_syllable_list`](crate::load_syllable_list) for a
+/// dialect study, instead of the standard Mandarin syllables. The input is
+/// lowercased and "v" is accepted in place of "ü".
+///
+/// # Arguments
+///
+/// * `pinyin` - Pinyin syllable without tone marks
+/// * `syllables` - The valid syllables, lowercased and with "ü" rather than "v"
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::collections::HashSet;
+/// # use study_rust_hanzi::is_known_pinyin_with_syllables;
+/// let syllables: HashSet<String> = ["sei", "lü"].iter().map(|s| s.to_string()).collect();
+/// assert!(is_known_pinyin_with_syllables("Sei", &syllables));
+/// assert!(is_known_pinyin_with_syllables("lv", &syllables));
+/// assert!(!is_known_pinyin_with_syllables("ma", &syllables));
+/// ```
+pub fn is_known_pinyin_with_syllables(pinyin: &str, syllables: &HashSet<String>) -> bool {
+    syllables.contains(&pinyin.to_lowercase().replace('v', "ü"))
+}
+
+/// Splits a pinyin query into its toneless form and an optional tone
+///
+/// The tone may be given either as a trailing digit (e.g. "ma3") or as a tone
+/// mark (e.g. "mǎ"). A trailing digit takes precedence over a tone mark. Only
+/// the digits 1-5 are recognized as tones; a trailing 0 is a wildcard meaning
+/// any tone, so "ma0" is the same query as "ma".
+///
+/// # Arguments
+///
+/// * `query` - Pinyin with an optional trailing tone digit or tone mark
+///
+/// # Returns
+///
+/// A tuple of the pinyin without tone marks and the requested tone, if any
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::parse_tone_query;
+/// assert_eq!(parse_tone_query("ma"), ("ma".to_string(), None));
+/// assert_eq!(parse_tone_query("ma3"), ("ma".to_string(), Some(3)));
+/// assert_eq!(parse_tone_query("mǎ"), ("ma".to_string(), Some(3)));
+/// assert_eq!(parse_tone_query("ma0"), ("ma".to_string(), None));
+/// ```
+pub fn parse_tone_query(query: &str) -> (String, Option<u32>) {
+    let digit_tone = query
+        .chars()
+        .last()
+        .and_then(|c| c.to_digit(10))
+        .filter(|tone| (0..=5).contains(tone));
+
+    match digit_tone {
+        // A trailing 0 matches any tone
+        Some(0) => (strip_tones(&query[..query.len() - 1]), None),
+        Some(tone) => (strip_tones(&query[..query.len() - 1]), Some(tone)),
+        None => (strip_tones(query), marked_tone(query)),
+    }
+}
+
+/// Names the pitch contour of a tone number
+///
+/// # Arguments
+///
+/// * `tone` - The tone number (1-4, or 5 for the neutral tone)
+///
+/// # Returns
+///
+/// "high level" (1), "rising" (2), "dipping" (3), "falling" (4), "neutral" (5),
+/// or "unknown" for any other number
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::tone_contour;
+/// assert_eq!(tone_contour(3), "dipping");
+/// assert_eq!(tone_contour(0), "unknown");
+/// ```
+pub fn tone_contour(tone: u32) -> &'static str {
+    match tone {
+        1 => "high level",
+        2 => "rising",
+        3 => "dipping",
+        4 => "falling",
+        5 => "neutral",
+        _ => "unknown",
+    }
+}
+
+/// Classifies the consonant-vowel structure of a syllable
+///
+/// The onset, if any, is a consonant (C). The rime is classified by its
+/// spelling: every vowel letter is a vowel (V) and a final "n" or "ng" is one
+/// consonant, so "iu", "ui" and "un" count as written, not as their full forms
+/// "iou", "uei" and "uen". "er" is a single vowel, and the syllabic nasals
+/// "m", "n" and "ng" are a single consonant.
+///
+/// # Arguments
+///
+/// * `onset` - The onset of the syllable
+/// * `rime` - The rime of the syllable
+///
+/// # Returns
+///
+/// The structure, e.g. "CV" or "VVC", or "unknown" for the rime `None`
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{syllable_structure, HanziOnset, HanziRime};
+/// assert_eq!(syllable_structure(&HanziOnset::M, &HanziRime::A), "CV");
+/// assert_eq!(syllable_structure(&HanziOnset::None, &HanziRime::An), "VC");
+/// assert_eq!(syllable_structure(&HanziOnset::None, &HanziRime::Ai), "VV");
+/// ```
+pub fn syllable_structure(onset: &HanziOnset, rime: &HanziRime) -> &'static str {
+    use HanziRime::*;
+
+    // The structure without and with an onset
+    let (bare, with_onset) = match rime {
+        E | A | O | Er | I | U | V => ("V", "CV"),
+        Ei | Ai | Ou | Ao | Ie | Ia | Iu | Uo | Ua | Ui | Ve | Ue => ("VV", "CVV"),
+        Iao | Uai => ("VVV", "CVVV"),
+        En | An | Ong | Eng | Ang | In | Ing | Un => ("VC", "CVC"),
+        Ian | Iong | Iang | Uan | Uang => ("VVC", "CVVC"),
+        M | N | Ng => ("C", "CC"),
+        None => return "unknown",
+    };
+
+    if *onset == HanziOnset::None {
+        bare
+    } else {
+        with_onset
     }
 }
 
@@ -250,6 +697,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_longest_onset_match_prefers_two_letter_onsets() {
+        assert_eq!(longest_onset_match("chi"), (HanziOnset::Ch, 2));
+        assert_eq!(longest_onset_match("ci"), (HanziOnset::C, 1));
+        assert_eq!(longest_onset_match("shan"), (HanziOnset::Sh, 2));
+        assert_eq!(longest_onset_match("san"), (HanziOnset::S, 1));
+        assert_eq!(longest_onset_match("zhu"), (HanziOnset::Zh, 2));
+        assert_eq!(longest_onset_match("zu"), (HanziOnset::Z, 1));
+    }
+
     #[test]
     fn test_set_hanzi_onsets_refactored() {
         // Test the refactored set_hanzi_onsets function with specific cases
@@ -305,4 +762,240 @@ mod tests {
         assert_eq!(test_records[2].onset, HanziOnset::M); // "ma" -> M
         assert_eq!(test_records[3].onset, HanziOnset::None); // "an" -> None (vowel-initial)
     }
+
+    #[test]
+    fn test_syllabic_nasal_interjections() {
+        let mut test_records: Vec<HanziRecord> = ["ng", "n", "m", "hm", "hng"]
+            .iter()
+            .enumerate()
+            .map(|(i, pinyin)| HanziRecord {
+                frequency: i as u32 + 1,
+                simplified: "嗯".to_string(),
+                traditional: "嗯".to_string(),
+                pinyin: pinyin.to_string(),
+                pinyin_without_tone: pinyin.to_string(),
+                tone: 0,
+                onset: HanziOnset::None,
+                rime: HanziRime::None,
+            })
+            .collect();
+
+        set_hanzi_onsets(&mut test_records);
+        set_hanzi_rime(&mut test_records);
+
+        assert_eq!(test_records[0].onset, HanziOnset::None); // "ng"
+        assert_eq!(test_records[0].rime, HanziRime::Ng);
+        assert_eq!(test_records[1].onset, HanziOnset::None); // "n"
+        assert_eq!(test_records[1].rime, HanziRime::N);
+        assert_eq!(test_records[2].onset, HanziOnset::None); // "m"
+        assert_eq!(test_records[2].rime, HanziRime::M);
+        assert_eq!(test_records[3].onset, HanziOnset::H); // "hm"
+        assert_eq!(test_records[3].rime, HanziRime::M);
+        assert_eq!(test_records[4].onset, HanziOnset::H); // "hng"
+        assert_eq!(test_records[4].rime, HanziRime::Ng);
+    }
+
+    #[test]
+    fn test_refresh_phonetics_overwrites_stale_values() {
+        let mut records = vec![
+            HanziRecord::builder("中", "zhōng", 1).build(),
+            HanziRecord::builder("安", "ān", 1).build(),
+        ];
+        // Deliberately wrong values from an older analysis
+        records[0].onset = HanziOnset::Z;
+        records[0].rime = HanziRime::None;
+        records[1].onset = HanziOnset::W;
+        records[1].rime = HanziRime::En;
+
+        refresh_phonetics(&mut records);
+
+        assert_eq!(records[0].onset, HanziOnset::Zh);
+        assert_eq!(records[0].rime, HanziRime::Ong);
+        assert_eq!(records[1].onset, HanziOnset::None);
+        assert_eq!(records[1].rime, HanziRime::An);
+    }
+
+    #[test]
+    fn test_set_hanzi_onsets_with_candidates() {
+        let mut records = vec![
+            HanziRecord::builder("中", "zhōng", 1).build(),
+            HanziRecord::builder("吃", "chī", 1).build(),
+            HanziRecord::builder("马", "mǎ", 3).build(),
+        ];
+
+        set_hanzi_onsets_with_candidates(&mut records, ONSET_CANDIDATES);
+        assert_eq!(records[0].onset, HanziOnset::Zh);
+        assert_eq!(records[1].onset, HanziOnset::Ch);
+
+        // Without retroflex onsets, zh and ch merge into z and c
+        let merged: Vec<&str> = ONSET_CANDIDATES
+            .iter()
+            .copied()
+            .filter(|onset| !matches!(*onset, "zh" | "ch" | "sh"))
+            .collect();
+        set_hanzi_onsets_with_candidates(&mut records, &merged);
+        assert_eq!(records[0].onset, HanziOnset::Z);
+        assert_eq!(records[1].onset, HanziOnset::C);
+        assert_eq!(records[2].onset, HanziOnset::M);
+
+        // Onsets missing from the list are not detected
+        set_hanzi_onsets_with_candidates(&mut records, &["zh"]);
+        assert_eq!(records[0].onset, HanziOnset::Zh);
+        assert_eq!(records[2].onset, HanziOnset::None);
+    }
+
+    #[test]
+    fn test_ue_after_j_q_x_y_n_l_is_ve() {
+        let mut records = vec![
+            HanziRecord::builder("觉", "jué", 2).build(),
+            HanziRecord::builder("略", "lüè", 4).build(),
+            HanziRecord::builder("略", "luè", 4).build(),
+        ];
+
+        refresh_phonetics(&mut records);
+
+        assert_eq!(records[0].onset, HanziOnset::J);
+        assert_eq!(records[0].rime, HanziRime::Ve);
+        assert_eq!(records[1].onset, HanziOnset::L);
+        assert_eq!(records[1].rime, HanziRime::Ve);
+        assert_eq!(records[2].onset, HanziOnset::L);
+        assert_eq!(records[2].rime, HanziRime::Ve);
+
+        assert_eq!(analyze_verbose(&records[0]).2, HanziRime::Ve);
+        assert_eq!(parse_syllable("jue"), Some((HanziOnset::J, HanziRime::Ve)));
+        assert_eq!(parse_syllable("lue"), Some((HanziOnset::L, HanziRime::Ve)));
+    }
+
+    #[test]
+    fn test_analyze_verbose() {
+        let record = HanziRecord {
+            frequency: 1,
+            simplified: "知".to_string(),
+            traditional: "知".to_string(),
+            pinyin: "zhī".to_string(),
+            pinyin_without_tone: "zhi".to_string(),
+            tone: 1,
+            onset: HanziOnset::None,
+            rime: HanziRime::None,
+        };
+
+        let (onset, stripped_len, rime) = analyze_verbose(&record);
+
+        // "zh" must be stripped as a whole, not only "z"
+        assert_eq!(onset, HanziOnset::Zh);
+        assert_eq!(stripped_len, 2);
+        assert_eq!(rime, HanziRime::I);
+    }
+
+    #[test]
+    fn test_valid_onsets_for() {
+        use HanziOnset::*;
+
+        let onsets = valid_onsets_for(&HanziRime::V);
+        assert_eq!(onsets, vec![N, L, J, Q, X, Y]);
+        assert!(!onsets.contains(&B) && !onsets.contains(&P));
+
+        let onsets = valid_onsets_for(&HanziRime::Ong);
+        assert!(onsets.contains(&D) && onsets.contains(&Zh) && onsets.contains(&H));
+        assert!(!onsets.contains(&B) && !onsets.contains(&P));
+
+        // "a" forms a syllable on its own, so None is listed last
+        assert_eq!(
+            valid_onsets_for(&HanziRime::A).last(),
+            Some(&HanziOnset::None)
+        );
+        assert!(valid_onsets_for(&HanziRime::None).is_empty());
+    }
+
+    #[test]
+    fn test_is_known_pinyin() {
+        assert!(is_known_pinyin("zhuang"));
+        assert!(is_known_pinyin("qv")); // qv → qü, written "qu"
+        assert!(is_known_pinyin("nv"));
+        assert!(is_known_pinyin("ng"));
+        assert!(!is_known_pinyin("xq"));
+        assert!(!is_known_pinyin("buang"));
+        assert!(!is_known_pinyin(""));
+    }
+
+    #[test]
+    fn test_is_known_pinyin_matches_dataset() {
+        let records = read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv file");
+
+        for record in &records {
+            assert!(
+                is_known_pinyin(&record.pinyin_without_tone),
+                "'{}' should be a known pinyin",
+                record.pinyin_without_tone
+            );
+        }
+    }
+
+    #[test]
+    fn test_tone_contour() {
+        assert_eq!(tone_contour(1), "high level");
+        assert_eq!(tone_contour(2), "rising");
+        assert_eq!(tone_contour(3), "dipping");
+        assert_eq!(tone_contour(4), "falling");
+        assert_eq!(tone_contour(5), "neutral");
+        assert_eq!(tone_contour(0), "unknown");
+        assert_eq!(tone_contour(6), "unknown");
+    }
+
+    #[test]
+    fn test_parse_marked_syllable() {
+        assert_eq!(
+            parse_marked_syllable("zhōng"),
+            (HanziOnset::Zh, HanziRime::Ong, 1)
+        );
+        assert_eq!(
+            parse_marked_syllable("nǚ"),
+            (HanziOnset::N, HanziRime::V, 3)
+        );
+        // A syllable without a tone mark is in the neutral tone
+        assert_eq!(
+            parse_marked_syllable("ma"),
+            (HanziOnset::M, HanziRime::A, 5)
+        );
+        assert_eq!(
+            parse_marked_syllable("xq"),
+            (HanziOnset::None, HanziRime::None, 5)
+        );
+    }
+
+    #[test]
+    fn test_syllable_structure() {
+        let structure = |pinyin: &str| {
+            let (onset, rime) = parse_syllable(pinyin).unwrap();
+            syllable_structure(&onset, &rime)
+        };
+
+        assert_eq!(structure("ma"), "CV");
+        assert_eq!(structure("an"), "VC");
+        assert_eq!(structure("ai"), "VV");
+        assert_eq!(structure("zhong"), "CVC");
+        assert_eq!(structure("xiao"), "CVVV");
+        assert_eq!(structure("guang"), "CVVC");
+        assert_eq!(structure("er"), "V");
+        assert_eq!(structure("hng"), "CC");
+        assert_eq!(syllable_structure(&HanziOnset::None, &HanziRime::Ng), "C");
+        assert_eq!(
+            syllable_structure(&HanziOnset::M, &HanziRime::None),
+            "unknown"
+        );
+    }
+
+    #[test]
+    fn test_parse_tone_query() {
+        assert_eq!(parse_tone_query("ma"), ("ma".to_string(), None));
+        assert_eq!(parse_tone_query("ma3"), ("ma".to_string(), Some(3)));
+        assert_eq!(parse_tone_query("mǎ"), ("ma".to_string(), Some(3)));
+        assert_eq!(parse_tone_query("ma5"), ("ma".to_string(), Some(5)));
+        assert_eq!(parse_tone_query("nǚ"), ("nü".to_string(), Some(3)));
+        // Only 1-5 are tones; 0 is a wildcard, even after a tone mark
+        assert_eq!(parse_tone_query("ma0"), ("ma".to_string(), None));
+        assert_eq!(parse_tone_query("mǎ0"), ("ma".to_string(), None));
+        assert_eq!(parse_tone_query("ma7"), ("ma7".to_string(), None));
+        assert_eq!(parse_tone_query(""), ("".to_string(), None));
+    }
 }
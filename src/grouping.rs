@@ -4,9 +4,13 @@
 //! based on pinyin pronunciation and tones. It handles the organization and display
 //! of character collections for analysis purposes.
 
-use crate::analysis::set_hanzi_onsets;
-use crate::types::{HanziOnset, HanziRecord};
-use std::collections::HashMap;
+use crate::analysis::{
+    analyze_verbose, longest_onset_match, parse_syllable, set_hanzi_onsets, set_hanzi_rime,
+    syllable_structure, tone_contour,
+};
+use crate::pinyin::strip_tones;
+use crate::types::{HanziOnset, HanziRecord, HanziRime, ToneLookupError};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Groups Hanzi records by pinyin without tone marks
 ///
@@ -43,8 +47,54 @@ pub fn group_by_pinyin(
     records: &[HanziRecord],
     use_traditional: bool,
 ) -> Vec<(String, Vec<String>)> {
-    let mut pinyin_groups: HashMap<&str, Vec<&str>> = HashMap::new();
-    for record in records {
+    group_by_pinyin_ordered(records, use_traditional, TieBreak::Alphabetical)
+}
+
+/// Ordering applied to pinyin groups of equal size
+///
+/// Used by [`group_by_pinyin_ordered`] to decide the order of groups that
+/// contain the same number of characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    /// Equal-size groups are sorted alphabetically by pinyin
+    #[default]
+    Alphabetical,
+    /// Equal-size groups keep the order in which their first character appears
+    /// in the records (i.e. frequency order for the bundled dataset)
+    FirstSeen,
+}
+
+/// Groups Hanzi records by pinyin without tone marks with a selectable tie-break
+///
+/// Works like [`group_by_pinyin`], but lets the caller choose how groups with the
+/// same number of characters are ordered.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to group
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+/// * `tie_break` - Ordering of groups with equal character counts
+///
+/// # Returns
+///
+/// A vector of tuples of pinyin without tone and the characters with that pinyin,
+/// sorted by number of characters (descending) and then by `tie_break`
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziRecord, group_by_pinyin_ordered, TieBreak};
+/// # let records: Vec<HanziRecord> = vec![]; // Placeholder for actual records
+/// let grouped = group_by_pinyin_ordered(&records, false, TieBreak::FirstSeen);
+/// ```
+pub fn group_by_pinyin_ordered(
+    records: &[HanziRecord],
+    use_traditional: bool,
+    tie_break: TieBreak,
+) -> Vec<(String, Vec<String>)> {
+    // Each group remembers the index of its first record for the FirstSeen tie-break
+    let mut pinyin_groups: HashMap<&str, (usize, Vec<&str>)> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
         let character = if use_traditional {
             &record.traditional
         } else {
@@ -52,17 +102,24 @@ pub fn group_by_pinyin(
         };
         pinyin_groups
             .entry(&record.pinyin_without_tone)
-            .or_default()
+            .or_insert_with(|| (index, Vec::new()))
+            .1
             .push(character);
     }
 
-    // Sort by frequency (descending) and then by pinyin (ascending)
+    // Sort by frequency (descending) and then by the selected tie-break
     let mut sorted_pinyins: Vec<_> = pinyin_groups.iter().collect();
-    sorted_pinyins.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+    sorted_pinyins.sort_by(|a, b| {
+        let by_size = b.1 .1.len().cmp(&a.1 .1.len());
+        match tie_break {
+            TieBreak::Alphabetical => by_size.then(a.0.cmp(b.0)),
+            TieBreak::FirstSeen => by_size.then(a.1 .0.cmp(&b.1 .0)),
+        }
+    });
 
     sorted_pinyins
         .into_iter()
-        .map(|(pinyin, characters)| {
+        .map(|(pinyin, (_, characters))| {
             (
                 pinyin.to_string(),
                 characters.iter().map(|s| s.to_string()).collect(),
@@ -71,6 +128,302 @@ pub fn group_by_pinyin(
         .collect()
 }
 
+/// Incremental counterpart of [`group_by_pinyin`]
+///
+/// Records are added one at a time, so callers can stream them from a reader
+/// without collecting a `Vec<HanziRecord>` first. [`PinyinGrouper::finish`]
+/// returns the same groups, in the same order, as [`group_by_pinyin`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziRecord, PinyinGrouper};
+/// let mut grouper = PinyinGrouper::new(false); // Use simplified characters
+/// grouper.add(&HanziRecord::builder("马", "mǎ", 3).build());
+/// grouper.add(&HanziRecord::builder("妈", "mā", 1).build());
+/// assert_eq!(
+///     grouper.finish(),
+///     vec![("ma".to_string(), vec!["马".to_string(), "妈".to_string()])]
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PinyinGrouper {
+    use_traditional: bool,
+    pinyin_groups: HashMap<String, Vec<String>>,
+}
+
+impl PinyinGrouper {
+    /// Creates an empty grouper
+    ///
+    /// # Arguments
+    ///
+    /// * `use_traditional` - Whether to use traditional characters instead of simplified
+    pub fn new(use_traditional: bool) -> Self {
+        PinyinGrouper {
+            use_traditional,
+            pinyin_groups: HashMap::new(),
+        }
+    }
+
+    /// Adds the character of a record to the group of its pinyin without tone marks
+    pub fn add(&mut self, record: &HanziRecord) {
+        let character = if self.use_traditional {
+            &record.traditional
+        } else {
+            &record.simplified
+        };
+        self.pinyin_groups
+            .entry(record.pinyin_without_tone.clone())
+            .or_default()
+            .push(character.clone());
+    }
+
+    /// Returns the groups sorted by number of characters (descending) and then by pinyin
+    pub fn finish(self) -> Vec<(String, Vec<String>)> {
+        let mut grouped: Vec<(String, Vec<String>)> = self.pinyin_groups.into_iter().collect();
+        grouped.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+        grouped
+    }
+}
+
+/// Index of the characters of each onset, for repeated lookups
+///
+/// Built once from records whose onset is already set (e.g. by
+/// [`set_hanzi_onsets`]), the index answers "which characters have onset X"
+/// with a hash lookup instead of a scan over all records. Characters keep the
+/// order of the records.
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{set_hanzi_onsets, HanziOnset, HanziRecord, OnsetIndex};
+/// let mut records = vec![
+///     HanziRecord::builder("马", "mǎ", 3).traditional("馬").build(),
+///     HanziRecord::builder("妈", "mā", 1).traditional("媽").build(),
+/// ];
+/// set_hanzi_onsets(&mut records);
+/// let index = OnsetIndex::new(&records, false); // Use simplified characters
+/// assert_eq!(index.get(&HanziOnset::M), ["马", "妈"]);
+/// assert!(index.get(&HanziOnset::B).is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OnsetIndex {
+    onset_characters: HashMap<HanziOnset, Vec<String>>,
+}
+
+impl OnsetIndex {
+    /// Builds the index from analyzed records
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - A slice of HanziRecord with their onset set
+    /// * `use_traditional` - Whether to use traditional characters instead of simplified
+    pub fn new(records: &[HanziRecord], use_traditional: bool) -> Self {
+        let mut onset_characters: HashMap<HanziOnset, Vec<String>> = HashMap::new();
+        for record in records {
+            let character = if use_traditional {
+                &record.traditional
+            } else {
+                &record.simplified
+            };
+            onset_characters
+                .entry(record.onset.clone())
+                .or_default()
+                .push(character.clone());
+        }
+        OnsetIndex { onset_characters }
+    }
+
+    /// Returns the characters with an onset, or an empty slice if there are none
+    pub fn get(&self, onset: &HanziOnset) -> &[String] {
+        self.onset_characters
+            .get(onset)
+            .map_or(&[], |characters| characters.as_slice())
+    }
+}
+
+/// Returns every distinct pinyin without tone marks, sorted alphabetically
+///
+/// This is a lighter alternative to [`group_by_pinyin`] when only the set of
+/// syllables is needed (e.g. for building an autocomplete list).
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to collect pinyin from
+///
+/// # Returns
+///
+/// A sorted vector of unique `pinyin_without_tone` values
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziRecord, distinct_pinyin};
+/// # let records: Vec<HanziRecord> = vec![]; // Placeholder for actual records
+/// let syllables = distinct_pinyin(&records);
+/// // Result: ["a", "ai", "an", ...]
+/// ```
+pub fn distinct_pinyin(records: &[HanziRecord]) -> Vec<String> {
+    let mut pinyins: Vec<String> = records
+        .iter()
+        .map(|record| record.pinyin_without_tone.clone())
+        .collect();
+    pinyins.sort();
+    pinyins.dedup();
+    pinyins
+}
+
+/// Returns every distinct tone-marked pinyin in the records
+///
+/// Unlike [`distinct_pinyin`], tones are kept, so the result lists each
+/// syllable-tone combination that is attested in the data (e.g. "mā" and "mǎ"
+/// but not "má" if no character has it). Useful for a syllabary chart.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to scan
+///
+/// # Returns
+///
+/// A sorted vector of unique `pinyin` values
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{occurring_syllables, HanziRecord};
+/// let records: Vec<HanziRecord> = [("妈", "mā", 1), ("马", "mǎ", 3), ("码", "mǎ", 3)]
+///     .iter()
+///     .map(|&(character, pinyin, tone)| HanziRecord::builder(character, pinyin, tone).build())
+///     .collect();
+/// assert_eq!(occurring_syllables(&records), vec!["mā", "mǎ"]);
+/// ```
+pub fn occurring_syllables(records: &[HanziRecord]) -> Vec<String> {
+    let mut syllables: Vec<String> = records.iter().map(|record| record.pinyin.clone()).collect();
+    syllables.sort();
+    syllables.dedup();
+    syllables
+}
+
+/// Returns the records whose traditional form is not a simplified form in the data
+///
+/// Useful for traditional-first learners: these are the traditional
+/// characters that do not appear as the simplified form of any record, such
+/// as 馬 when the data has 马. Records whose two forms are the same are never
+/// returned.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to scan
+///
+/// # Returns
+///
+/// The matching records, in the order of `records`
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{traditional_only, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("马", "mǎ", 3).traditional("馬").build(),
+///     HanziRecord::builder("的", "de", 5).build(),
+/// ];
+/// let only = traditional_only(&records);
+/// assert_eq!(only.len(), 1);
+/// assert_eq!(only[0].traditional, "馬");
+/// ```
+pub fn traditional_only(records: &[HanziRecord]) -> Vec<&HanziRecord> {
+    let simplified: HashSet<&str> = records
+        .iter()
+        .map(|record| record.simplified.as_str())
+        .collect();
+    records
+        .iter()
+        .filter(|record| !simplified.contains(record.traditional.as_str()))
+        .collect()
+}
+
+/// Sorts pinyin groups by onset, then rime, in pinyin chart order
+///
+/// Each pinyin is split with [`parse_syllable`] and the groups are ordered by
+/// the `Ord` of [`HanziOnset`] and then of [`HanziRime`], so "ba" comes before
+/// "pa" and "ba" before "bo". Vowel-initial syllables have the onset `None`
+/// and come last; so do pinyin that cannot be analyzed. Ties are broken
+/// alphabetically by pinyin.
+///
+/// # Arguments
+///
+/// * `grouped_data` - The pinyin groups to sort in place
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::sort_by_onset_and_rime;
+/// let mut grouped: Vec<(String, Vec<String>)> = ["an", "pa", "bo", "ba"]
+///     .iter()
+///     .map(|pinyin| (pinyin.to_string(), Vec::new()))
+///     .collect();
+/// sort_by_onset_and_rime(&mut grouped);
+/// let order: Vec<&str> = grouped.iter().map(|(pinyin, _)| pinyin.as_str()).collect();
+/// assert_eq!(order, vec!["ba", "bo", "pa", "an"]);
+/// ```
+pub fn sort_by_onset_and_rime(grouped_data: &mut [(String, Vec<String>)]) {
+    grouped_data.sort_by_cached_key(|(pinyin, _)| {
+        let (onset, rime) = parse_syllable(pinyin).unwrap_or_default();
+        (onset, rime, pinyin.clone())
+    });
+}
+
+/// Column widths and folding behavior used by [`format_pinyin_output`]
+///
+/// The pinyin field is left-aligned in `pinyin_width` characters and the
+/// character count is right-aligned in `count_width` characters. Continuation
+/// lines produced by folding are indented to line up with the characters.
+///
+/// With `fold_exact` (the default), folding breaks after exactly `fold_size`
+/// characters, so a group one character over the width gets a continuation
+/// line holding a single character. Without it, a tail of at most a fifth of
+/// `fold_size` (rounded down) is kept on the line before instead, which may
+/// then be up to that much longer than `fold_size`. Characters are never split
+/// in either mode.
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::PinyinFormat;
+/// let format = PinyinFormat::default();
+/// assert_eq!(format.pinyin_width, 8);
+/// assert_eq!(format.count_width, 3);
+/// assert_eq!(format.indent_width(), 14);
+/// assert!(format.fold_exact);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinyinFormat {
+    pub pinyin_width: usize,
+    pub count_width: usize,
+    pub fold_exact: bool,
+}
+
+impl PinyinFormat {
+    /// Returns the indent of continuation lines
+    ///
+    /// This is the width of the `pinyin: count ` header, so that folded
+    /// characters line up with the first line of characters.
+    pub fn indent_width(&self) -> usize {
+        self.pinyin_width + 2 + self.count_width + 1
+    }
+}
+
+impl Default for PinyinFormat {
+    /// Returns the historical widths, 8 for pinyin and 3 for the count, with exact folding
+    fn default() -> Self {
+        PinyinFormat {
+            pinyin_width: 8,
+            count_width: 3,
+            fold_exact: true,
+        }
+    }
+}
+
 /// Formats pinyin grouping data for display with optional line folding
 ///
 /// Takes grouped pinyin data and formats it for display, with optional line folding
@@ -80,7 +433,8 @@ pub fn group_by_pinyin(
 ///
 /// * `grouped_data` - A slice of tuples containing pinyin and character vectors
 /// * `fold_size` - Optional width for line folding. If provided, long character lists
-///   will be folded to this width with continuation lines
+///   will be folded to this width with continuation lines; a width of 0 does not fold
+/// * `format` - Column widths of the pinyin and count fields
 ///
 /// # Returns
 ///
@@ -101,52 +455,55 @@ pub fn group_by_pinyin(
 ///
 /// # Formatting Details
 ///
-/// - Pinyin is left-aligned in an 8-character field
-/// - Character count is right-aligned in a 3-character field
-/// - Continuation lines are indented with 14 spaces to align with characters
+/// - Pinyin is left-aligned in a `format.pinyin_width` field (8 by default)
+/// - Character count is right-aligned in a `format.count_width` field (3 by default)
+/// - Continuation lines are indented by `format.indent_width()` spaces (14 by default)
+///   to align with characters
+/// - Lines break after exactly `fold_size` characters unless `format.fold_exact` is
+///   false, which keeps a short tail on the line before (see [`PinyinFormat`])
 pub fn format_pinyin_output(
     grouped_data: &[(String, Vec<String>)],
     fold_size: Option<usize>,
+    format: &PinyinFormat,
 ) -> Vec<String> {
     let mut output_lines = Vec::new();
+    let pinyin_width = format.pinyin_width;
+    let count_width = format.count_width;
+    let indent = " ".repeat(format.indent_width());
 
     for (pinyin, characters) in grouped_data {
         let char_list = characters.join("");
 
-        if let Some(fold_size) = fold_size {
-            if char_list.len() > fold_size {
-                // Fold long lines: first fold_size chars on the same line as count
-                let chars: Vec<char> = char_list.chars().collect();
-                let first_chunk: String = chars.iter().take(fold_size).collect();
-
-                output_lines.push(format!(
-                    "{:<8}: {:3} {}",
-                    pinyin,
-                    characters.len(),
-                    first_chunk
-                ));
-
-                // Remaining characters in chunks of fold_size
-                for chunk in chars
-                    .iter()
-                    .skip(fold_size)
-                    .collect::<Vec<_>>()
-                    .chunks(fold_size)
-                {
-                    let chunk_str: String = chunk.iter().map(|c| **c).collect();
-                    output_lines.push(format!("              {chunk_str}"));
+        if let Some(fold_size) = fold_size.filter(|&size| size > 0) {
+            // Count chars, not bytes, so that a character outside the BMP
+            // (e.g. "𠀀") is never split or counted as several characters
+            let chars: Vec<char> = char_list.chars().collect();
+            let mut chunks: Vec<String> = chars
+                .chunks(fold_size)
+                .map(|chunk| chunk.iter().collect())
+                .collect();
+            // Without fold_exact, a short tail stays on the line before it
+            let tolerance = if format.fold_exact { 0 } else { fold_size / 5 };
+            if chunks.len() > 1 && chunks[chunks.len() - 1].chars().count() <= tolerance {
+                let tail = chunks.pop().unwrap_or_default();
+                if let Some(last) = chunks.last_mut() {
+                    last.push_str(&tail);
                 }
-            } else {
-                output_lines.push(format!(
-                    "{:<8}: {:3} {}",
-                    pinyin,
-                    characters.len(),
-                    char_list
-                ));
+            }
+
+            // The first chunk goes on the same line as the count
+            output_lines.push(format!(
+                "{:<pinyin_width$}: {:count_width$} {}",
+                pinyin,
+                characters.len(),
+                chunks.first().map_or("", String::as_str)
+            ));
+            for chunk in chunks.iter().skip(1) {
+                output_lines.push(format!("{indent}{chunk}"));
             }
         } else {
             output_lines.push(format!(
-                "{:<8}: {:3} {}",
+                "{:<pinyin_width$}: {:count_width$} {}",
                 pinyin,
                 characters.len(),
                 char_list
@@ -157,630 +514,3491 @@ pub fn format_pinyin_output(
     output_lines
 }
 
-/// Groups Hanzi records by tone for a specific pinyin
+/// Formats pinyin grouping data showing at most `max_chars` characters per group
 ///
-/// Filters records by the target pinyin and groups them by tone.
-/// Returns None if no matching records are found.
+/// Like [`format_pinyin_output`] without folding, but a group with more than
+/// `max_chars` characters shows only the first `max_chars` of them, followed by
+/// a `…(+N)` marker with the number of characters left out. The count column
+/// still shows the full number of characters.
 ///
 /// # Arguments
 ///
-/// * `records` - A slice of HanziRecord to search through
-/// * `target_pinyin` - The pinyin (without tone) to filter by
-/// * `use_traditional` - Whether to use traditional characters instead of simplified
+/// * `grouped_data` - A slice of tuples containing pinyin and character vectors
+/// * `max_chars` - The maximum number of characters shown per group
+/// * `format` - Column widths of the pinyin and count fields
 ///
 /// # Returns
 ///
-/// An optional vector of tuples where each tuple contains:
-/// - The tone number (u32): 1-4 for standard tones, 5 for neutral tone
-/// - The pinyin with tone marks as a String
-/// - A vector of character strings for that tone
-///
-/// Returns `None` if no characters match the target pinyin.
-///
-/// # Tone Sorting
-///
-/// Results are sorted by tone number (1, 2, 3, 4, 5) in ascending order.
+/// A vector of formatted strings ready for display, one per group
 ///
 /// # Examples
 ///
 /// ```rust
-/// # use study_rust_hanzi::{HanziRecord, HanziOnset, HanziRime, group_by_tone};
-/// # let records = vec![]; // Placeholder for actual records
-/// if let Some(tone_groups) = group_by_tone(&records, "ma", false) {
-///     // tone_groups: [(1, "mā", vec!["妈"]), (3, "mǎ", vec!["马"]), ...]
-/// }
+/// # use study_rust_hanzi::{format_pinyin_output_truncated, PinyinFormat};
+/// let characters = ["机", "计", "记", "几", "己"].map(String::from).to_vec();
+/// let pinyin_data = vec![("ji".to_string(), characters)];
+/// let output = format_pinyin_output_truncated(&pinyin_data, 3, &PinyinFormat::default());
+/// assert_eq!(output, vec!["ji      :   5 机计记…(+2)"]);
 /// ```
-pub fn group_by_tone(
-    records: &[HanziRecord],
-    target_pinyin: &str,
-    use_traditional: bool,
-) -> Option<Vec<(u32, String, Vec<String>)>> {
-    let matching_records: Vec<_> = records
+pub fn format_pinyin_output_truncated(
+    grouped_data: &[(String, Vec<String>)],
+    max_chars: usize,
+    format: &PinyinFormat,
+) -> Vec<String> {
+    let pinyin_width = format.pinyin_width;
+    let count_width = format.count_width;
+
+    grouped_data
         .iter()
-        .filter(|record| record.pinyin_without_tone == target_pinyin)
-        .collect();
+        .map(|(pinyin, characters)| {
+            let shown = characters.len().min(max_chars);
+            let mut char_list = characters[..shown].join("");
+            if characters.len() > shown {
+                char_list.push_str(&format!("…(+{})", characters.len() - shown));
+            }
+            format!(
+                "{:<pinyin_width$}: {:count_width$} {}",
+                pinyin,
+                characters.len(),
+                char_list
+            )
+        })
+        .collect()
+}
 
-    if matching_records.is_empty() {
-        return None;
+/// Returns the lower of two frequency ranks, ignoring the unknown rank 0
+///
+/// 0 is returned only when both ranks are 0.
+fn min_known_rank(a: u32, b: u32) -> u32 {
+    match (a, b) {
+        (0, rank) | (rank, 0) => rank,
+        (a, b) => a.min(b),
     }
+}
 
-    let mut tone_groups: HashMap<u32, (Vec<&str>, &str)> = HashMap::new();
-    for record in matching_records {
+/// Returns the lowest frequency rank (most common character) of each pinyin
+///
+/// The unknown rank 0 is ignored when the pinyin has a record with a known
+/// rank, so a single unranked character does not make the pinyin look the
+/// most common.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to scan
+///
+/// # Returns
+///
+/// A map from pinyin without tone marks to the minimum non-zero `frequency`
+/// among its records, or 0 if every record has rank 0
+pub fn min_frequency_by_pinyin(records: &[HanziRecord]) -> HashMap<String, u32> {
+    let mut min_frequencies: HashMap<String, u32> = HashMap::new();
+    for record in records {
+        min_frequencies
+            .entry(record.pinyin_without_tone.clone())
+            .and_modify(|frequency| *frequency = min_known_rank(*frequency, record.frequency))
+            .or_insert(record.frequency);
+    }
+    min_frequencies
+}
+
+/// Returns the frequency rank of each character
+///
+/// Allows a constant-time frequency lookup by character. A character listed
+/// in several records (e.g. under different readings) maps to its lowest rank;
+/// the unknown rank 0 is used only if none of its records has a known rank.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to scan
+/// * `use_traditional` - Whether to key the map by traditional instead of simplified characters
+///
+/// # Returns
+///
+/// A map from character to the minimum non-zero `frequency` among its
+/// records, or 0 if every record has rank 0
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{frequency_map, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("长", "cháng", 2).traditional("長").frequency(200).build(),
+///     HanziRecord::builder("长", "zhǎng", 3).traditional("長").frequency(80).build(),
+/// ];
+/// assert_eq!(frequency_map(&records, false)["长"], 80);
+/// assert_eq!(frequency_map(&records, true)["長"], 80);
+/// ```
+pub fn frequency_map(records: &[HanziRecord], use_traditional: bool) -> HashMap<String, u32> {
+    let mut frequencies: HashMap<String, u32> = HashMap::new();
+    for record in records {
         let character = if use_traditional {
             &record.traditional
         } else {
             &record.simplified
         };
-        let entry = tone_groups
-            .entry(record.tone)
-            .or_insert_with(|| (Vec::new(), &record.pinyin));
-        entry.0.push(character);
+        frequencies
+            .entry(character.clone())
+            .and_modify(|frequency| *frequency = min_known_rank(*frequency, record.frequency))
+            .or_insert(record.frequency);
     }
-
-    // Sort by tone (1, 2, 3, 4, 5 for neutral tone)
-    let mut sorted_tones: Vec<_> = tone_groups.iter().collect();
-    sorted_tones.sort_by_key(|&(tone, _)| *tone);
-
-    Some(
-        sorted_tones
-            .into_iter()
-            .map(|(tone, (characters, pinyin))| {
-                (
-                    *tone,
-                    pinyin.to_string(),
-                    characters.iter().map(|s| s.to_string()).collect(),
-                )
-            })
-            .collect(),
-    )
+    frequencies
 }
 
-/// Formats tone grouping data for display
-///
-/// Takes grouped tone data and formats it for display. Each line shows the pinyin
-/// with tone marks followed by the corresponding characters for that tone.
+/// Returns the lowest and highest frequency rank of each pinyin
 ///
 /// # Arguments
 ///
-/// * `tone_groups` - A slice of tuples containing tone data where each tuple has:
-///   - Tone number (u32): 1-4 for standard tones, 5 for neutral tone
-///   - Pinyin with tone marks (String): e.g., "jī", "jí", "jǐ", "jì"
-///   - Character vector (`Vec<String>`): characters with that pinyin and tone
+/// * `records` - A slice of HanziRecord to scan
 ///
 /// # Returns
 ///
-/// A vector of formatted strings ready for display, one per tone group
-///
-/// # Output Format
-///
-/// Each line follows the pattern:
-/// ```text
-/// pinyin_with_tone: characters
-/// ```
+/// A map from pinyin without tone marks to the (minimum, maximum) `frequency`
+/// among its records
 ///
 /// # Examples
 ///
 /// ```rust
-/// # use study_rust_hanzi::format_tone_output;
-/// let tone_data = vec![
-///     (1, "mā".to_string(), vec!["妈".to_string()]),
-///     (3, "mǎ".to_string(), vec!["马".to_string(), "码".to_string()]),
+/// # use study_rust_hanzi::{frequency_range_by_pinyin, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("计", "jì", 4).frequency(601).build(),
+///     HanziRecord::builder("机", "jī", 1).frequency(5).build(),
 /// ];
-/// let output = format_tone_output(&tone_data);
-/// // Result: ["mā: 妈", "mǎ: 马码"]
+/// assert_eq!(frequency_range_by_pinyin(&records)["ji"], (5, 601));
 /// ```
-///
-/// # Usage with group_by_tone
-///
-/// This function is typically used in conjunction with [`group_by_tone`]:
-/// ```rust,no_run
-/// # use study_rust_hanzi::{group_by_tone, format_tone_output};
-/// # let records = vec![]; // Placeholder
-/// if let Some(tone_groups) = group_by_tone(&records, "ma", false) {
-///     let formatted = format_tone_output(&tone_groups);
-///     for line in formatted {
-///         println!("{}", line);
-///     }
-/// }
-/// ```
-pub fn format_tone_output(tone_groups: &[(u32, String, Vec<String>)]) -> Vec<String> {
-    tone_groups
-        .iter()
-        .map(|(_tone, pinyin, characters)| {
-            let char_list = characters.join("");
-            format!("{pinyin}: {char_list}")
-        })
-        .collect()
+pub fn frequency_range_by_pinyin(records: &[HanziRecord]) -> HashMap<String, (u32, u32)> {
+    let mut ranges: HashMap<String, (u32, u32)> = HashMap::new();
+    for record in records {
+        ranges
+            .entry(record.pinyin_without_tone.clone())
+            .and_modify(|(min, max)| {
+                *min = (*min).min(record.frequency);
+                *max = (*max).max(record.frequency);
+            })
+            .or_insert((record.frequency, record.frequency));
+    }
+    ranges
 }
 
-/// Groups Hanzi records by onset and returns count for each onset type
+/// Formats pinyin grouping data with the frequency rank range of each group
 ///
-/// This function first applies onset analysis to the given records using
-/// `analysis::set_hanzi_onsets()`, then counts the number of HanziRecord elements
-/// for each HanziOnset type. Returns a vector of tuples containing onset and count,
-/// sorted by count in descending order.
+/// Like [`format_pinyin_output`], but the last line of each group ends with
+/// `[rank MIN–MAX]`, the lowest and highest frequency rank of its characters.
+/// Groups missing from `ranges` get no range.
 ///
 /// # Arguments
 ///
-/// * `records` - A slice of HanziRecord to analyze and group
+/// * `grouped_data` - A slice of tuples containing pinyin and character vectors
+/// * `fold_size` - Optional width for line folding, as in [`format_pinyin_output`]
+/// * `format` - Column widths of the pinyin and count fields
+/// * `ranges` - The rank range of each pinyin, from [`frequency_range_by_pinyin`]
 ///
 /// # Returns
 ///
-/// An optional vector of tuples where each tuple contains:
-/// - The HanziOnset type
-/// - The count of records with that onset (u32)
-///
-/// Returns `None` if the input records slice is empty.
-/// The vector is sorted by count in descending order (most frequent onsets first).
+/// A vector of formatted strings ready for display
 ///
 /// # Examples
 ///
 /// ```rust
-/// # use study_rust_hanzi::{HanziRecord, HanziOnset, HanziRime, group_by_onset};
-/// # let records = vec![]; // Placeholder for actual records
-/// if let Some(onset_counts) = group_by_onset(&records) {
-///     // onset_counts: [(HanziOnset::N, 1500), (HanziOnset::L, 1200), ...]
-///     for (onset, count) in onset_counts {
-///         println!("{:?}: {}", onset, count);
-///     }
-/// }
+/// # use std::collections::HashMap;
+/// # use study_rust_hanzi::{format_pinyin_output_with_ranges, PinyinFormat};
+/// let pinyin_data = vec![("ji".to_string(), vec!["机".to_string(), "计".to_string()])];
+/// let ranges = HashMap::from([("ji".to_string(), (5, 601))]);
+/// let output =
+///     format_pinyin_output_with_ranges(&pinyin_data, None, &PinyinFormat::default(), &ranges);
+/// assert_eq!(output, vec!["ji      :   2 机计  [rank 5–601]"]);
 /// ```
-pub fn group_by_onset(records: &[HanziRecord]) -> Option<Vec<(HanziOnset, u32)>> {
-    if records.is_empty() {
-        return None;
-    }
-
-    // Create a mutable copy of records to apply onset analysis
-    let mut records_copy: Vec<HanziRecord> = records.to_vec();
-
-    // Apply onset analysis
-    set_hanzi_onsets(&mut records_copy);
-
-    // Count records by onset type
-    let mut onset_counts: HashMap<HanziOnset, u32> = HashMap::new();
-    for record in &records_copy {
-        *onset_counts.entry(record.onset.clone()).or_insert(0) += 1;
+pub fn format_pinyin_output_with_ranges(
+    grouped_data: &[(String, Vec<String>)],
+    fold_size: Option<usize>,
+    format: &PinyinFormat,
+    ranges: &HashMap<String, (u32, u32)>,
+) -> Vec<String> {
+    let mut output_lines = Vec::new();
+    for group in grouped_data {
+        let mut group_lines = format_pinyin_output(std::slice::from_ref(group), fold_size, format);
+        if let (Some((min, max)), Some(last_line)) = (ranges.get(&group.0), group_lines.last_mut())
+        {
+            last_line.push_str(&format!("  [rank {min}–{max}]"));
+        }
+        output_lines.extend(group_lines);
     }
 
-    // Convert to vector and sort by count in descending order
-    let mut result: Vec<(HanziOnset, u32)> = onset_counts.into_iter().collect();
-    result.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by count descending
-
-    Some(result)
+    output_lines
 }
 
-/// Formats onset grouping data for display
+/// Returns the most common character of every distinct pinyin
 ///
-/// Takes grouped onset data and formats it for display. Each line shows the onset
-/// type followed by the count of characters with that onset.
+/// Useful for a "one character per sound" primer. For each pinyin without tone
+/// marks, the record with the lowest frequency rank is chosen.
 ///
 /// # Arguments
 ///
-/// * `onset_counts` - A slice of tuples containing onset data where each tuple has:
-///   - HanziOnset: The onset type (e.g., HanziOnset::J, HanziOnset::M)
-///   - u32: The count of records with that onset
+/// * `records` - A slice of HanziRecord to scan
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
 ///
 /// # Returns
 ///
-/// A vector of formatted strings ready for display, one per onset group
-///
-/// # Output Format
+/// A vector of tuples where each tuple contains:
+/// - The pinyin without tone as a String
+/// - The most common character with that pinyin
+/// - Its frequency rank (u32)
 ///
-/// Each line follows the pattern:
-/// ```text
-/// onset_name: count
-/// ```
+/// sorted by frequency rank (ascending), then by pinyin
 ///
 /// # Examples
 ///
 /// ```rust
-/// # use study_rust_hanzi::{HanziOnset, format_onset_output};
-/// let onset_data = vec![
-///     (HanziOnset::J, 150),
-///     (HanziOnset::M, 120),
-///     (HanziOnset::None, 80),
-/// ];
-/// let output = format_onset_output(&onset_data);
-/// // Result: ["j: 150", "m: 120", "none: 80"]
-/// ```
-///
-/// # Usage with group_by_onset
-///
-/// This function is typically used in conjunction with [`group_by_onset`]:
-/// ```rust,no_run
-/// # use study_rust_hanzi::{group_by_onset, format_onset_output};
-/// # let records = vec![]; // Placeholder
-/// if let Some(onset_counts) = group_by_onset(&records) {
-///     let formatted = format_onset_output(&onset_counts);
-///     for line in formatted {
-///         println!("{}", line);
-///     }
-/// }
+/// # use study_rust_hanzi::{HanziRecord, representative_characters};
+/// # let records: Vec<HanziRecord> = vec![]; // Placeholder for actual records
+/// let primer = representative_characters(&records, false);
+/// // Result: [("de", "的", 1), ("yi", "一", 2), ...]
 /// ```
-pub fn format_onset_output(onset_counts: &[(HanziOnset, u32)]) -> Vec<String> {
-    onset_counts
-        .iter()
-        .map(|(onset, count)| {
-            let onset_name = onset.as_str();
-            format!("{onset_name}: {count}")
-        })
-        .collect()
+pub fn representative_characters(
+    records: &[HanziRecord],
+    use_traditional: bool,
+) -> Vec<(String, String, u32)> {
+    let mut result = pick_per_pinyin(records, use_traditional, |record, best| {
+        record.frequency < best.frequency
+    });
+
+    // Sort by frequency rank (ascending), then by pinyin
+    result.sort_by(|a, b| a.2.cmp(&b.2).then(a.0.cmp(&b.0)));
+
+    result
 }
 
-/// Groups Hanzi records by a specific onset and then by pinyin without tone marks
+/// Returns the rarest character of every distinct pinyin
 ///
-/// Takes a slice of HanziRecord, filters them by the specified onset, and groups them
-/// by their pinyin_without_tone field. Returns a vector of tuples containing the pinyin
-/// and a vector of characters. The results are sorted by frequency (descending) and then
-/// by pinyin (ascending).
+/// The counterpart of [`representative_characters`], useful for surfacing
+/// obscure characters for advanced study. For each pinyin without tone marks,
+/// the record with the highest frequency rank is chosen.
 ///
 /// # Arguments
 ///
-/// * `records` - A slice of HanziRecord to process
-/// * `target_onset` - The HanziOnset to filter by
+/// * `records` - A slice of HanziRecord to scan
 /// * `use_traditional` - Whether to use traditional characters instead of simplified
 ///
 /// # Returns
 ///
 /// A vector of tuples where each tuple contains:
 /// - The pinyin without tone as a String
-/// - A vector of character strings corresponding to that pinyin for the specified onset
+/// - The rarest character with that pinyin
+/// - Its frequency rank (u32)
 ///
-/// Returns `None` if no records match the specified onset.
-///
-/// # Sorting Order
-///
-/// Results are sorted by:
-/// 1. Number of characters (descending) - most common pinyin first
-/// 2. Pinyin alphabetically (ascending) - consistent ordering for same frequency
+/// sorted by frequency rank (descending), then by pinyin
 ///
 /// # Examples
 ///
 /// ```rust
-/// # use study_rust_hanzi::{HanziRecord, HanziOnset, HanziRime, group_by_onset_and_pinyin};
-/// # let records = vec![]; // Placeholder for actual records
-/// if let Some(grouped) = group_by_onset_and_pinyin(&records, &HanziOnset::J, false) {
-///     // grouped: [("ji", vec!["机", "计", "基"]), ("jia", vec!["家", "加"]), ...]
-///     for (pinyin, characters) in grouped {
-///         println!("{}: {}", pinyin, characters.join(""));
-///     }
-/// }
+/// # use study_rust_hanzi::{HanziRecord, rarest_characters};
+/// # let records: Vec<HanziRecord> = vec![]; // Placeholder for actual records
+/// let obscure = rarest_characters(&records, false);
+/// // Result: [("ji", "屐", 4991), ...]
 /// ```
-pub fn group_by_onset_and_pinyin(
+pub fn rarest_characters(
     records: &[HanziRecord],
-    target_onset: &HanziOnset,
     use_traditional: bool,
-) -> Option<Vec<(String, Vec<String>)>> {
-    // Create a mutable copy of records to apply onset analysis
-    let mut records_copy: Vec<HanziRecord> = records.to_vec();
-
-    // Apply onset analysis
-    set_hanzi_onsets(&mut records_copy);
+) -> Vec<(String, String, u32)> {
+    let mut result = pick_per_pinyin(records, use_traditional, |record, best| {
+        record.frequency > best.frequency
+    });
 
-    // Filter records by the target onset
-    let filtered_records: Vec<&HanziRecord> = records_copy
-        .iter()
-        .filter(|record| record.onset == *target_onset)
-        .collect();
+    // Sort by frequency rank (descending), then by pinyin
+    result.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
 
-    if filtered_records.is_empty() {
-        return None;
-    }
+    result
+}
 
-    // Group by pinyin_without_tone
-    let mut pinyin_groups: HashMap<&str, Vec<&str>> = HashMap::new();
-    for record in filtered_records {
-        let character = if use_traditional {
-            &record.traditional
-        } else {
-            &record.simplified
-        };
-        pinyin_groups
+/// Picks one record per pinyin, replacing the pick whenever `prefer(record, pick)` holds
+///
+/// Returns unsorted (pinyin, character, frequency rank) tuples.
+fn pick_per_pinyin(
+    records: &[HanziRecord],
+    use_traditional: bool,
+    prefer: impl Fn(&HanziRecord, &HanziRecord) -> bool,
+) -> Vec<(String, String, u32)> {
+    let mut picks: HashMap<&str, &HanziRecord> = HashMap::new();
+    for record in records {
+        picks
             .entry(&record.pinyin_without_tone)
-            .or_default()
-            .push(character);
+            .and_modify(|pick| {
+                if prefer(record, pick) {
+                    *pick = record;
+                }
+            })
+            .or_insert(record);
     }
 
-    // Convert to vector and sort
-    let mut result: Vec<(String, Vec<String>)> = pinyin_groups
+    picks
         .into_iter()
-        .map(|(pinyin, chars)| {
-            let mut chars: Vec<String> = chars.into_iter().map(|s| s.to_string()).collect();
-            chars.sort();
-            chars.dedup(); // Remove duplicates
-            (pinyin.to_string(), chars)
+        .map(|(pinyin, record)| {
+            let character = if use_traditional {
+                &record.traditional
+            } else {
+                &record.simplified
+            };
+            (pinyin.to_string(), character.to_string(), record.frequency)
         })
-        .collect();
-
-    // Sort by character count (descending) then by pinyin (ascending)
-    result.sort_by(|a, b| match b.1.len().cmp(&a.1.len()) {
-        std::cmp::Ordering::Equal => a.0.cmp(&b.0),
-        other => other,
-    });
+        .collect()
+}
 
-    Some(result)
+/// Which end of the frequency ranking [`top_characters`] takes characters from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RankDirection {
+    /// The most common characters, lowest rank first
+    #[default]
+    Most,
+    /// The least common characters, highest rank first
+    Least,
 }
 
-/// Formats onset-pinyin grouping data for display with optional line folding
+/// Returns the `n` most or least common characters
 ///
-/// Takes grouped onset-pinyin data and formats it for display. Each line shows the pinyin
-/// followed by the count and the characters with that pinyin for the specific onset.
-/// Uses the same alignment format as `format_pinyin_output` for consistency.
+/// Records are ordered by frequency rank, and the first `n` are taken from the
+/// top of the ranking with [`RankDirection::Most`] or from its bottom with
+/// [`RankDirection::Least`]. Passing a filtered subset (e.g. ranks 1-2000)
+/// gives the edge of that range. Records with the unknown rank 0 are left out.
 ///
 /// # Arguments
 ///
-/// * `pinyin_groups` - A slice of tuples containing pinyin grouping data where each tuple has:
-///   - `String`: The pinyin without tone marks (e.g., "ji", "ma")
-///   - `Vec<String>`: The vector of characters with that pinyin
-/// * `fold_size` - Optional width for line folding. If provided, long character lists
-///   will be folded to this width with continuation lines
+/// * `records` - A slice of HanziRecord to rank
+/// * `n` - The maximum number of characters to return
+/// * `direction` - Whether to take the most or the least common characters
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
 ///
 /// # Returns
 ///
-/// A vector of formatted strings ready for display, one per pinyin group
+/// (character, frequency rank) tuples, starting with the most common character
+/// for [`RankDirection::Most`] and with the least common one for
+/// [`RankDirection::Least`]
 ///
-/// # Output Format
+/// # Examples
 ///
-/// Without folding:
-/// ```text
-/// pinyin  : count characters_here
+/// ```rust
+/// # use study_rust_hanzi::{top_characters, HanziRecord, RankDirection};
+/// let records: Vec<HanziRecord> = [("的", 1), ("马", 601), ("一", 2)]
+///     .iter()
+///     .map(|&(character, rank)| HanziRecord::builder(character, "de", 5).frequency(rank).build())
+///     .collect();
+/// assert_eq!(
+///     top_characters(&records, 2, RankDirection::Most, false),
+///     vec![("的".to_string(), 1), ("一".to_string(), 2)]
+/// );
+/// assert_eq!(
+///     top_characters(&records, 1, RankDirection::Least, false),
+///     vec![("马".to_string(), 601)]
+/// );
 /// ```
+pub fn top_characters(
+    records: &[HanziRecord],
+    n: usize,
+    direction: RankDirection,
+    use_traditional: bool,
+) -> Vec<(String, u32)> {
+    let mut ranked: Vec<&HanziRecord> = records
+        .iter()
+        .filter(|record| record.frequency != 0)
+        .collect();
+    ranked.sort_by_key(|record| record.frequency);
+    if direction == RankDirection::Least {
+        ranked.reverse();
+    }
+
+    ranked
+        .into_iter()
+        .take(n)
+        .map(|record| {
+            let character = if use_traditional {
+                &record.traditional
+            } else {
+                &record.simplified
+            };
+            (character.clone(), record.frequency)
+        })
+        .collect()
+}
+
+/// Returns the pinyin that have characters in each of the tones 1-4
 ///
-/// With folding (fold_size = 10):
-/// ```text
-/// pinyin  : count first_10_ch
-///                 next_chars
-/// ```
+/// Useful for complete-paradigm practice. The neutral tone (5) and the unknown
+/// tone (0) are not required.
 ///
-/// # Formatting Details
+/// # Arguments
 ///
-/// - Pinyin is left-aligned in an 8-character field
-/// - Character count is right-aligned in a 3-character field
-/// - Continuation lines are indented with 14 spaces to align with characters
+/// * `records` - A slice of HanziRecord to scan
+///
+/// # Returns
+///
+/// A sorted vector of the pinyin without tone marks that have at least one
+/// character in every one of the tones 1, 2, 3 and 4
 ///
 /// # Examples
 ///
 /// ```rust
-/// # use study_rust_hanzi::format_onset_pinyin_output;
-/// let pinyin_data = vec![
-///     ("ji".to_string(), vec!["机".to_string(), "计".to_string(), "基".to_string()]),
-///     ("jia".to_string(), vec!["家".to_string(), "加".to_string()]),
-/// ];
-/// let output = format_onset_pinyin_output(&pinyin_data, None);
-/// assert_eq!(output[0], "ji      :   3 机计基");
-/// assert_eq!(output[1], "jia     :   2 家加");
+/// # use study_rust_hanzi::{full_paradigm_syllables, HanziRecord};
+/// let records: Vec<HanziRecord> = [("妈", "mā", 1), ("麻", "má", 2), ("马", "mǎ", 3), ("骂", "mà", 4), ("八", "bā", 1)]
+///     .iter()
+///     .map(|&(character, pinyin, tone)| HanziRecord::builder(character, pinyin, tone).build())
+///     .collect();
+/// assert_eq!(full_paradigm_syllables(&records), vec!["ma"]);
 /// ```
-pub fn format_onset_pinyin_output(
-    pinyin_groups: &[(String, Vec<String>)],
-    fold_size: Option<usize>,
-) -> Vec<String> {
-    let mut output_lines = Vec::new();
-
-    for (pinyin, characters) in pinyin_groups {
-        let char_list = characters.join("");
+pub fn full_paradigm_syllables(records: &[HanziRecord]) -> Vec<String> {
+    // Bit n-1 is set when tone n (1-4) occurs
+    let mut tone_masks: HashMap<&str, u8> = HashMap::new();
+    for record in records {
+        if (1..=4).contains(&record.tone) {
+            *tone_masks.entry(&record.pinyin_without_tone).or_insert(0) |= 1 << (record.tone - 1);
+        }
+    }
 
-        if let Some(fold_size) = fold_size {
-            if char_list.len() > fold_size {
-                // Fold long lines: first fold_size chars on the same line as count
-                let chars: Vec<char> = char_list.chars().collect();
-                let first_chunk: String = chars.iter().take(fold_size).collect();
-
-                output_lines.push(format!(
-                    "{:<8}: {:3} {}",
-                    pinyin,
-                    characters.len(),
-                    first_chunk
-                ));
-
-                // Remaining characters in chunks of fold_size
-                for chunk in chars
-                    .iter()
-                    .skip(fold_size)
-                    .collect::<Vec<_>>()
-                    .chunks(fold_size)
-                {
-                    let chunk_str: String = chunk.iter().map(|c| **c).collect();
-                    output_lines.push(format!("              {chunk_str}"));
+    let mut syllables: Vec<String> = tone_masks
+        .into_iter()
+        .filter(|&(_, mask)| mask == 0b1111)
+        .map(|(pinyin, _)| pinyin.to_string())
+        .collect();
+    syllables.sort();
+    syllables
+}
+
+/// Finds tone minimal pairs: syllables with the same onset and rime in different tones
+///
+/// Records are grouped by onset and rime (onset and rime analysis is run on a
+/// copy of the records), and every group whose characters span more than one
+/// tone is returned. For each tone the most common character (lowest frequency
+/// rank) is kept, which gives one contrast item per tone for drills. Records
+/// with the unknown tone (0) or an unanalyzable rime are ignored.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to scan
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// A vector of tuples where each tuple contains:
+/// - The pinyin without tone marks as a String
+/// - The (tone, character) pairs of the group, sorted by tone
+///
+/// sorted by pinyin
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{tone_minimal_pairs, HanziRecord};
+/// let records: Vec<HanziRecord> = [("机", "jī", 1), ("计", "jì", 4), ("八", "bā", 1)]
+///     .iter()
+///     .map(|&(character, pinyin, tone)| HanziRecord::builder(character, pinyin, tone).build())
+///     .collect();
+/// let pairs = tone_minimal_pairs(&records, false);
+/// assert_eq!(pairs, vec![("ji".to_string(), vec![(1, "机".to_string()), (4, "计".to_string())])]);
+/// ```
+pub fn tone_minimal_pairs(
+    records: &[HanziRecord],
+    use_traditional: bool,
+) -> Vec<(String, Vec<(u32, String)>)> {
+    // Create a mutable copy of records to apply onset and rime analysis
+    let mut records_copy: Vec<HanziRecord> = records.to_vec();
+    set_hanzi_onsets(&mut records_copy);
+    set_hanzi_rime(&mut records_copy);
+
+    // The most common record of every tone, per (onset, rime)
+    type ToneGroup<'a> = HashMap<u32, &'a HanziRecord>;
+    let mut groups: HashMap<(&HanziOnset, &HanziRime), ToneGroup> = HashMap::new();
+    for record in records_copy
+        .iter()
+        .filter(|record| record.tone != 0 && record.rime != HanziRime::None)
+    {
+        groups
+            .entry((&record.onset, &record.rime))
+            .or_default()
+            .entry(record.tone)
+            .and_modify(|best| {
+                if record.frequency < best.frequency {
+                    *best = record;
                 }
-            } else {
-                output_lines.push(format!(
-                    "{:<8}: {:3} {}",
-                    pinyin,
-                    characters.len(),
-                    char_list
-                ));
-            }
+            })
+            .or_insert(record);
+    }
+
+    let mut result: Vec<(String, Vec<(u32, String)>)> = groups
+        .into_values()
+        .filter(|tones| tones.len() > 1)
+        .map(|tones| {
+            let mut tones: Vec<(u32, &HanziRecord)> = tones.into_iter().collect();
+            tones.sort_by_key(|&(tone, _)| tone);
+            let pinyin = tones[0].1.pinyin_without_tone.clone();
+            let characters = tones
+                .into_iter()
+                .map(|(tone, record)| {
+                    let character = if use_traditional {
+                        &record.traditional
+                    } else {
+                        &record.simplified
+                    };
+                    (tone, character.to_string())
+                })
+                .collect();
+            (pinyin, characters)
+        })
+        .collect();
+
+    result.sort_by(|a, b| a.0.cmp(&b.0));
+    result
+}
+
+/// Quotes a string as a JSON string literal
+///
+/// The escapes used are also valid in a TOML basic string.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Exports the pinyin chart as JSON
+///
+/// Every record is analyzed with [`set_hanzi_onsets`] and [`set_hanzi_rime`],
+/// and the characters are collected per syllable and tone. Records whose rime
+/// cannot be analyzed are left out. The result is a single JSON object keyed
+/// by the pinyin without tone marks, with the syllables in onset and rime
+/// chart order:
+///
+/// ```json
+/// {"ma":{"onset":"m","rime":"a","tones":{"1":["妈"],"3":["马","码"]}}}
+/// ```
+///
+/// The onset of a vowel-initial syllable is `null`. Tones are keyed by their
+/// number, with `"5"` for the neutral tone and `"0"` for an unknown tone, and
+/// the characters of a tone keep the order of the records.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to export
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{export_chart_json, HanziRecord};
+/// let records: Vec<HanziRecord> = [("妈", "mā", 1), ("安", "ān", 1)]
+///     .iter()
+///     .map(|&(character, pinyin, tone)| HanziRecord::builder(character, pinyin, tone).build())
+///     .collect();
+/// assert_eq!(
+///     export_chart_json(&records, false),
+///     r#"{"ma":{"onset":"m","rime":"a","tones":{"1":["妈"]}},"an":{"onset":null,"rime":"an","tones":{"1":["安"]}}}"#
+/// );
+/// ```
+pub fn export_chart_json(records: &[HanziRecord], use_traditional: bool) -> String {
+    // Create a mutable copy of records to apply onset and rime analysis
+    let mut records_copy: Vec<HanziRecord> = records.to_vec();
+    set_hanzi_onsets(&mut records_copy);
+    set_hanzi_rime(&mut records_copy);
+
+    // Characters per tone, per syllable in chart order
+    type ToneCharacters<'a> = BTreeMap<u32, Vec<&'a str>>;
+    let mut chart: BTreeMap<(&HanziOnset, &HanziRime, &str), ToneCharacters> = BTreeMap::new();
+    for record in records_copy
+        .iter()
+        .filter(|record| record.rime != HanziRime::None)
+    {
+        let character = if use_traditional {
+            &record.traditional
         } else {
-            output_lines.push(format!(
-                "{:<8}: {:3} {}",
+            &record.simplified
+        };
+        chart
+            .entry((&record.onset, &record.rime, &record.pinyin_without_tone))
+            .or_default()
+            .entry(record.tone)
+            .or_default()
+            .push(character);
+    }
+
+    let syllables: Vec<String> = chart
+        .into_iter()
+        .map(|((onset, rime, pinyin), tones)| {
+            let onset = match onset {
+                HanziOnset::None => "null".to_string(),
+                onset => json_string(onset.as_str()),
+            };
+            let tones: Vec<String> = tones
+                .into_iter()
+                .map(|(tone, characters)| {
+                    let characters: Vec<String> = characters.into_iter().map(json_string).collect();
+                    format!("\"{tone}\":[{}]", characters.join(","))
+                })
+                .collect();
+            format!(
+                "{}:{{\"onset\":{onset},\"rime\":{},\"tones\":{{{}}}}}",
+                json_string(pinyin),
+                json_string(rime.as_str()),
+                tones.join(",")
+            )
+        })
+        .collect();
+
+    format!("{{{}}}", syllables.join(","))
+}
+
+/// Returns the ANSI color code for a frequency rank tier
+///
+/// - Ranks 1-500: green
+/// - Ranks 501-2000: yellow
+/// - Other ranks (including the unknown rank 0): no color
+fn frequency_tier_color(min_frequency: u32) -> Option<&'static str> {
+    match min_frequency {
+        1..=500 => Some("\x1b[32m"),
+        501..=2000 => Some("\x1b[33m"),
+        _ => None,
+    }
+}
+
+/// Formats pinyin grouping data like [`format_pinyin_output`], coloring the pinyin by frequency
+///
+/// The pinyin of each group is colored with ANSI escape codes according to the
+/// lowest frequency rank of the group (see [`min_frequency_by_pinyin`]):
+/// ranks 1-500 are green, ranks 501-2000 are yellow and rarer groups are left
+/// uncolored. Padding is kept outside the escape codes so columns stay aligned.
+///
+/// # Arguments
+///
+/// * `grouped_data` - A slice of tuples containing pinyin and character vectors
+/// * `fold_size` - Optional width for line folding
+/// * `format` - Column widths of the pinyin and count fields
+/// * `min_frequencies` - Lowest frequency rank of each pinyin
+/// * `colored` - Whether to emit escape codes. If false, the output is identical
+///   to [`format_pinyin_output`]
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display
+pub fn format_pinyin_output_colored(
+    grouped_data: &[(String, Vec<String>)],
+    fold_size: Option<usize>,
+    format: &PinyinFormat,
+    min_frequencies: &HashMap<String, u32>,
+    colored: bool,
+) -> Vec<String> {
+    if !colored {
+        return format_pinyin_output(grouped_data, fold_size, format);
+    }
+
+    let mut output_lines = Vec::new();
+    for group in grouped_data {
+        let mut group_lines = format_pinyin_output(std::slice::from_ref(group), fold_size, format);
+        let color = min_frequencies
+            .get(&group.0)
+            .and_then(|&frequency| frequency_tier_color(frequency));
+        if let (Some(color), Some(first_line)) = (color, group_lines.first_mut()) {
+            // The first line starts with the pinyin itself, followed by padding
+            let rest = &first_line[group.0.len()..];
+            *first_line = format!("{color}{}\x1b[0m{rest}", group.0);
+        }
+        output_lines.extend(group_lines);
+    }
+
+    output_lines
+}
+
+/// Formats pinyin grouping data as a compact pinyin-and-count table
+///
+/// Produces the same `pinyin : count` columns as [`format_pinyin_output`] but
+/// omits the character lists entirely, giving a compact overview.
+///
+/// # Arguments
+///
+/// * `grouped_data` - A slice of tuples containing pinyin and character vectors
+/// * `format` - Column widths of the pinyin and count fields
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per pinyin group
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{format_pinyin_counts, PinyinFormat};
+/// let pinyin_data = vec![("ji".to_string(), vec!["机".to_string(), "计".to_string()])];
+/// let output = format_pinyin_counts(&pinyin_data, &PinyinFormat::default());
+/// assert_eq!(output[0], "ji      :   2");
+/// ```
+pub fn format_pinyin_counts(
+    grouped_data: &[(String, Vec<String>)],
+    format: &PinyinFormat,
+) -> Vec<String> {
+    let pinyin_width = format.pinyin_width;
+    let count_width = format.count_width;
+    grouped_data
+        .iter()
+        .map(|(pinyin, characters)| {
+            format!(
+                "{:<pinyin_width$}: {:count_width$}",
                 pinyin,
-                characters.len(),
-                char_list
-            ));
+                characters.len()
+            )
+        })
+        .collect()
+}
+
+/// Returns the number of terminal columns a string occupies
+///
+/// East Asian wide characters (CJK ideographs, Hangul, fullwidth forms) take
+/// two columns, every other character one.
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match u32::from(c) {
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Formats pinyin grouping data as a table with box-drawing borders
+///
+/// The table has a header row and the columns Pinyin, Count and Characters.
+/// Column widths are computed from the contents, counting CJK characters as
+/// two terminal columns so that the borders line up.
+///
+/// # Arguments
+///
+/// * `grouped_data` - A slice of tuples containing pinyin and character vectors
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, including the border lines
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_pinyin_table;
+/// let pinyin_data = vec![("ji".to_string(), vec!["机".to_string(), "计".to_string()])];
+/// let table = format_pinyin_table(&pinyin_data);
+/// assert_eq!(table[0], "┌────────┬───────┬────────────┐");
+/// assert_eq!(table[1], "│ Pinyin │ Count │ Characters │");
+/// assert_eq!(table[3], "│ ji     │     2 │ 机计       │");
+/// assert_eq!(table[4], "└────────┴───────┴────────────┘");
+/// ```
+pub fn format_pinyin_table(grouped_data: &[(String, Vec<String>)]) -> Vec<String> {
+    const HEADERS: [&str; 3] = ["Pinyin", "Count", "Characters"];
+
+    let rows: Vec<[String; 3]> = grouped_data
+        .iter()
+        .map(|(pinyin, characters)| {
+            [
+                pinyin.clone(),
+                characters.len().to_string(),
+                characters.join(""),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(display_width);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
         }
     }
 
+    let border = |left: &str, middle: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+        format!("{left}{}{right}", segments.join(middle))
+    };
+    // The count column is right-aligned, the others left-aligned
+    let line = |cells: [&str; 3]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(column, (cell, width))| {
+                let padding = " ".repeat(width - display_width(cell));
+                if column == 1 {
+                    format!(" {padding}{cell} ")
+                } else {
+                    format!(" {cell}{padding} ")
+                }
+            })
+            .collect();
+        format!("│{}│", padded.join("│"))
+    };
+
+    let mut output_lines = vec![border("┌", "┬", "┐"), line(HEADERS)];
+    output_lines.push(border("├", "┼", "┤"));
+    for row in &rows {
+        output_lines.push(line([&row[0], &row[1], &row[2]]));
+    }
+    output_lines.push(border("└", "┴", "┘"));
+
     output_lines
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{HanziOnset, HanziRime};
+/// Quotes a CSV field, doubling any quotes inside it
+fn quote_csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
 
-    fn create_test_records() -> Vec<HanziRecord> {
-        vec![
-            HanziRecord {
-                frequency: 1,
-                simplified: "机".to_string(),
-                traditional: "機".to_string(),
-                pinyin: "jī".to_string(),
-                pinyin_without_tone: "ji".to_string(),
-                tone: 1,
-                onset: HanziOnset::J,
-                rime: HanziRime::I,
-            },
-            HanziRecord {
-                frequency: 2,
-                simplified: "计".to_string(),
-                traditional: "計".to_string(),
-                pinyin: "jì".to_string(),
-                pinyin_without_tone: "ji".to_string(),
-                tone: 4,
-                onset: HanziOnset::J,
-                rime: HanziRime::I,
-            },
-            HanziRecord {
-                frequency: 3,
-                simplified: "马".to_string(),
-                traditional: "馬".to_string(),
-                pinyin: "mǎ".to_string(),
-                pinyin_without_tone: "ma".to_string(),
-                tone: 3,
-                onset: HanziOnset::M,
-                rime: HanziRime::A,
-            },
-        ]
+/// Formats pinyin grouping data as CSV
+///
+/// The first line is the header `pinyin,count,characters`, followed by one
+/// record per pinyin. The characters field is always quoted; the pinyin field
+/// is quoted only when it contains a comma, a quote or a line break. Quotes
+/// inside a quoted field are doubled, following RFC 4180.
+///
+/// # Arguments
+///
+/// * `grouped_data` - A slice of tuples containing pinyin and character vectors
+///
+/// # Returns
+///
+/// A vector of CSV lines, starting with the header
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_pinyin_csv;
+/// let pinyin_data = vec![("ji".to_string(), vec!["机".to_string(), "计".to_string()])];
+/// let output = format_pinyin_csv(&pinyin_data);
+/// assert_eq!(output, vec!["pinyin,count,characters", "ji,2,\"机计\""]);
+/// ```
+pub fn format_pinyin_csv(grouped_data: &[(String, Vec<String>)]) -> Vec<String> {
+    let mut output_lines = vec!["pinyin,count,characters".to_string()];
+
+    for (pinyin, characters) in grouped_data {
+        let pinyin_field = if pinyin.contains([',', '"', '\n', '\r']) {
+            quote_csv_field(pinyin)
+        } else {
+            pinyin.clone()
+        };
+        output_lines.push(format!(
+            "{pinyin_field},{},{}",
+            characters.len(),
+            quote_csv_field(&characters.join(""))
+        ));
     }
 
-    #[test]
-    fn test_group_by_pinyin_simplified() {
-        let records = create_test_records();
-        let grouped = group_by_pinyin(&records, false);
+    output_lines
+}
 
-        // ji should come first as it has more characters than ma
-        assert_eq!(grouped[0].0, "ji");
-        assert_eq!(grouped[0].1, vec!["机", "计"]);
-        assert_eq!(grouped[1].0, "ma");
-        assert_eq!(grouped[1].1, vec!["马"]);
+/// Writes a pinyin as a TOML key, quoting it unless it is a bare key
+///
+/// Bare keys may only contain ASCII letters, digits, `_` and `-`, so a pinyin
+/// with "ü" must be quoted.
+fn toml_key(pinyin: &str) -> String {
+    let is_bare = !pinyin.is_empty()
+        && pinyin
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if is_bare {
+        pinyin.to_string()
+    } else {
+        json_string(pinyin)
     }
+}
 
-    #[test]
-    fn test_group_by_pinyin_traditional() {
-        let records = create_test_records();
-        let grouped = group_by_pinyin(&records, true);
+/// Formats pinyin grouping data as a TOML document
+///
+/// Each pinyin becomes a key whose value is the array of its characters, in
+/// the order of the grouping. Pinyin that are not valid bare keys, such as
+/// "nü", are written as quoted keys.
+///
+/// # Arguments
+///
+/// * `grouped_data` - A slice of tuples containing pinyin and character vectors
+///
+/// # Returns
+///
+/// A vector of TOML lines, one key-value pair per pinyin
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_pinyin_toml;
+/// let pinyin_data = vec![
+///     ("ji".to_string(), vec!["机".to_string(), "计".to_string()]),
+///     ("nü".to_string(), vec!["女".to_string()]),
+/// ];
+/// let output = format_pinyin_toml(&pinyin_data);
+/// assert_eq!(output, vec![r#"ji = ["机", "计"]"#, r#""nü" = ["女"]"#]);
+/// ```
+pub fn format_pinyin_toml(grouped_data: &[(String, Vec<String>)]) -> Vec<String> {
+    grouped_data
+        .iter()
+        .map(|(pinyin, characters)| {
+            let characters: Vec<String> = characters
+                .iter()
+                .map(|character| json_string(character))
+                .collect();
+            format!("{} = [{}]", toml_key(pinyin), characters.join(", "))
+        })
+        .collect()
+}
+
+/// Part of a syllable shown by [`format_pinyin_syllable_parts`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyllablePart {
+    /// The onset (initial consonant), e.g. `Zh`
+    Onset,
+    /// The rime (vowel and final consonant), e.g. `Ong`
+    Rime,
+}
+
+/// Formats pinyin grouping data with the onset or rime of each pinyin
+///
+/// Each pinyin is split by [`parse_syllable`] and the requested part is shown by
+/// its variant name in place of the character list. A pinyin that cannot be
+/// parsed shows `None`.
+///
+/// # Arguments
+///
+/// * `grouped_data` - A slice of tuples containing pinyin and character vectors
+/// * `part` - Which part of the syllable to show
+/// * `format` - Column width of the pinyin field
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per pinyin group
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{format_pinyin_syllable_parts, PinyinFormat, SyllablePart};
+/// let pinyin_data = vec![("zhong".to_string(), vec!["中".to_string()])];
+/// let format = PinyinFormat::default();
+/// let rimes = format_pinyin_syllable_parts(&pinyin_data, SyllablePart::Rime, &format);
+/// assert_eq!(rimes[0], "zhong   : Ong");
+/// let onsets = format_pinyin_syllable_parts(&pinyin_data, SyllablePart::Onset, &format);
+/// assert_eq!(onsets[0], "zhong   : Zh");
+/// ```
+pub fn format_pinyin_syllable_parts(
+    grouped_data: &[(String, Vec<String>)],
+    part: SyllablePart,
+    format: &PinyinFormat,
+) -> Vec<String> {
+    let pinyin_width = format.pinyin_width;
+    grouped_data
+        .iter()
+        .map(|(pinyin, _)| {
+            let (onset, rime) =
+                parse_syllable(pinyin).unwrap_or((HanziOnset::None, HanziRime::None));
+            let shown = match part {
+                SyllablePart::Onset => format!("{onset:?}"),
+                SyllablePart::Rime => format!("{rime:?}"),
+            };
+            format!("{pinyin:<pinyin_width$}: {shown}")
+        })
+        .collect()
+}
+
+/// Groups Hanzi records by tone for a specific pinyin
+///
+/// Filters records by the target pinyin and groups them by tone.
+/// Returns None if no matching records are found.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to search through
+/// * `target_pinyin` - The pinyin (without tone) to filter by
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// An optional vector of tuples where each tuple contains:
+/// - The tone number (u32): 1-4 for standard tones, 5 for neutral tone, 0 for unknown tone
+/// - The pinyin with tone marks as a String
+/// - A vector of character strings for that tone
+///
+/// Returns `None` if no characters match the target pinyin.
+///
+/// # Tone Sorting
+///
+/// Results are sorted by tone number (1, 2, 3, 4, 5) in ascending order.
+/// Records with an unknown tone (0) are kept in their own group and sorted last.
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziRecord, HanziOnset, HanziRime, group_by_tone};
+/// # let records = vec![]; // Placeholder for actual records
+/// if let Some(tone_groups) = group_by_tone(&records, "ma", false) {
+///     // tone_groups: [(1, "mā", vec!["妈"]), (3, "mǎ", vec!["马"]), ...]
+/// }
+/// ```
+pub fn group_by_tone(
+    records: &[HanziRecord],
+    target_pinyin: &str,
+    use_traditional: bool,
+) -> Option<Vec<(u32, String, Vec<String>)>> {
+    try_group_by_tone(records, target_pinyin, use_traditional).ok()
+}
+
+/// Groups Hanzi records by tone for a specific pinyin, failing if none match
+///
+/// This is the `Result`-returning counterpart of [`group_by_tone`], with the
+/// same grouping and tone sorting. When no record has the target pinyin it
+/// returns [`ToneLookupError::NotFound`] carrying that pinyin, so callers can
+/// report it without building their own message.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to search through
+/// * `target_pinyin` - The pinyin (without tone) to filter by, already normalized
+///   (e.g. "ü" rather than "v")
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// * `Ok(Vec<(u32, String, Vec<String>)>)` - The (tone, marked pinyin, characters) groups
+/// * `Err(ToneLookupError::NotFound)` - If no characters match the target pinyin
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{try_group_by_tone, HanziRecord, ToneLookupError};
+/// let records = vec![HanziRecord::builder("马", "mǎ", 3).build()];
+/// assert!(try_group_by_tone(&records, "ma", false).is_ok());
+/// assert_eq!(
+///     try_group_by_tone(&records, "mo", false),
+///     Err(ToneLookupError::NotFound { pinyin: "mo".to_string() })
+/// );
+/// ```
+pub fn try_group_by_tone(
+    records: &[HanziRecord],
+    target_pinyin: &str,
+    use_traditional: bool,
+) -> Result<Vec<(u32, String, Vec<String>)>, ToneLookupError> {
+    let matching_records: Vec<_> = records
+        .iter()
+        .filter(|record| record.pinyin_without_tone == target_pinyin)
+        .collect();
+
+    if matching_records.is_empty() {
+        return Err(ToneLookupError::NotFound {
+            pinyin: target_pinyin.to_string(),
+        });
+    }
+
+    let mut tone_groups: HashMap<u32, (Vec<&str>, &str)> = HashMap::new();
+    for record in matching_records {
+        let character = if use_traditional {
+            &record.traditional
+        } else {
+            &record.simplified
+        };
+        let entry = tone_groups
+            .entry(record.tone)
+            .or_insert_with(|| (Vec::new(), &record.pinyin));
+        entry.0.push(character);
+    }
+
+    // Sort by tone (1, 2, 3, 4, 5 for neutral tone, then 0 for unknown tone)
+    let mut sorted_tones: Vec<_> = tone_groups.iter().collect();
+    sorted_tones.sort_by_key(|&(tone, _)| tone_sort_key(*tone));
+
+    Ok(sorted_tones
+        .into_iter()
+        .map(|(tone, (characters, pinyin))| {
+            (
+                *tone,
+                pinyin.to_string(),
+                characters.iter().map(|s| s.to_string()).collect(),
+            )
+        })
+        .collect())
+}
+
+/// Groups Hanzi records by tone for a specific pinyin, ordered by frequency
+///
+/// Produces the same groups as [`group_by_tone`], ordered for spaced
+/// repetition: the characters of each tone are sorted by frequency rank (most
+/// common first), and the tone groups are sorted by the rank of their most
+/// common character rather than by tone number. Records with an unknown rank
+/// (0) come after all ranked records.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to search through
+/// * `target_pinyin` - The pinyin (without tone) to filter by
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// * `Some(Vec<(u32, String, Vec<String>)>)` - The (tone, marked pinyin, characters) groups
+/// * `None` - If no characters match the target pinyin
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{group_by_tone_by_frequency, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("妈", "mā", 1).frequency(900).build(),
+///     HanziRecord::builder("码", "mǎ", 3).frequency(1200).build(),
+///     HanziRecord::builder("马", "mǎ", 3).frequency(500).build(),
+/// ];
+/// let groups = group_by_tone_by_frequency(&records, "ma", false).unwrap();
+/// assert_eq!(groups[0], (3, "mǎ".to_string(), vec!["马".to_string(), "码".to_string()]));
+/// assert_eq!(groups[1].0, 1);
+/// ```
+pub fn group_by_tone_by_frequency(
+    records: &[HanziRecord],
+    target_pinyin: &str,
+    use_traditional: bool,
+) -> Option<Vec<(u32, String, Vec<String>)>> {
+    let mut matching_records: Vec<&HanziRecord> = records
+        .iter()
+        .filter(|record| record.pinyin_without_tone == target_pinyin)
+        .collect();
+    if matching_records.is_empty() {
+        return None;
+    }
+
+    // Most common first, with the unknown rank 0 last
+    matching_records.sort_by_key(|record| match record.frequency {
+        0 => u32::MAX,
+        rank => rank,
+    });
+
+    // Tone groups are created in order of their most common character
+    let mut tone_groups: Vec<(u32, String, Vec<String>)> = Vec::new();
+    for record in matching_records {
+        let character = if use_traditional {
+            &record.traditional
+        } else {
+            &record.simplified
+        };
+        match tone_groups
+            .iter_mut()
+            .find(|(tone, _, _)| *tone == record.tone)
+        {
+            Some((_, _, characters)) => characters.push(character.clone()),
+            None => tone_groups.push((record.tone, record.pinyin.clone(), vec![character.clone()])),
+        }
+    }
+
+    Some(tone_groups)
+}
+
+/// Lists homophones: characters sharing both pinyin and tone
+///
+/// Groups records by pinyin without tone marks together with the tone number and
+/// keeps only the groups containing more than one character. This is stricter than
+/// [`group_by_pinyin`], which ignores tones.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to search through
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// A vector of tuples where each tuple contains:
+/// - The pinyin with tone marks as a String
+/// - The tone number (u32)
+/// - A vector of the homophonous characters
+///
+/// # Sorting Order
+///
+/// Results are sorted by:
+/// 1. Number of characters (descending) - largest homophone groups first
+/// 2. Pinyin alphabetically (ascending), then tone (ascending)
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziRecord, HanziOnset, HanziRime, homophones};
+/// # let records = vec![]; // Placeholder for actual records
+/// let groups = homophones(&records, false);
+/// // Result: [("jì", 4, vec!["计", "记", "纪"]), ...]
+/// ```
+pub fn homophones(
+    records: &[HanziRecord],
+    use_traditional: bool,
+) -> Vec<(String, u32, Vec<String>)> {
+    let mut groups: HashMap<(&str, u32), (&str, Vec<&str>)> = HashMap::new();
+    for record in records {
+        let character = if use_traditional {
+            &record.traditional
+        } else {
+            &record.simplified
+        };
+        groups
+            .entry((&record.pinyin_without_tone, record.tone))
+            .or_insert_with(|| (&record.pinyin, Vec::new()))
+            .1
+            .push(character);
+    }
+
+    let mut result: Vec<(String, u32, Vec<String>)> = groups
+        .into_iter()
+        .filter(|(_, (_, characters))| characters.len() > 1)
+        .map(|((_, tone), (pinyin, characters))| {
+            (
+                pinyin.to_string(),
+                tone,
+                characters.iter().map(|s| s.to_string()).collect(),
+            )
+        })
+        .collect();
+
+    // Sort by group size (descending), then by pinyin and tone (ascending)
+    result.sort_by(|a, b| {
+        b.2.len()
+            .cmp(&a.2.len())
+            .then(a.0.cmp(&b.0))
+            .then(a.1.cmp(&b.1))
+    });
+
+    result
+}
+
+/// Returns the sort key of a tone number
+///
+/// Tones 1-5 sort in their natural order. The unknown tone (0) sorts after
+/// the neutral tone (5) so that it never precedes a real tone category.
+fn tone_sort_key(tone: u32) -> u32 {
+    if tone == 0 {
+        u32::MAX
+    } else {
+        tone
+    }
+}
+
+/// Formats tone grouping data for display
+///
+/// Takes grouped tone data and formats it for display. Each line shows the pinyin
+/// with tone marks followed by the corresponding characters for that tone.
+///
+/// # Arguments
+///
+/// * `tone_groups` - A slice of tuples containing tone data where each tuple has:
+///   - Tone number (u32): 1-4 for standard tones, 5 for neutral tone, 0 for unknown tone
+///   - Pinyin with tone marks (String): e.g., "jī", "jí", "jǐ", "jì"
+///   - Character vector (`Vec<String>`): characters with that pinyin and tone
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per tone group
+///
+/// # Output Format
+///
+/// Each line follows the pattern:
+/// ```text
+/// pinyin_with_tone: characters
+/// ```
+///
+/// Groups with an unknown tone (0) are labeled explicitly:
+/// ```text
+/// pinyin (unknown tone): characters
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_tone_output;
+/// let tone_data = vec![
+///     (1, "mā".to_string(), vec!["妈".to_string()]),
+///     (3, "mǎ".to_string(), vec!["马".to_string(), "码".to_string()]),
+/// ];
+/// let output = format_tone_output(&tone_data);
+/// // Result: ["mā: 妈", "mǎ: 马码"]
+/// ```
+///
+/// # Usage with group_by_tone
+///
+/// This function is typically used in conjunction with [`group_by_tone`]:
+/// ```rust,no_run
+/// # use study_rust_hanzi::{group_by_tone, format_tone_output};
+/// # let records = vec![]; // Placeholder
+/// if let Some(tone_groups) = group_by_tone(&records, "ma", false) {
+///     let formatted = format_tone_output(&tone_groups);
+///     for line in formatted {
+///         println!("{}", line);
+///     }
+/// }
+/// ```
+pub fn format_tone_output(tone_groups: &[(u32, String, Vec<String>)]) -> Vec<String> {
+    tone_groups
+        .iter()
+        .map(|(tone, pinyin, characters)| {
+            let char_list = characters.join("");
+            if *tone == 0 {
+                format!("{pinyin} (unknown tone): {char_list}")
+            } else {
+                format!("{pinyin}: {char_list}")
+            }
+        })
+        .collect()
+}
+
+/// Formats tone grouping data for display, labeling each tone with its contour
+///
+/// Like [`format_tone_output`], but the pinyin of each group is followed by the
+/// contour name from [`tone_contour`]. Groups with an unknown tone (0) keep the
+/// `(unknown tone)` label.
+///
+/// # Arguments
+///
+/// * `tone_groups` - A slice of (tone, marked pinyin, characters) tuples
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per tone group
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_tone_output_with_contour;
+/// let tone_data = vec![
+///     (1, "mā".to_string(), vec!["妈".to_string()]),
+///     (3, "mǎ".to_string(), vec!["马".to_string(), "码".to_string()]),
+/// ];
+/// let output = format_tone_output_with_contour(&tone_data);
+/// assert_eq!(output, vec!["mā (high level): 妈", "mǎ (dipping): 马码"]);
+/// ```
+pub fn format_tone_output_with_contour(tone_groups: &[(u32, String, Vec<String>)]) -> Vec<String> {
+    tone_groups
+        .iter()
+        .map(|(tone, pinyin, characters)| {
+            let char_list = characters.join("");
+            if *tone == 0 {
+                format!("{pinyin} (unknown tone): {char_list}")
+            } else {
+                format!("{pinyin} ({}): {char_list}", tone_contour(*tone))
+            }
+        })
+        .collect()
+}
+
+/// Formats tone grouping data for display with tone numbers instead of tone marks
+///
+/// Like [`format_tone_output`], but each group shows the toneless pinyin
+/// followed by its tone digit (e.g. `ma3` rather than `mǎ`), with `5` for the
+/// neutral tone. Groups with an unknown tone (0) show the toneless pinyin with
+/// the `(unknown tone)` label.
+///
+/// # Arguments
+///
+/// * `tone_groups` - A slice of (tone, marked pinyin, characters) tuples
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per tone group
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_tone_output_numbered;
+/// let tone_data = vec![
+///     (1, "mā".to_string(), vec!["妈".to_string()]),
+///     (3, "mǎ".to_string(), vec!["马".to_string(), "码".to_string()]),
+/// ];
+/// let output = format_tone_output_numbered(&tone_data);
+/// assert_eq!(output, vec!["ma1: 妈", "ma3: 马码"]);
+/// ```
+pub fn format_tone_output_numbered(tone_groups: &[(u32, String, Vec<String>)]) -> Vec<String> {
+    tone_groups
+        .iter()
+        .map(|(tone, pinyin, characters)| {
+            let char_list = characters.join("");
+            let toneless = strip_tones(pinyin);
+            if *tone == 0 {
+                format!("{toneless} (unknown tone): {char_list}")
+            } else {
+                format!("{toneless}{tone}: {char_list}")
+            }
+        })
+        .collect()
+}
+
+/// Formats tone grouping data for display with simplified and traditional forms paired
+///
+/// Takes the groups of [`group_by_tone`] for the same pinyin once with
+/// simplified and once with traditional characters, which list the same records
+/// in the same order. Each character is shown in its simplified form followed
+/// by the traditional form in parentheses, e.g. `马(馬)`; the parentheses are
+/// omitted when both forms are the same. The pairs are separated by spaces.
+///
+/// # Arguments
+///
+/// * `simplified_groups` - The (tone, marked pinyin, characters) groups with simplified characters
+/// * `traditional_groups` - The same groups with traditional characters
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per tone group
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_tone_output_paired;
+/// let simplified = vec![(3, "mǎ".to_string(), vec!["马".to_string(), "码".to_string()])];
+/// let traditional = vec![(3, "mǎ".to_string(), vec!["馬".to_string(), "碼".to_string()])];
+/// let output = format_tone_output_paired(&simplified, &traditional);
+/// assert_eq!(output, vec!["mǎ: 马(馬) 码(碼)"]);
+/// ```
+pub fn format_tone_output_paired(
+    simplified_groups: &[(u32, String, Vec<String>)],
+    traditional_groups: &[(u32, String, Vec<String>)],
+) -> Vec<String> {
+    simplified_groups
+        .iter()
+        .zip(traditional_groups)
+        .map(|((tone, pinyin, simplified), (_, _, traditional))| {
+            let char_list = simplified
+                .iter()
+                .zip(traditional)
+                .map(|(simplified, traditional)| {
+                    if simplified == traditional {
+                        simplified.clone()
+                    } else {
+                        format!("{simplified}({traditional})")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            if *tone == 0 {
+                format!("{pinyin} (unknown tone): {char_list}")
+            } else {
+                format!("{pinyin}: {char_list}")
+            }
+        })
+        .collect()
+}
+
+/// Formats tone grouping data as the tone-marked pinyin forms only
+///
+/// Joins the pinyin with tone marks of every tone group with single spaces,
+/// omitting the characters. This is useful for practicing the tonal variants
+/// of a syllable.
+///
+/// # Arguments
+///
+/// * `tone_groups` - A slice of tuples of tone number, pinyin with tone marks and characters
+///
+/// # Returns
+///
+/// The space-separated pinyin forms in the order of `tone_groups`
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_tone_pinyin_only;
+/// let tone_data = vec![
+///     (1, "mā".to_string(), vec!["妈".to_string()]),
+///     (3, "mǎ".to_string(), vec!["马".to_string()]),
+/// ];
+/// assert_eq!(format_tone_pinyin_only(&tone_data), "mā mǎ");
+/// ```
+pub fn format_tone_pinyin_only(tone_groups: &[(u32, String, Vec<String>)]) -> String {
+    tone_groups
+        .iter()
+        .map(|(_tone, pinyin, _characters)| pinyin.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Groups Hanzi records by onset and returns count for each onset type
+///
+/// This function first applies onset analysis to the given records using
+/// `analysis::set_hanzi_onsets()`, then counts the number of HanziRecord elements
+/// for each HanziOnset type. Returns a vector of tuples containing onset and count,
+/// sorted by count in descending order.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze and group
+///
+/// # Returns
+///
+/// An optional vector of tuples where each tuple contains:
+/// - The HanziOnset type
+/// - The count of records with that onset (u32)
+///
+/// Returns `None` if the input records slice is empty.
+/// The vector is sorted by count in descending order (most frequent onsets first).
+/// Onsets with equal counts follow the onset chart order of [`HanziOnset`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziRecord, HanziOnset, HanziRime, group_by_onset};
+/// # let records = vec![]; // Placeholder for actual records
+/// if let Some(onset_counts) = group_by_onset(&records) {
+///     // onset_counts: [(HanziOnset::N, 1500), (HanziOnset::L, 1200), ...]
+///     for (onset, count) in onset_counts {
+///         println!("{:?}: {}", onset, count);
+///     }
+/// }
+/// ```
+pub fn group_by_onset(records: &[HanziRecord]) -> Option<Vec<(HanziOnset, u32)>> {
+    if records.is_empty() {
+        return None;
+    }
+
+    // Create a mutable copy of records to apply onset analysis
+    let mut records_copy: Vec<HanziRecord> = records.to_vec();
+
+    // Apply onset analysis
+    set_hanzi_onsets(&mut records_copy);
+
+    // Count records by onset type
+    let mut onset_counts: HashMap<HanziOnset, u32> = HashMap::new();
+    for record in &records_copy {
+        *onset_counts.entry(record.onset.clone()).or_insert(0) += 1;
+    }
+
+    // Convert to vector and sort by count in descending order, then by onset chart order
+    let mut result: Vec<(HanziOnset, u32)> = onset_counts.into_iter().collect();
+    result.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    Some(result)
+}
+
+/// Adds every onset missing from onset counts with a count of 0
+///
+/// [`group_by_onset`] lists only the onsets that occur in the data. This
+/// completes its result to all onsets of [`HanziOnset::all`] for a full chart.
+/// The result keeps the order of [`group_by_onset`]: by count (descending),
+/// then by onset chart order, so the added onsets come last in chart order.
+///
+/// # Arguments
+///
+/// * `onset_counts` - A slice of (onset, count) tuples, as returned by [`group_by_onset`]
+///
+/// # Returns
+///
+/// The counts of all onsets, including `None`
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{include_empty_onsets, HanziOnset};
+/// let counts = include_empty_onsets(&[(HanziOnset::M, 2)]);
+/// assert_eq!(counts.len(), HanziOnset::all().len());
+/// assert_eq!(counts[0], (HanziOnset::M, 2));
+/// assert_eq!(counts[1], (HanziOnset::B, 0));
+/// ```
+pub fn include_empty_onsets(onset_counts: &[(HanziOnset, u32)]) -> Vec<(HanziOnset, u32)> {
+    let mut result = onset_counts.to_vec();
+    for onset in HanziOnset::all() {
+        if !result.iter().any(|(present, _)| present == onset) {
+            result.push((onset.clone(), 0));
+        }
+    }
+
+    // Sort by count in descending order, then by onset chart order
+    result.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    result
+}
+
+/// Lists the onsets that do not occur in the records
+///
+/// Helps verify that a dataset is complete: every onset of
+/// [`HanziOnset::all`], including `None` for vowel-initial syllables, that no
+/// record has after onset analysis is reported.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// The unused onsets in onset chart order; all onsets for empty input
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{unused_onsets, HanziOnset, HanziRecord};
+/// let records = vec![HanziRecord::builder("马", "mǎ", 3).build()];
+/// let unused = unused_onsets(&records);
+/// assert!(!unused.contains(&HanziOnset::M));
+/// assert_eq!(unused.len(), HanziOnset::all().len() - 1);
+/// ```
+pub fn unused_onsets(records: &[HanziRecord]) -> Vec<HanziOnset> {
+    let onset_counts = group_by_onset(records).unwrap_or_default();
+    HanziOnset::all()
+        .iter()
+        .filter(|onset| !onset_counts.iter().any(|(present, _)| present == *onset))
+        .cloned()
+        .collect()
+}
+
+/// Counts the characters affected by merging one onset into another
+///
+/// Quantifies the scope of a phonological merger, e.g. `Zh` into `Z` in
+/// Taiwanese Mandarin: every record whose pinyin has the onset `from` would
+/// change its onset. Onsets are analyzed as in [`group_by_onset`]. Merging an
+/// onset into itself affects no characters.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+/// * `from` - The onset that disappears in the merger
+/// * `into` - The onset it merges into
+///
+/// # Returns
+///
+/// The number of records with the onset `from`
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{merge_impact, HanziOnset, HanziRecord};
+/// let records: Vec<HanziRecord> = [("中", "zhōng", 1), ("字", "zì", 4), ("知", "zhī", 1)]
+///     .iter()
+///     .map(|&(character, pinyin, tone)| HanziRecord::builder(character, pinyin, tone).build())
+///     .collect();
+/// assert_eq!(merge_impact(&records, HanziOnset::Zh, HanziOnset::Z), 2);
+/// ```
+pub fn merge_impact(records: &[HanziRecord], from: HanziOnset, into: HanziOnset) -> u32 {
+    if from == into {
+        return 0;
+    }
+
+    records
+        .iter()
+        .filter(|record| longest_onset_match(&record.pinyin_without_tone).0 == from)
+        .count() as u32
+}
+
+/// Groups Hanzi records by rime and returns count for each rime type
+///
+/// This function first applies onset and rime analysis to the given records using
+/// `analysis::set_hanzi_onsets()` and `analysis::set_hanzi_rime()`, then counts the
+/// number of HanziRecord elements for each HanziRime type.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze and group
+///
+/// # Returns
+///
+/// An optional vector of tuples where each tuple contains:
+/// - The HanziRime type
+/// - The count of records with that rime (u32)
+///
+/// Returns `None` if the input records slice is empty.
+/// The vector is sorted by count in descending order (most frequent rimes first).
+/// Rimes with equal counts follow the rime chart order of [`HanziRime`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziRecord, group_by_rime};
+/// # let records: Vec<HanziRecord> = vec![]; // Placeholder for actual records
+/// if let Some(rime_counts) = group_by_rime(&records) {
+///     // rime_counts: [(HanziRime::I, 600), (HanziRime::U, 400), ...]
+///     for (rime, count) in rime_counts {
+///         println!("{:?}: {}", rime, count);
+///     }
+/// }
+/// ```
+pub fn group_by_rime(records: &[HanziRecord]) -> Option<Vec<(HanziRime, u32)>> {
+    if records.is_empty() {
+        return None;
+    }
+
+    // Create a mutable copy of records to apply onset and rime analysis
+    let mut records_copy: Vec<HanziRecord> = records.to_vec();
+    set_hanzi_onsets(&mut records_copy);
+    set_hanzi_rime(&mut records_copy);
+
+    // Count records by rime type
+    let mut rime_counts: HashMap<HanziRime, u32> = HashMap::new();
+    for record in &records_copy {
+        *rime_counts.entry(record.rime.clone()).or_insert(0) += 1;
+    }
+
+    // Convert to vector and sort by count in descending order, then by rime chart order
+    let mut result: Vec<(HanziRime, u32)> = rime_counts.into_iter().collect();
+    result.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    Some(result)
+}
+
+/// Counts the distinct syllables ending in each rime
+///
+/// Unlike [`group_by_rime`], which counts characters, this counts the unique
+/// toneless pinyin of each rime, so "mā", "mǎ" and "bā" add two syllables to
+/// the rime "a". Rimes are analyzed as in [`group_by_rime`].
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// (rime, syllable count) tuples sorted by count in descending order; rimes
+/// with equal counts follow the rime chart order of [`HanziRime`]
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{distinct_syllables_per_rime, HanziRecord, HanziRime};
+/// let records: Vec<HanziRecord> = [("妈", "mā", 1), ("马", "mǎ", 3), ("八", "bā", 1), ("米", "mǐ", 3)]
+///     .iter()
+///     .map(|&(character, pinyin, tone)| HanziRecord::builder(character, pinyin, tone).build())
+///     .collect();
+/// assert_eq!(
+///     distinct_syllables_per_rime(&records),
+///     vec![(HanziRime::A, 2), (HanziRime::I, 1)]
+/// );
+/// ```
+pub fn distinct_syllables_per_rime(records: &[HanziRecord]) -> Vec<(HanziRime, usize)> {
+    let mut syllables: HashMap<HanziRime, HashSet<&str>> = HashMap::new();
+    for record in records {
+        let (_, _, rime) = analyze_verbose(record);
+        syllables
+            .entry(rime)
+            .or_default()
+            .insert(&record.pinyin_without_tone);
+    }
+
+    let mut result: Vec<(HanziRime, usize)> = syllables
+        .into_iter()
+        .map(|(rime, syllables)| (rime, syllables.len()))
+        .collect();
+    result.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    result
+}
+
+/// Counts the distinct characters of each onset and tone
+///
+/// Onsets are analyzed as in [`group_by_onset`], and each simplified character
+/// is counted once per (onset, tone) pair, so a character with two records of
+/// the same syllable is not counted twice. This gives the cells of an onset ×
+/// tone heatmap; pairs without characters are absent.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// A map from (onset, tone) to the number of distinct characters
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{onset_tone_counts, HanziOnset, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("机", "jī", 1).build(),
+///     HanziRecord::builder("鸡", "jī", 1).build(),
+///     HanziRecord::builder("计", "jì", 4).build(),
+/// ];
+/// let counts = onset_tone_counts(&records);
+/// assert_eq!(counts[&(HanziOnset::J, 1)], 2);
+/// assert_eq!(counts[&(HanziOnset::J, 4)], 1);
+/// ```
+pub fn onset_tone_counts(records: &[HanziRecord]) -> HashMap<(HanziOnset, u32), u32> {
+    let mut characters: HashMap<(HanziOnset, u32), HashSet<&str>> = HashMap::new();
+    for record in records {
+        let (onset, _) = longest_onset_match(&record.pinyin_without_tone);
+        characters
+            .entry((onset, record.tone))
+            .or_default()
+            .insert(&record.simplified);
+    }
+
+    characters
+        .into_iter()
+        .map(|(key, characters)| (key, characters.len() as u32))
+        .collect()
+}
+
+/// Counts characters by tone number
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to count
+///
+/// # Returns
+///
+/// * `Some(Vec<(u32, u32)>)` - (tone, count) tuples in tone order (1, 2, 3, 4,
+///   5 for the neutral tone, then 0 for the unknown tone)
+/// * `None` - If the input slice is empty
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{count_by_tone, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("吗", "ma", 5).build(),
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+///     HanziRecord::builder("码", "mǎ", 3).build(),
+/// ];
+/// assert_eq!(count_by_tone(&records), Some(vec![(3, 2), (5, 1)]));
+/// ```
+pub fn count_by_tone(records: &[HanziRecord]) -> Option<Vec<(u32, u32)>> {
+    if records.is_empty() {
+        return None;
+    }
+
+    // The histogram is in numeric order, so 0 is moved after the neutral tone
+    let mut result: Vec<(u32, u32)> = tone_histogram(records).into_iter().collect();
+    result.sort_by_key(|&(tone, _)| tone_sort_key(tone));
+
+    Some(result)
+}
+
+/// Counts characters by tone number, in numeric order
+///
+/// This is the histogram of [`Summary::tone_histogram`](crate::Summary::tone_histogram),
+/// shared with [`count_by_tone`].
+pub(crate) fn tone_histogram(records: &[HanziRecord]) -> BTreeMap<u32, u32> {
+    let mut histogram: BTreeMap<u32, u32> = BTreeMap::new();
+    for record in records {
+        *histogram.entry(record.tone).or_insert(0) += 1;
+    }
+    histogram
+}
+
+/// Suggests a teaching order of onsets, most common first
+///
+/// The onsets are taken from [`group_by_onset`], so they are ordered by
+/// character count (descending), then by onset chart order. Onsets that do not
+/// occur in the records are left out. `None` (vowel-initial syllables) is
+/// kept, since it is taught like any other onset.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// The onsets in suggested learning order; empty for empty input
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{onset_learning_order, HanziOnset, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("八", "bā", 1).build(),
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+///     HanziRecord::builder("妈", "mā", 1).build(),
+/// ];
+/// assert_eq!(onset_learning_order(&records), vec![HanziOnset::M, HanziOnset::B]);
+/// ```
+pub fn onset_learning_order(records: &[HanziRecord]) -> Vec<HanziOnset> {
+    group_by_onset(records)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(onset, _)| onset)
+        .collect()
+}
+
+/// Suggests a teaching order of rimes, most common first
+///
+/// The rimes are taken from [`group_by_rime`], so they are ordered by
+/// character count (descending), then by rime chart order. Rimes that do not
+/// occur in the records are left out, and so is `None`, which only marks
+/// syllables that could not be analyzed.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// The rimes in suggested learning order; empty for empty input
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{rime_learning_order, HanziRecord, HanziRime};
+/// let records = vec![
+///     HanziRecord::builder("北", "běi", 3).build(),
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+///     HanziRecord::builder("八", "bā", 1).build(),
+/// ];
+/// assert_eq!(rime_learning_order(&records), vec![HanziRime::A, HanziRime::Ei]);
+/// ```
+pub fn rime_learning_order(records: &[HanziRecord]) -> Vec<HanziRime> {
+    group_by_rime(records)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(rime, _)| rime)
+        .filter(|rime| *rime != HanziRime::None)
+        .collect()
+}
+
+/// Counts characters by syllable structure
+///
+/// The onset and rime of every record are derived from its pinyin with
+/// [`analyze_verbose`] and classified with [`syllable_structure`]. Records
+/// whose rime cannot be analyzed are counted as "unknown".
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// (structure, count) tuples sorted by count (descending), then by structure;
+/// empty for empty input
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{group_by_structure, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+///     HanziRecord::builder("八", "bā", 1).build(),
+///     HanziRecord::builder("安", "ān", 1).build(),
+/// ];
+/// assert_eq!(group_by_structure(&records), vec![("CV", 2), ("VC", 1)]);
+/// ```
+pub fn group_by_structure(records: &[HanziRecord]) -> Vec<(&'static str, u32)> {
+    let mut structure_counts: HashMap<&'static str, u32> = HashMap::new();
+    for record in records {
+        let (onset, _, rime) = analyze_verbose(record);
+        *structure_counts
+            .entry(syllable_structure(&onset, &rime))
+            .or_insert(0) += 1;
+    }
+
+    // Sort by count in descending order, then by structure
+    let mut result: Vec<(&'static str, u32)> = structure_counts.into_iter().collect();
+    result.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    result
+}
+
+/// A syllable pattern of an optional onset and an optional rime
+///
+/// A field left as `None` is a wildcard matching any onset or rime, so a
+/// query with only an onset lists every syllable starting with it, and one
+/// with only a rime lists every onset combining with it. Note that
+/// `Some(HanziOnset::None)` is not a wildcard: it matches vowel-initial
+/// syllables only.
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziOnset, HanziRime, SyllableQuery};
+/// let query = SyllableQuery {
+///     onset: Some(HanziOnset::M),
+///     rime: None,
+/// };
+/// assert!(query.matches(&HanziOnset::M, &HanziRime::Ang));
+/// assert!(!query.matches(&HanziOnset::B, &HanziRime::Ang));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyllableQuery {
+    /// The onset to match, or `None` for any onset
+    pub onset: Option<HanziOnset>,
+    /// The rime to match, or `None` for any rime
+    pub rime: Option<HanziRime>,
+}
+
+impl SyllableQuery {
+    /// Returns whether a syllable's onset and rime match the query
+    pub fn matches(&self, onset: &HanziOnset, rime: &HanziRime) -> bool {
+        self.onset.as_ref().is_none_or(|wanted| wanted == onset)
+            && self.rime.as_ref().is_none_or(|wanted| wanted == rime)
+    }
+}
+
+/// Lists the characters of every syllable matching an onset and rime pattern
+///
+/// The onset and rime of every record are derived from its pinyin with
+/// [`analyze_verbose`] and checked against `query`. This generalizes
+/// [`group_by_onset_and_pinyin`] and [`group_by_rime_and_onset`]: either
+/// part of the syllable can be fixed or left open. Records whose rime cannot
+/// be analyzed never match.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to search through
+/// * `query` - The onset and rime to match
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// (pinyin without tone marks, characters) groups in onset and rime chart
+/// order, as sorted by [`sort_by_onset_and_rime`]; empty if nothing matches
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{search_syllables, HanziOnset, HanziRecord, SyllableQuery};
+/// let records: Vec<HanziRecord> = [("忙", "máng", 2), ("马", "mǎ", 3), ("八", "bā", 1)]
+///     .iter()
+///     .map(|&(character, pinyin, tone)| HanziRecord::builder(character, pinyin, tone).build())
+///     .collect();
+/// let query = SyllableQuery {
+///     onset: Some(HanziOnset::M),
+///     rime: None,
+/// };
+/// let groups = search_syllables(&records, &query, false);
+/// assert_eq!(
+///     groups,
+///     vec![
+///         ("ma".to_string(), vec!["马".to_string()]),
+///         ("mang".to_string(), vec!["忙".to_string()]),
+///     ]
+/// );
+/// ```
+pub fn search_syllables(
+    records: &[HanziRecord],
+    query: &SyllableQuery,
+    use_traditional: bool,
+) -> Vec<(String, Vec<String>)> {
+    let mut syllables: Vec<(String, Vec<String>)> = Vec::new();
+    for record in records {
+        let (onset, _, rime) = analyze_verbose(record);
+        if rime == HanziRime::None || !query.matches(&onset, &rime) {
+            continue;
+        }
+
+        let character = if use_traditional {
+            &record.traditional
+        } else {
+            &record.simplified
+        };
+        match syllables
+            .iter_mut()
+            .find(|(pinyin, _)| *pinyin == record.pinyin_without_tone)
+        {
+            Some((_, characters)) => characters.push(character.clone()),
+            None => syllables.push((record.pinyin_without_tone.clone(), vec![character.clone()])),
+        }
+    }
+
+    sort_by_onset_and_rime(&mut syllables);
+    syllables
+}
+
+/// Formats onset grouping data for display
+///
+/// Takes grouped onset data and formats it for display. Each line shows the onset
+/// type followed by the count of characters with that onset.
+///
+/// # Arguments
+///
+/// * `onset_counts` - A slice of tuples containing onset data where each tuple has:
+///   - HanziOnset: The onset type (e.g., HanziOnset::J, HanziOnset::M)
+///   - u32: The count of records with that onset
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per onset group
+///
+/// # Output Format
+///
+/// Each line follows the pattern:
+/// ```text
+/// onset_name: count
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziOnset, format_onset_output};
+/// let onset_data = vec![
+///     (HanziOnset::J, 150),
+///     (HanziOnset::M, 120),
+///     (HanziOnset::None, 80),
+/// ];
+/// let output = format_onset_output(&onset_data);
+/// // Result: ["j: 150", "m: 120", "none: 80"]
+/// ```
+///
+/// # Usage with group_by_onset
+///
+/// This function is typically used in conjunction with [`group_by_onset`]:
+/// ```rust,no_run
+/// # use study_rust_hanzi::{group_by_onset, format_onset_output};
+/// # let records = vec![]; // Placeholder
+/// if let Some(onset_counts) = group_by_onset(&records) {
+///     let formatted = format_onset_output(&onset_counts);
+///     for line in formatted {
+///         println!("{}", line);
+///     }
+/// }
+/// ```
+pub fn format_onset_output(onset_counts: &[(HanziOnset, u32)]) -> Vec<String> {
+    onset_counts
+        .iter()
+        .map(|(onset, count)| {
+            let onset_name = onset.as_str();
+            format!("{onset_name}: {count}")
+        })
+        .collect()
+}
+
+/// Formats rime grouping data for display
+///
+/// Each line shows the rime followed by the count of characters with that
+/// rime, in the same `rime: count` layout as [`format_onset_output`].
+///
+/// # Arguments
+///
+/// * `rime_counts` - A slice of (rime, count) tuples, as returned by [`group_by_rime`]
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per rime
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{format_rime_output, HanziRime};
+/// let output = format_rime_output(&[(HanziRime::Ang, 120), (HanziRime::V, 12)]);
+/// assert_eq!(output, vec!["ang: 120", "ü: 12"]);
+/// ```
+pub fn format_rime_output(rime_counts: &[(HanziRime, u32)]) -> Vec<String> {
+    rime_counts
+        .iter()
+        .map(|(rime, count)| format!("{}: {count}", rime.as_str()))
+        .collect()
+}
+
+/// Formats tone counts for display
+///
+/// Each line shows the tone number followed by the count of characters with
+/// that tone, in the same `tone: count` layout as [`format_onset_output`].
+///
+/// # Arguments
+///
+/// * `tone_counts` - A slice of (tone, count) tuples, as returned by [`count_by_tone`]
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per tone
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_tone_counts;
+/// assert_eq!(format_tone_counts(&[(1, 30), (5, 4)]), vec!["1: 30", "5: 4"]);
+/// ```
+pub fn format_tone_counts(tone_counts: &[(u32, u32)]) -> Vec<String> {
+    tone_counts
+        .iter()
+        .map(|(tone, count)| format!("{tone}: {count}"))
+        .collect()
+}
+
+/// Formats tone counts as a single distribution line
+///
+/// # Arguments
+///
+/// * `tone_counts` - A slice of (tone, count) tuples, as returned by [`count_by_tone`]
+///
+/// # Returns
+///
+/// A `Tones:` line with a `tone=count` pair per tone, in the given order
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_tone_distribution;
+/// let line = format_tone_distribution(&[(1, 1200), (2, 900), (5, 300)]);
+/// assert_eq!(line, "Tones: 1=1200 2=900 5=300");
+/// ```
+pub fn format_tone_distribution(tone_counts: &[(u32, u32)]) -> String {
+    let pairs: Vec<String> = tone_counts
+        .iter()
+        .map(|(tone, count)| format!("{tone}={count}"))
+        .collect();
+    format!("Tones: {}", pairs.join(" "))
+}
+
+/// Formats onset grouping data with each onset's share of the total
+///
+/// Like [`format_onset_output`], but each line also shows the count as a
+/// percentage of the sum of all counts, with one decimal place. An empty input
+/// gives an empty output, and counts that are all 0 are shown as 0.0%.
+///
+/// # Arguments
+///
+/// * `onset_counts` - A slice of (onset, count) tuples, as returned by [`group_by_onset`]
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per onset group
+///
+/// # Output Format
+///
+/// ```text
+/// onset_name: count (percent%)
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziOnset, format_onset_output_pct};
+/// let onset_data = vec![(HanziOnset::J, 3), (HanziOnset::M, 1)];
+/// let output = format_onset_output_pct(&onset_data);
+/// assert_eq!(output, vec!["j: 3 (75.0%)", "m: 1 (25.0%)"]);
+/// ```
+pub fn format_onset_output_pct(onset_counts: &[(HanziOnset, u32)]) -> Vec<String> {
+    let total: u32 = onset_counts.iter().map(|(_, count)| count).sum();
+
+    onset_counts
+        .iter()
+        .map(|(onset, count)| {
+            let onset_name = onset.as_str();
+            // Without any characters there is no share to divide
+            let percent = if total == 0 {
+                0.0
+            } else {
+                f64::from(*count) * 100.0 / f64::from(total)
+            };
+            format!("{onset_name}: {count} ({percent:.1}%)")
+        })
+        .collect()
+}
+
+/// Formats a one-line summary per onset with its count and sample characters
+///
+/// Onset analysis is run on a copy of the records, and each onset gets one
+/// line with its character count followed by its `samples` most common
+/// characters (lowest frequency rank first), separated by spaces. Onsets are
+/// ordered as in [`group_by_onset`]: by count (descending), then chart order.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to summarize
+/// * `samples` - The number of example characters shown per onset
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per onset
+///
+/// # Output Format
+///
+/// ```text
+/// onset_name: count  char char char
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{format_onset_summary, HanziRecord};
+/// let records: Vec<HanziRecord> = [("机", "jī", 1), ("马", "mǎ", 3), ("计", "jì", 4)]
+///     .iter()
+///     .enumerate()
+///     .map(|(rank, &(character, pinyin, tone))| {
+///         HanziRecord::builder(character, pinyin, tone)
+///             .frequency(rank as u32 + 1)
+///             .build()
+///     })
+///     .collect();
+/// let output = format_onset_summary(&records, 3, false);
+/// assert_eq!(output, vec!["j: 2  机 计", "m: 1  马"]);
+/// ```
+pub fn format_onset_summary(
+    records: &[HanziRecord],
+    samples: usize,
+    use_traditional: bool,
+) -> Vec<String> {
+    // Create a mutable copy of records to apply onset analysis
+    let mut records_copy: Vec<HanziRecord> = records.to_vec();
+    set_hanzi_onsets(&mut records_copy);
+    records_copy.sort_by_key(|record| record.frequency);
+
+    let mut onset_groups: HashMap<&HanziOnset, Vec<&str>> = HashMap::new();
+    for record in &records_copy {
+        let character = if use_traditional {
+            &record.traditional
+        } else {
+            &record.simplified
+        };
+        onset_groups
+            .entry(&record.onset)
+            .or_default()
+            .push(character);
+    }
+
+    // Sort by count in descending order, then by onset chart order
+    let mut sorted_groups: Vec<_> = onset_groups.into_iter().collect();
+    sorted_groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+
+    sorted_groups
+        .into_iter()
+        .map(|(onset, characters)| {
+            let sample_list: Vec<&str> = characters.iter().take(samples).copied().collect();
+            format!(
+                "{}: {}  {}",
+                onset.as_str(),
+                characters.len(),
+                sample_list.join(" ")
+            )
+        })
+        .collect()
+}
+
+/// Groups Hanzi records by a specific onset and then by pinyin without tone marks
+///
+/// Takes a slice of HanziRecord, filters them by the specified onset, and groups them
+/// by their pinyin_without_tone field. Returns a vector of tuples containing the pinyin
+/// and a vector of characters. The results are sorted by frequency (descending) and then
+/// by pinyin (ascending).
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to process
+/// * `target_onset` - The HanziOnset to filter by
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// A vector of tuples where each tuple contains:
+/// - The pinyin without tone as a String
+/// - A vector of character strings corresponding to that pinyin for the specified onset
+///
+/// Returns `None` if no records match the specified onset.
+///
+/// # Sorting Order
+///
+/// Results are sorted by:
+/// 1. Number of characters (descending) - most common pinyin first
+/// 2. Pinyin alphabetically (ascending) - consistent ordering for same frequency
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{HanziRecord, HanziOnset, HanziRime, group_by_onset_and_pinyin};
+/// # let records = vec![]; // Placeholder for actual records
+/// if let Some(grouped) = group_by_onset_and_pinyin(&records, &HanziOnset::J, false) {
+///     // grouped: [("ji", vec!["机", "计", "基"]), ("jia", vec!["家", "加"]), ...]
+///     for (pinyin, characters) in grouped {
+///         println!("{}: {}", pinyin, characters.join(""));
+///     }
+/// }
+/// ```
+pub fn group_by_onset_and_pinyin(
+    records: &[HanziRecord],
+    target_onset: &HanziOnset,
+    use_traditional: bool,
+) -> Option<Vec<(String, Vec<String>)>> {
+    // Create a mutable copy of records to apply onset analysis
+    let mut records_copy: Vec<HanziRecord> = records.to_vec();
+
+    // Apply onset analysis
+    set_hanzi_onsets(&mut records_copy);
+
+    // Filter records by the target onset
+    let filtered_records: Vec<&HanziRecord> = records_copy
+        .iter()
+        .filter(|record| record.onset == *target_onset)
+        .collect();
+
+    if filtered_records.is_empty() {
+        return None;
+    }
+
+    // Group by pinyin_without_tone
+    let mut pinyin_groups: HashMap<&str, Vec<&str>> = HashMap::new();
+    for record in filtered_records {
+        let character = if use_traditional {
+            &record.traditional
+        } else {
+            &record.simplified
+        };
+        pinyin_groups
+            .entry(&record.pinyin_without_tone)
+            .or_default()
+            .push(character);
+    }
+
+    // Convert to vector and sort
+    let mut result: Vec<(String, Vec<String>)> = pinyin_groups
+        .into_iter()
+        .map(|(pinyin, chars)| {
+            let mut chars: Vec<String> = chars.into_iter().map(|s| s.to_string()).collect();
+            chars.sort();
+            chars.dedup(); // Remove duplicates
+            (pinyin.to_string(), chars)
+        })
+        .collect();
+
+    // Sort by character count (descending) then by pinyin (ascending)
+    result.sort_by(|a, b| match b.1.len().cmp(&a.1.len()) {
+        std::cmp::Ordering::Equal => a.0.cmp(&b.0),
+        other => other,
+    });
+
+    Some(result)
+}
+
+/// Groups Hanzi records with a specific rime by their onset
+///
+/// This is the rime counterpart of [`group_by_onset_and_pinyin`]. Onset and rime
+/// analysis is applied to a copy of the records, which are then filtered by the
+/// target rime and grouped by onset. Characters keep the order of the records.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to process
+/// * `target_rime` - The HanziRime to filter by
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// A vector of tuples where each tuple contains:
+/// - The HanziOnset (`HanziOnset::None` for vowel-initial syllables)
+/// - A vector of character strings with that onset and the specified rime
+///
+/// Returns `None` if no records match the specified rime.
+///
+/// # Sorting Order
+///
+/// Results are sorted by:
+/// 1. Number of characters (descending) - most common onset first
+/// 2. Onset chart order - consistent ordering for same frequency
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{group_by_rime_and_onset, HanziOnset, HanziRecord, HanziRime};
+/// let records = vec![
+///     HanziRecord::builder("忙", "máng", 2).build(),
+///     HanziRecord::builder("帮", "bāng", 1).build(),
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+/// ];
+/// let grouped = group_by_rime_and_onset(&records, &HanziRime::Ang, false).unwrap();
+/// assert_eq!(grouped[0], (HanziOnset::B, vec!["帮".to_string()]));
+/// assert_eq!(grouped[1], (HanziOnset::M, vec!["忙".to_string()]));
+/// ```
+pub fn group_by_rime_and_onset(
+    records: &[HanziRecord],
+    target_rime: &HanziRime,
+    use_traditional: bool,
+) -> Option<Vec<(HanziOnset, Vec<String>)>> {
+    // Create a mutable copy of records to apply onset and rime analysis
+    let mut records_copy: Vec<HanziRecord> = records.to_vec();
+    set_hanzi_onsets(&mut records_copy);
+    set_hanzi_rime(&mut records_copy);
+
+    let mut onset_groups: HashMap<&HanziOnset, Vec<&str>> = HashMap::new();
+    for record in records_copy
+        .iter()
+        .filter(|record| record.rime == *target_rime)
+    {
+        let character = if use_traditional {
+            &record.traditional
+        } else {
+            &record.simplified
+        };
+        onset_groups
+            .entry(&record.onset)
+            .or_default()
+            .push(character);
+    }
+
+    if onset_groups.is_empty() {
+        return None;
+    }
+
+    let mut result: Vec<(HanziOnset, Vec<String>)> = onset_groups
+        .into_iter()
+        .map(|(onset, characters)| {
+            (
+                onset.clone(),
+                characters.iter().map(|s| s.to_string()).collect(),
+            )
+        })
+        .collect();
+
+    // Sort by character count (descending) then by onset chart order
+    result.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+
+    Some(result)
+}
+
+/// Collects the characters of one rime for each onset
+///
+/// Works like [`group_by_rime_and_onset`], but returns an empty vector instead
+/// of `None` when no record has the rime, so the result can be iterated
+/// directly.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to process
+/// * `rime` - The HanziRime to filter by
+/// * `use_traditional` - Whether to use traditional characters instead of simplified
+///
+/// # Returns
+///
+/// The onsets with their characters of that rime, most characters first
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{collect_by_rime, HanziOnset, HanziRecord, HanziRime};
+/// let records = vec![
+///     HanziRecord::builder("忙", "máng", 2).build(),
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+/// ];
+/// let collected = collect_by_rime(&records, &HanziRime::Ang, false);
+/// assert_eq!(collected, vec![(HanziOnset::M, vec!["忙".to_string()])]);
+/// assert!(collect_by_rime(&records, &HanziRime::Ong, false).is_empty());
+/// ```
+pub fn collect_by_rime(
+    records: &[HanziRecord],
+    rime: &HanziRime,
+    use_traditional: bool,
+) -> Vec<(HanziOnset, Vec<String>)> {
+    group_by_rime_and_onset(records, rime, use_traditional).unwrap_or_default()
+}
+
+/// Formats onset-pinyin grouping data for display with optional line folding
+///
+/// Takes grouped onset-pinyin data and formats it for display. Each line shows the pinyin
+/// followed by the count and the characters with that pinyin for the specific onset.
+/// Uses the same alignment format as `format_pinyin_output` for consistency.
+///
+/// # Arguments
+///
+/// * `pinyin_groups` - A slice of tuples containing pinyin grouping data where each tuple has:
+///   - `String`: The pinyin without tone marks (e.g., "ji", "ma")
+///   - `Vec<String>`: The vector of characters with that pinyin
+/// * `fold_size` - Optional width for line folding. If provided, long character lists
+///   will be folded to this width with continuation lines
+///
+/// # Returns
+///
+/// A vector of formatted strings ready for display, one per pinyin group
+///
+/// # Output Format
+///
+/// Without folding:
+/// ```text
+/// pinyin  : count characters_here
+/// ```
+///
+/// With folding (fold_size = 10):
+/// ```text
+/// pinyin  : count first_10_ch
+///                 next_chars
+/// ```
+///
+/// # Formatting Details
+///
+/// - Pinyin is left-aligned in an 8-character field
+/// - Character count is right-aligned in a 3-character field
+/// - Continuation lines are indented with 14 spaces to align with characters
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::format_onset_pinyin_output;
+/// let pinyin_data = vec![
+///     ("ji".to_string(), vec!["机".to_string(), "计".to_string(), "基".to_string()]),
+///     ("jia".to_string(), vec!["家".to_string(), "加".to_string()]),
+/// ];
+/// let output = format_onset_pinyin_output(&pinyin_data, None);
+/// assert_eq!(output[0], "ji      :   3 机计基");
+/// assert_eq!(output[1], "jia     :   2 家加");
+/// ```
+pub fn format_onset_pinyin_output(
+    pinyin_groups: &[(String, Vec<String>)],
+    fold_size: Option<usize>,
+) -> Vec<String> {
+    format_pinyin_output(pinyin_groups, fold_size, &PinyinFormat::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HanziOnset, HanziRime};
+
+    fn create_test_records() -> Vec<HanziRecord> {
+        vec![
+            HanziRecord {
+                frequency: 1,
+                simplified: "机".to_string(),
+                traditional: "機".to_string(),
+                pinyin: "jī".to_string(),
+                pinyin_without_tone: "ji".to_string(),
+                tone: 1,
+                onset: HanziOnset::J,
+                rime: HanziRime::I,
+            },
+            HanziRecord {
+                frequency: 2,
+                simplified: "计".to_string(),
+                traditional: "計".to_string(),
+                pinyin: "jì".to_string(),
+                pinyin_without_tone: "ji".to_string(),
+                tone: 4,
+                onset: HanziOnset::J,
+                rime: HanziRime::I,
+            },
+            HanziRecord {
+                frequency: 3,
+                simplified: "马".to_string(),
+                traditional: "馬".to_string(),
+                pinyin: "mǎ".to_string(),
+                pinyin_without_tone: "ma".to_string(),
+                tone: 3,
+                onset: HanziOnset::M,
+                rime: HanziRime::A,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_group_by_pinyin_simplified() {
+        let records = create_test_records();
+        let grouped = group_by_pinyin(&records, false);
+
+        // ji should come first as it has more characters than ma
+        assert_eq!(grouped[0].0, "ji");
+        assert_eq!(grouped[0].1, vec!["机", "计"]);
+        assert_eq!(grouped[1].0, "ma");
+        assert_eq!(grouped[1].1, vec!["马"]);
+    }
+
+    #[test]
+    fn test_group_by_pinyin_traditional() {
+        let records = create_test_records();
+        let grouped = group_by_pinyin(&records, true);
+
+        // Traditional characters should be used
+        assert_eq!(grouped[0].0, "ji");
+        assert_eq!(grouped[0].1, vec!["機", "計"]);
+        assert_eq!(grouped[1].0, "ma");
+        assert_eq!(grouped[1].1, vec!["馬"]);
+    }
+
+    #[test]
+    fn test_group_by_pinyin_ordered_tie_break() {
+        // "ma" appears before "ba" in the records, both groups have one character
+        let records = vec![
+            HanziRecord {
+                frequency: 1,
+                simplified: "马".to_string(),
+                traditional: "馬".to_string(),
+                pinyin: "mǎ".to_string(),
+                pinyin_without_tone: "ma".to_string(),
+                tone: 3,
+                onset: HanziOnset::M,
+                rime: HanziRime::A,
+            },
+            HanziRecord {
+                frequency: 2,
+                simplified: "八".to_string(),
+                traditional: "八".to_string(),
+                pinyin: "bā".to_string(),
+                pinyin_without_tone: "ba".to_string(),
+                tone: 1,
+                onset: HanziOnset::B,
+                rime: HanziRime::A,
+            },
+        ];
+
+        let alphabetical = group_by_pinyin_ordered(&records, false, TieBreak::Alphabetical);
+        assert_eq!(alphabetical[0].0, "ba");
+        assert_eq!(alphabetical[1].0, "ma");
+
+        let first_seen = group_by_pinyin_ordered(&records, false, TieBreak::FirstSeen);
+        assert_eq!(first_seen[0].0, "ma");
+        assert_eq!(first_seen[1].0, "ba");
+
+        // The default matches group_by_pinyin
+        let default = group_by_pinyin_ordered(&records, false, TieBreak::default());
+        assert_eq!(default, group_by_pinyin(&records, false));
+    }
+
+    #[test]
+    fn test_pinyin_grouper_matches_group_by_pinyin() {
+        let records = create_test_records();
+
+        for use_traditional in [false, true] {
+            let mut grouper = PinyinGrouper::new(use_traditional);
+            for record in &records {
+                grouper.add(record);
+            }
+            assert_eq!(grouper.finish(), group_by_pinyin(&records, use_traditional));
+        }
+
+        // An empty grouper yields no groups
+        assert!(PinyinGrouper::new(false).finish().is_empty());
+    }
+
+    #[test]
+    fn test_distinct_pinyin() {
+        let records = create_test_records();
+        let pinyins = distinct_pinyin(&records);
+
+        // "ji" appears twice in the records but only once in the result
+        assert_eq!(pinyins, vec!["ji", "ma"]);
+    }
+
+    #[test]
+    fn test_occurring_syllables() {
+        let mut records = create_test_records();
+        for (character, pinyin, tone) in [("记", "jì", 4), ("麻", "má", 2), ("吗", "ma", 5)] {
+            records.push(HanziRecord::builder(character, pinyin, tone).build());
+        }
+
+        let syllables = occurring_syllables(&records);
+
+        // Each marked pinyin appears once, even when several characters share it
+        let mut unique = syllables.clone();
+        unique.dedup();
+        assert_eq!(syllables, unique);
+        assert_eq!(syllables.iter().filter(|s| *s == "jì").count(), 1);
+
+        // The result is sorted
+        let mut sorted = syllables.clone();
+        sorted.sort();
+        assert_eq!(syllables, sorted);
+        assert!(syllables.contains(&"má".to_string()) && syllables.contains(&"ma".to_string()));
+    }
+
+    #[test]
+    fn test_sort_by_onset_and_rime() {
+        let mut grouped: Vec<(String, Vec<String>)> =
+            ["ai", "pa", "zhong", "ba", "bo", "xq", "zong", "bai"]
+                .iter()
+                .map(|pinyin| (pinyin.to_string(), vec!["字".to_string()]))
+                .collect();
+
+        sort_by_onset_and_rime(&mut grouped);
+
+        let order: Vec<&str> = grouped.iter().map(|(pinyin, _)| pinyin.as_str()).collect();
+        // b < p < z < zh in onset order, a < o < ai in rime order,
+        // and vowel-initial or unparsable pinyin come last
+        assert_eq!(
+            order,
+            vec!["ba", "bo", "bai", "pa", "zong", "zhong", "ai", "xq"]
+        );
+    }
+
+    #[test]
+    fn test_format_pinyin_output_no_fold() {
+        let test_data = vec![
+            ("ji".to_string(), vec!["机".to_string(), "计".to_string()]),
+            ("ma".to_string(), vec!["马".to_string()]),
+        ];
+
+        let output = format_pinyin_output(&test_data, None, &PinyinFormat::default());
+
+        assert_eq!(output.len(), 2);
+        assert!(output[0].contains("ji"));
+        assert!(output[0].contains("2"));
+        assert!(output[0].contains("机计"));
+        assert!(output[1].contains("ma"));
+        assert!(output[1].contains("1"));
+        assert!(output[1].contains("马"));
+    }
+
+    #[test]
+    fn test_format_pinyin_output_with_fold() {
+        let test_data = vec![(
+            "test".to_string(),
+            vec![
+                "一".to_string(),
+                "二".to_string(),
+                "三".to_string(),
+                "四".to_string(),
+                "五".to_string(),
+            ],
+        )];
+
+        let output = format_pinyin_output(&test_data, Some(3), &PinyinFormat::default());
+
+        // fold_size is 3, so first line should have 3 characters, remaining on next line
+        assert!(
+            output.len() >= 2,
+            "Should have at least 2 lines when folding"
+        );
+        assert!(output[0].contains("test"));
+        assert!(output[0].contains("5")); // character count
+        assert!(
+            !output[1].trim().is_empty(),
+            "Second line should contain remaining characters"
+        );
+    }
+
+    #[test]
+    fn test_format_pinyin_output_fold_supplementary_plane() {
+        // "𠀀" (U+20000) is one character of four bytes in UTF-8
+        let test_data = vec![(
+            "qiu".to_string(),
+            vec![
+                "𠀀".to_string(),
+                "丘".to_string(),
+                "𠀀".to_string(),
+                "秋".to_string(),
+            ],
+        )];
+
+        // Four characters fit in a fold width of 4, so there is no continuation line
+        let output = format_pinyin_output(&test_data, Some(4), &PinyinFormat::default());
+        assert_eq!(output, vec!["qiu     :   4 𠀀丘𠀀秋"]);
+
+        let output = format_pinyin_output(&test_data, Some(3), &PinyinFormat::default());
+        assert_eq!(output.len(), 2);
+        assert!(output[0].ends_with(" 𠀀丘𠀀"));
+        assert_eq!(output[1].trim(), "秋");
+    }
+
+    #[test]
+    fn test_format_pinyin_output_alignment() {
+        let test_data = vec![
+            ("ji".to_string(), vec!["机".to_string()]),
+            ("longpinyin".to_string(), vec!["长".to_string()]),
+        ];
+
+        let output = format_pinyin_output(&test_data, None, &PinyinFormat::default());
+
+        // Test output format
+        for line in &output {
+            assert!(line.contains(":"), "Each line should contain ':'");
+            let parts: Vec<&str> = line.split(':').collect();
+            assert_eq!(parts.len(), 2, "Each line should have exactly one ':'");
+        }
+    }
+
+    #[test]
+    fn test_format_pinyin_output_custom_widths() {
+        let test_data = vec![
+            ("ji".to_string(), vec!["机".to_string(), "计".to_string()]),
+            ("zhuang".to_string(), vec!["装".to_string()]),
+        ];
+        let format = PinyinFormat {
+            pinyin_width: 10,
+            count_width: 5,
+            ..PinyinFormat::default()
+        };
+
+        let output = format_pinyin_output(&test_data, None, &format);
+
+        assert_eq!(output[0], "ji        :     2 机计");
+        assert_eq!(output[1], "zhuang    :     1 装");
+    }
+
+    #[test]
+    fn test_format_pinyin_output_custom_widths_fold_indent() {
+        let test_data = vec![(
+            "ji".to_string(),
+            vec!["机".to_string(), "计".to_string(), "记".to_string()],
+        )];
+        let format = PinyinFormat {
+            pinyin_width: 10,
+            count_width: 5,
+            ..PinyinFormat::default()
+        };
+
+        let output = format_pinyin_output(&test_data, Some(2), &format);
+
+        // Continuation line must start exactly under the first character
+        assert_eq!(output[0], "ji        :     3 机计");
+        assert_eq!(
+            output[1],
+            format!("{}记", " ".repeat(format.indent_width()))
+        );
+        assert_eq!(output[0].find('机'), output[1].find('记'));
+    }
+
+    #[test]
+    fn test_format_pinyin_output_fold_exact() {
+        let characters: Vec<String> = "一二三四五六七八九十甲乙丙丁戊己庚辛壬癸子丑"
+            .chars()
+            .map(String::from)
+            .collect();
+        let test_data = vec![("yi".to_string(), characters)];
+        let chunk_sizes = |output: &[String]| -> Vec<usize> {
+            output
+                .iter()
+                .map(|line| line.chars().filter(|c| !c.is_ascii()).count())
+                .collect()
+        };
+
+        // 22 characters at width 10: exact folding leaves a tail of 2
+        let exact = format_pinyin_output(&test_data, Some(10), &PinyinFormat::default());
+        assert_eq!(chunk_sizes(&exact), vec![10, 10, 2]);
+
+        // The tail of 2 is within the tolerance of 10 / 5 and joins the line before
+        let tolerant = PinyinFormat {
+            fold_exact: false,
+            ..PinyinFormat::default()
+        };
+        let output = format_pinyin_output(&test_data, Some(10), &tolerant);
+        assert_eq!(chunk_sizes(&output), vec![10, 12]);
+        assert_eq!(output[1].trim_start(), "甲乙丙丁戊己庚辛壬癸子丑");
+
+        // A group just over the width is not folded at all
+        let output = format_pinyin_output(&test_data, Some(20), &tolerant);
+        assert_eq!(chunk_sizes(&output), vec![22]);
+
+        // A tail of 5 is over the tolerance of 17 / 5 = 3, so it keeps its line
+        let output = format_pinyin_output(&test_data, Some(17), &tolerant);
+        assert_eq!(chunk_sizes(&output), vec![17, 5]);
+    }
+
+    #[test]
+    fn test_format_pinyin_output_fold_zero() {
+        let test_data = vec![("ji".to_string(), vec!["机".to_string(), "计".to_string()])];
+
+        // A width of 0 does not fold instead of panicking
+        let format = PinyinFormat::default();
+        assert_eq!(
+            format_pinyin_output(&test_data, Some(0), &format),
+            format_pinyin_output(&test_data, None, &format)
+        );
+    }
+
+    #[test]
+    fn test_format_pinyin_counts() {
+        let test_data = vec![
+            ("ji".to_string(), vec!["机".to_string(), "计".to_string()]),
+            ("ma".to_string(), vec!["马".to_string()]),
+        ];
+
+        let output = format_pinyin_counts(&test_data, &PinyinFormat::default());
+
+        assert_eq!(output, vec!["ji      :   2", "ma      :   1"]);
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("ji"), 2);
+        assert_eq!(display_width("机计"), 4);
+        assert_eq!(display_width("nü"), 2);
+        assert_eq!(display_width(""), 0);
+    }
+
+    #[test]
+    fn test_format_pinyin_csv() {
+        let test_data = vec![
+            (
+                "ji".to_string(),
+                vec!["机".to_string(), "计".to_string(), "记".to_string()],
+            ),
+            ("ma".to_string(), vec!["马".to_string()]),
+        ];
+
+        let output = format_pinyin_csv(&test_data);
+
+        assert_eq!(output.len(), 3);
+        assert_eq!(output[0], "pinyin,count,characters");
+        assert_eq!(output[1], "ji,3,\"机计记\"");
+        assert_eq!(output[2], "ma,1,\"马\"");
+        assert_eq!(format_pinyin_csv(&[]), vec!["pinyin,count,characters"]);
+    }
+
+    #[test]
+    fn test_format_pinyin_csv_escapes_fields() {
+        let test_data = vec![("a,b".to_string(), vec!["\"".to_string(), ",".to_string()])];
+
+        let output = format_pinyin_csv(&test_data);
+
+        assert_eq!(output[1], "\"a,b\",2,\"\"\",\"");
+    }
+
+    #[test]
+    fn test_min_frequency_by_pinyin_ignores_unknown_rank() {
+        let mut records = create_test_records();
+        // An unranked reading must not make ma the most common pinyin
+        records.push(HanziRecord::builder("吗", "ma", 5).frequency(0).build());
+        records.push(HanziRecord::builder("么", "me", 5).frequency(0).build());
+
+        let min_frequencies = min_frequency_by_pinyin(&records);
+        assert_eq!(min_frequencies["ji"], 1);
+        assert_eq!(min_frequencies["ma"], 3);
+        // Only unranked records: the rank stays unknown
+        assert_eq!(min_frequencies["me"], 0);
+    }
+
+    #[test]
+    fn test_frequency_map() {
+        let mut records = create_test_records();
+        // A second reading of 马 with a higher rank
+        records.push(HanziRecord::builder("马", "ma", 5).frequency(900).build());
+
+        let frequencies = frequency_map(&records, false);
+        assert_eq!(frequencies.len(), 3);
+        assert_eq!(frequencies["马"], 3);
+        assert_eq!(frequencies["计"], 2);
+
+        let traditional = frequency_map(&records, true);
+        assert_eq!(traditional["馬"], 3);
+        assert!(!traditional.contains_key("计"));
+    }
+
+    #[test]
+    fn test_frequency_map_ignores_unknown_rank() {
+        let mut records = create_test_records();
+        // An unranked second reading of 机 must not hide its rank
+        records.push(HanziRecord::builder("机", "ji", 3).frequency(0).build());
+        records.push(HanziRecord::builder("吗", "ma", 5).frequency(0).build());
+
+        let frequencies = frequency_map(&records, false);
+        assert_eq!(frequencies["机"], 1);
+        assert_eq!(frequencies["吗"], 0);
+    }
+
+    #[test]
+    fn test_format_pinyin_table() {
+        let test_data = vec![
+            ("ji".to_string(), vec!["机".to_string(), "计".to_string()]),
+            ("ma".to_string(), vec!["马".to_string()]),
+        ];
+
+        let table = format_pinyin_table(&test_data);
+
+        // Border, header, separator, two rows, border
+        assert_eq!(table.len(), 6);
+        assert_eq!(table[0], "┌────────┬───────┬────────────┐");
+        assert_eq!(table[5], "└────────┴───────┴────────────┘");
+        assert_eq!(table[4], "│ ma     │     1 │ 马         │");
+
+        // Every line has the same display width
+        let width = display_width(&table[0]);
+        assert!(table.iter().all(|line| display_width(line) == width));
+    }
+
+    #[test]
+    fn test_format_pinyin_syllable_parts() {
+        let test_data = vec![
+            ("zhong".to_string(), vec!["中".to_string()]),
+            ("ng".to_string(), vec!["嗯".to_string()]),
+            ("xq".to_string(), vec!["?".to_string()]),
+        ];
+        let format = PinyinFormat::default();
+
+        let onsets = format_pinyin_syllable_parts(&test_data, SyllablePart::Onset, &format);
+        assert_eq!(
+            onsets,
+            vec!["zhong   : Zh", "ng      : None", "xq      : None"]
+        );
+
+        let rimes = format_pinyin_syllable_parts(&test_data, SyllablePart::Rime, &format);
+        assert_eq!(
+            rimes,
+            vec!["zhong   : Ong", "ng      : Ng", "xq      : None"]
+        );
+    }
+
+    #[test]
+    fn test_format_pinyin_output_colored() {
+        let mut records = create_test_records();
+        records[0].frequency = 100; // ji: common
+        records[1].frequency = 3000;
+        records[2].frequency = 1500; // ma: intermediate
+        let grouped = group_by_pinyin(&records, false);
+        let min_frequencies = min_frequency_by_pinyin(&records);
+        assert_eq!(min_frequencies["ji"], 100);
+
+        let format = PinyinFormat::default();
+        let plain = format_pinyin_output_colored(&grouped, None, &format, &min_frequencies, false);
+        assert!(plain.iter().all(|line| !line.contains('\x1b')));
+        assert_eq!(plain, format_pinyin_output(&grouped, None, &format));
+
+        let colored = format_pinyin_output_colored(&grouped, None, &format, &min_frequencies, true);
+        assert_eq!(colored[0], "\x1b[32mji\x1b[0m      :   2 机计");
+        assert_eq!(colored[1], "\x1b[33mma\x1b[0m      :   1 马");
+    }
+
+    #[test]
+    fn test_group_by_tone_found() {
+        let records = create_test_records();
+        let result = group_by_tone(&records, "ji", false);
+
+        assert!(result.is_some());
+        let tone_groups = result.unwrap();
+
+        // ji has 2 characters (tone 1: 机, tone 4: 计)
+        assert_eq!(tone_groups.len(), 2);
+
+        // Should be sorted in tone order
+        assert_eq!(tone_groups[0].0, 1); // tone 1
+        assert_eq!(tone_groups[0].2, vec!["机"]); // 机
+
+        assert_eq!(tone_groups[1].0, 4); // tone 4
+        assert_eq!(tone_groups[1].2, vec!["计"]); // 计
+    }
+
+    #[test]
+    fn test_group_by_tone_traditional() {
+        let records = create_test_records();
+        let result = group_by_tone(&records, "ji", true);
+
+        assert!(result.is_some());
+        let tone_groups = result.unwrap();
+
+        // Traditional characters should be used
+        assert_eq!(tone_groups[0].2, vec!["機"]); // 機 (traditional)
+        assert_eq!(tone_groups[1].2, vec!["計"]); // 計 (traditional)
+    }
+
+    #[test]
+    fn test_group_by_tone_not_found() {
+        let records = create_test_records();
+        let result = group_by_tone(&records, "nonexistent", false);
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_format_tone_output_with_contour() {
+        let test_data = vec![
+            (2, "má".to_string(), vec!["麻".to_string()]),
+            (4, "mà".to_string(), vec!["骂".to_string()]),
+            (5, "ma".to_string(), vec!["吗".to_string()]),
+            (0, "ma".to_string(), vec!["嘛".to_string()]),
+        ];
+
+        let output = format_tone_output_with_contour(&test_data);
+
+        assert_eq!(
+            output,
+            vec![
+                "má (rising): 麻",
+                "mà (falling): 骂",
+                "ma (neutral): 吗",
+                "ma (unknown tone): 嘛"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_tone_output_numbered() {
+        let test_data = vec![
+            (3, "mǎ".to_string(), vec!["马".to_string()]),
+            (3, "nǚ".to_string(), vec!["女".to_string()]),
+            (5, "ma".to_string(), vec!["吗".to_string()]),
+            (0, "ma".to_string(), vec!["嘛".to_string()]),
+        ];
+
+        let output = format_tone_output_numbered(&test_data);
+
+        assert_eq!(
+            output,
+            vec!["ma3: 马", "nü3: 女", "ma5: 吗", "ma (unknown tone): 嘛"]
+        );
+        assert!(!output.iter().any(|line| line.starts_with("mǎ:")));
+    }
+
+    #[test]
+    fn test_format_tone_output_paired() {
+        let records = vec![
+            HanziRecord::builder("机", "jī", 1)
+                .traditional("機")
+                .build(),
+            HanziRecord::builder("鸡", "jī", 1)
+                .traditional("雞")
+                .build(),
+            HanziRecord::builder("几", "jǐ", 3).build(),
+        ];
+        let simplified = group_by_tone(&records, "ji", false).unwrap();
+        let traditional = group_by_tone(&records, "ji", true).unwrap();
+
+        assert_eq!(
+            format_tone_output_paired(&simplified, &traditional),
+            vec!["jī: 机(機) 鸡(雞)", "jǐ: 几"]
+        );
+    }
+
+    #[test]
+    fn test_try_group_by_tone() {
+        let records = create_test_records();
+
+        let tone_groups = try_group_by_tone(&records, "ji", false).unwrap();
+        assert_eq!(Some(tone_groups), group_by_tone(&records, "ji", false));
+
+        let error = try_group_by_tone(&records, "nü", false).unwrap_err();
+        assert_eq!(
+            error,
+            ToneLookupError::NotFound {
+                pinyin: "nü".to_string()
+            }
+        );
+        assert!(error.to_string().contains("nü"));
+    }
+
+    #[test]
+    fn test_group_by_tone_pinyin_with_tone_marks() {
+        let records = create_test_records();
+        let result = group_by_tone(&records, "ji", false);
+
+        assert!(result.is_some());
+        let tone_groups = result.unwrap();
+
+        // pinyin should contain tone marks
+        assert_eq!(tone_groups[0].1, "jī"); // tone 1
+        assert_eq!(tone_groups[1].1, "jì"); // tone 4
+    }
+
+    #[test]
+    fn test_representative_characters() {
+        let records = create_test_records();
+        let representatives = representative_characters(&records, false);
+
+        // 机 (rank 1) is more common than 计 (rank 2)
+        assert_eq!(
+            representatives,
+            vec![
+                ("ji".to_string(), "机".to_string(), 1),
+                ("ma".to_string(), "马".to_string(), 3),
+            ]
+        );
+
+        let traditional = representative_characters(&records, true);
+        assert_eq!(traditional[0].1, "機");
+    }
+
+    #[test]
+    fn test_rarest_characters() {
+        let records = create_test_records();
+        let rarest = rarest_characters(&records, false);
+
+        // 计 (rank 2) is rarer than 机 (rank 1)
+        assert_eq!(
+            rarest,
+            vec![
+                ("ma".to_string(), "马".to_string(), 3),
+                ("ji".to_string(), "计".to_string(), 2),
+            ]
+        );
+
+        let traditional = rarest_characters(&records, true);
+        assert_eq!(traditional[1].1, "計");
+    }
+
+    #[test]
+    fn test_full_paradigm_syllables() {
+        // "ji" only has tones 1 and 4, "ma" has all four plus the neutral tone
+        let mut records = create_test_records();
+        for (character, pinyin, tone) in [
+            ("妈", "mā", 1),
+            ("麻", "má", 2),
+            ("骂", "mà", 4),
+            ("吗", "ma", 5),
+        ] {
+            records.push(HanziRecord::builder(character, pinyin, tone).build());
+        }
+
+        assert_eq!(full_paradigm_syllables(&records), vec!["ma"]);
+        assert!(full_paradigm_syllables(&create_test_records()).is_empty());
+    }
+
+    #[test]
+    fn test_tone_minimal_pairs() {
+        let mut records = create_test_records();
+        // A single-tone syllable forms no pair
+        records.push(HanziRecord::builder("八", "bā", 1).build());
 
-        // Traditional characters should be used
-        assert_eq!(grouped[0].0, "ji");
-        assert_eq!(grouped[0].1, vec!["機", "計"]);
-        assert_eq!(grouped[1].0, "ma");
-        assert_eq!(grouped[1].1, vec!["馬"]);
+        let pairs = tone_minimal_pairs(&records, false);
+        let ji = pairs
+            .iter()
+            .find(|(pinyin, _)| pinyin == "ji")
+            .expect("ji should form a minimal pair");
+        let tones: Vec<u32> = ji.1.iter().map(|(tone, _)| *tone).collect();
+        assert_eq!(tones, vec![1, 4]);
+        assert_eq!(ji.1[0].1, "机");
+        assert!(pairs.iter().all(|(pinyin, _)| pinyin != "ba"));
+
+        let traditional = tone_minimal_pairs(&records, true);
+        let ji = traditional
+            .iter()
+            .find(|(pinyin, _)| pinyin == "ji")
+            .unwrap();
+        assert_eq!(ji.1[0].1, "機");
     }
 
     #[test]
-    fn test_format_pinyin_output_no_fold() {
-        let test_data = vec![
-            ("ji".to_string(), vec!["机".to_string(), "计".to_string()]),
-            ("ma".to_string(), vec!["马".to_string()]),
-        ];
+    fn test_unused_onsets() {
+        let records = crate::io::read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv");
+        assert!(unused_onsets(&records).is_empty());
 
-        let output = format_pinyin_output(&test_data, None);
+        // Without any w-initial syllable, W is unused
+        let without_w: Vec<HanziRecord> = records
+            .into_iter()
+            .filter(|record| !record.pinyin_without_tone.starts_with('w'))
+            .collect();
+        assert_eq!(unused_onsets(&without_w), vec![HanziOnset::W]);
 
-        assert_eq!(output.len(), 2);
-        assert!(output[0].contains("ji"));
-        assert!(output[0].contains("2"));
-        assert!(output[0].contains("机计"));
-        assert!(output[1].contains("ma"));
-        assert!(output[1].contains("1"));
-        assert!(output[1].contains("马"));
+        assert_eq!(unused_onsets(&[]), HanziOnset::all());
     }
 
     #[test]
-    fn test_format_pinyin_output_with_fold() {
-        let test_data = vec![(
-            "test".to_string(),
-            vec![
-                "一".to_string(),
-                "二".to_string(),
-                "三".to_string(),
-                "四".to_string(),
-                "五".to_string(),
-            ],
-        )];
+    fn test_merge_impact() {
+        let records = crate::io::read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv");
+        let zh_count = group_by_onset(&records)
+            .unwrap()
+            .into_iter()
+            .find(|(onset, _)| *onset == HanziOnset::Zh)
+            .map(|(_, count)| count)
+            .unwrap();
 
-        let output = format_pinyin_output(&test_data, Some(3));
+        assert_eq!(zh_count, 291);
+        assert_eq!(merge_impact(&records, HanziOnset::Zh, HanziOnset::Z), 291);
+        assert_eq!(merge_impact(&records, HanziOnset::Zh, HanziOnset::Zh), 0);
+        assert_eq!(merge_impact(&[], HanziOnset::Zh, HanziOnset::Z), 0);
+    }
 
-        // fold_size is 3, so first line should have 3 characters, remaining on next line
-        assert!(
-            output.len() >= 2,
-            "Should have at least 2 lines when folding"
+    #[test]
+    fn test_count_by_tone() {
+        let mut records = create_test_records();
+        records.push(HanziRecord::builder("吗", "ma", 5).build());
+        records.push(HanziRecord::builder("?", "ma", 0).build());
+
+        // The unknown tone 0 comes after the neutral tone
+        let tone_counts = count_by_tone(&records).unwrap();
+        assert_eq!(tone_counts, vec![(1, 1), (3, 1), (4, 1), (5, 1), (0, 1)]);
+        assert_eq!(
+            format_tone_counts(&tone_counts),
+            vec!["1: 1", "3: 1", "4: 1", "5: 1", "0: 1"]
         );
-        assert!(output[0].contains("test"));
-        assert!(output[0].contains("5")); // character count
-        assert!(
-            !output[1].trim().is_empty(),
-            "Second line should contain remaining characters"
+        assert!(count_by_tone(&[]).is_none());
+        assert_eq!(
+            format_tone_distribution(&tone_counts),
+            "Tones: 1=1 3=1 4=1 5=1 0=1"
         );
+        assert_eq!(format_tone_distribution(&[]), "Tones: ");
     }
 
     #[test]
-    fn test_format_pinyin_output_alignment() {
-        let test_data = vec![
-            ("ji".to_string(), vec!["机".to_string()]),
-            ("longpinyin".to_string(), vec!["长".to_string()]),
-        ];
+    fn test_format_rime_output() {
+        let rime_counts = group_by_rime(&create_test_records()).unwrap();
+        assert_eq!(format_rime_output(&rime_counts), vec!["i: 2", "a: 1"]);
+    }
 
-        let output = format_pinyin_output(&test_data, None);
+    #[test]
+    fn test_search_syllables() {
+        let records = crate::io::read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv");
 
-        // Test output format
-        for line in &output {
-            assert!(line.contains(":"), "Each line should contain ':'");
-            let parts: Vec<&str> = line.split(':').collect();
-            assert_eq!(parts.len(), 2, "Each line should have exactly one ':'");
-        }
+        // Onset fixed, rime open
+        let m_query = SyllableQuery {
+            onset: Some(HanziOnset::M),
+            rime: None,
+        };
+        let m_groups = search_syllables(&records, &m_query, false);
+        assert!(m_groups.iter().all(|(pinyin, _)| pinyin.starts_with('m')));
+        let pinyins: Vec<&str> = m_groups.iter().map(|(pinyin, _)| pinyin.as_str()).collect();
+        assert!(pinyins.contains(&"ma") && pinyins.contains(&"mang"));
+        let m_total: usize = m_groups
+            .iter()
+            .map(|(_, characters)| characters.len())
+            .sum();
+        let m_count = group_by_onset(&records)
+            .unwrap()
+            .into_iter()
+            .find(|(onset, _)| *onset == HanziOnset::M)
+            .unwrap()
+            .1;
+        assert_eq!(m_total, m_count as usize);
+
+        // Rime fixed, onset open
+        let ang_query = SyllableQuery {
+            onset: None,
+            rime: Some(HanziRime::Ang),
+        };
+        let ang_groups = search_syllables(&records, &ang_query, false);
+        assert!(ang_groups.iter().all(|(pinyin, _)| pinyin.ends_with("ang")));
+        assert_eq!(ang_groups[0].0, "bang");
+
+        // Both fixed
+        let ma_query = SyllableQuery {
+            onset: Some(HanziOnset::M),
+            rime: Some(HanziRime::A),
+        };
+        let ma_groups = search_syllables(&records, &ma_query, true);
+        assert_eq!(ma_groups.len(), 1);
+        assert!(ma_groups[0].1.contains(&"馬".to_string()));
     }
 
     #[test]
-    fn test_group_by_tone_found() {
-        let records = create_test_records();
-        let result = group_by_tone(&records, "ji", false);
+    fn test_group_by_structure() {
+        let mut records = create_test_records();
+        records.push(HanziRecord::builder("爱", "ài", 4).build());
+        records.push(HanziRecord::builder("中", "zhōng", 1).build());
 
-        assert!(result.is_some());
-        let tone_groups = result.unwrap();
+        // "ji", "ji" and "ma" are CV
+        let structures = group_by_structure(&records);
+        assert_eq!(structures, vec![("CV", 3), ("CVC", 1), ("VV", 1)]);
+        assert!(group_by_structure(&[]).is_empty());
+    }
 
-        // ji has 2 characters (tone 1: 机, tone 4: 计)
-        assert_eq!(tone_groups.len(), 2);
+    #[test]
+    fn test_format_pinyin_toml() {
+        let mut grouped = group_by_pinyin(&create_test_records(), false);
+        grouped.push(("nü".to_string(), vec!["女".to_string()]));
 
-        // Should be sorted in tone order
-        assert_eq!(tone_groups[0].0, 1); // tone 1
-        assert_eq!(tone_groups[0].2, vec!["机"]); // 机
+        let output = format_pinyin_toml(&grouped);
+        assert_eq!(output[0], r#"ji = ["机", "计"]"#);
+        assert_eq!(output[2], r#""nü" = ["女"]"#);
 
-        assert_eq!(tone_groups[1].0, 4); // tone 4
-        assert_eq!(tone_groups[1].2, vec!["计"]); // 计
+        let document: toml::Table = output
+            .join("\n")
+            .parse()
+            .expect("Output should be valid TOML");
+        assert_eq!(document.len(), 3);
+        let ji = document["ji"].as_array().expect("ji should be an array");
+        assert_eq!(ji.len(), 2);
+        assert_eq!(ji[0].as_str(), Some("机"));
+        assert_eq!(document["nü"][0].as_str(), Some("女"));
     }
 
     #[test]
-    fn test_group_by_tone_traditional() {
-        let records = create_test_records();
-        let result = group_by_tone(&records, "ji", true);
+    fn test_export_chart_json() {
+        let json = export_chart_json(&create_test_records(), false);
 
-        assert!(result.is_some());
-        let tone_groups = result.unwrap();
+        // "ma" follows "ji" in onset chart order
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"ma":{"onset":"m","rime":"a","tones":{"3":["马"]}},"#,
+                r#""ji":{"onset":"j","rime":"i","tones":{"1":["机"],"4":["计"]}}}"#
+            )
+        );
 
-        // Traditional characters should be used
-        assert_eq!(tone_groups[0].2, vec!["機"]); // 機 (traditional)
-        assert_eq!(tone_groups[1].2, vec!["計"]); // 計 (traditional)
+        let traditional = export_chart_json(&create_test_records(), true);
+        assert!(traditional
+            .contains(r#""ji":{"onset":"j","rime":"i","tones":{"1":["機"],"4":["計"]}}"#));
     }
 
     #[test]
-    fn test_group_by_tone_not_found() {
-        let records = create_test_records();
-        let result = group_by_tone(&records, "nonexistent", false);
-
-        assert!(result.is_none());
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("机"), r#""机""#);
+        assert_eq!(json_string("a\"b\\c\n"), r#""a\"b\\c\n""#);
+        assert_eq!(json_string("\u{1}"), r#""\u0001""#);
     }
 
     #[test]
-    fn test_group_by_tone_pinyin_with_tone_marks() {
-        let records = create_test_records();
-        let result = group_by_tone(&records, "ji", false);
+    fn test_group_by_tone_by_frequency() {
+        let records = vec![
+            HanziRecord::builder("计", "jì", 4).frequency(40).build(),
+            HanziRecord::builder("机", "jī", 1).frequency(300).build(),
+            HanziRecord::builder("既", "jì", 4).frequency(0).build(),
+            HanziRecord::builder("几", "jǐ", 3).frequency(20).build(),
+            HanziRecord::builder("记", "jì", 4).frequency(10).build(),
+        ];
 
-        assert!(result.is_some());
-        let tone_groups = result.unwrap();
+        let groups = group_by_tone_by_frequency(&records, "ji", false).unwrap();
+        let tones: Vec<u32> = groups.iter().map(|(tone, _, _)| *tone).collect();
+        assert_eq!(tones, vec![4, 3, 1]);
+        // The unknown rank 0 comes last
+        assert_eq!(groups[0].2, vec!["记", "计", "既"]);
+        assert_eq!(groups[0].1, "jì");
 
-        // pinyin should contain tone marks
-        assert_eq!(tone_groups[0].1, "jī"); // tone 1
-        assert_eq!(tone_groups[1].1, "jì"); // tone 4
+        assert!(group_by_tone_by_frequency(&records, "ma", false).is_none());
     }
 
     #[test]
@@ -809,6 +4027,15 @@ mod tests {
         assert!(output.is_empty());
     }
 
+    #[test]
+    fn test_format_tone_pinyin_only() {
+        let records = create_test_records();
+        let tone_groups = group_by_tone(&records, "ji", false).unwrap();
+
+        assert_eq!(format_tone_pinyin_only(&tone_groups), "jī jì");
+        assert_eq!(format_tone_pinyin_only(&[]), "");
+    }
+
     #[test]
     fn test_tone_sorting() {
         let mut records = create_test_records();
@@ -834,6 +4061,68 @@ mod tests {
         assert_eq!(tone_groups[1].0, 5); // tone 5 comes after
     }
 
+    #[test]
+    fn test_unknown_tone_sorts_last() {
+        let mut records = create_test_records();
+        records.push(HanziRecord {
+            frequency: 4,
+            simplified: "吗".to_string(),
+            traditional: "嗎".to_string(),
+            pinyin: "ma".to_string(),
+            pinyin_without_tone: "ma".to_string(),
+            tone: 5, // neutral tone
+            onset: HanziOnset::M,
+            rime: HanziRime::A,
+        });
+        records.push(HanziRecord {
+            frequency: 5,
+            simplified: "嘛".to_string(),
+            traditional: "嘛".to_string(),
+            pinyin: "ma".to_string(),
+            pinyin_without_tone: "ma".to_string(),
+            tone: 0, // unknown tone
+            onset: HanziOnset::M,
+            rime: HanziRime::A,
+        });
+
+        let tone_groups = group_by_tone(&records, "ma", false).unwrap();
+
+        // Tone 0 must not be merged with tone 5 and must come last
+        assert_eq!(tone_groups.len(), 3);
+        assert_eq!(tone_groups[0].0, 3);
+        assert_eq!(tone_groups[1].0, 5);
+        assert_eq!(tone_groups[1].2, vec!["吗"]);
+        assert_eq!(tone_groups[2].0, 0);
+        assert_eq!(tone_groups[2].2, vec!["嘛"]);
+
+        let output = format_tone_output(&tone_groups);
+        assert_eq!(output[1], "ma: 吗");
+        assert_eq!(output[2], "ma (unknown tone): 嘛");
+    }
+
+    #[test]
+    fn test_homophones() {
+        let mut records = create_test_records();
+        records.push(HanziRecord {
+            frequency: 4,
+            simplified: "记".to_string(),
+            traditional: "記".to_string(),
+            pinyin: "jì".to_string(),
+            pinyin_without_tone: "ji".to_string(),
+            tone: 4,
+            onset: HanziOnset::J,
+            rime: HanziRime::I,
+        });
+
+        let groups = homophones(&records, false);
+
+        // Only 计 and 记 share both pinyin and tone
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, "jì");
+        assert_eq!(groups[0].1, 4);
+        assert_eq!(groups[0].2, vec!["计", "记"]);
+    }
+
     #[test]
     fn test_group_by_onset() {
         let records = create_test_records();
@@ -863,6 +4152,126 @@ mod tests {
         assert!(onset_map.contains_key(&HanziOnset::M));
     }
 
+    #[test]
+    fn test_group_by_onset_ties_follow_chart_order() {
+        // "ma" and "ba" both have one record; B precedes M in the onset chart
+        let records = vec![
+            HanziRecord::builder("马", "mǎ", 3).frequency(1).build(),
+            HanziRecord::builder("八", "bā", 1).frequency(2).build(),
+            HanziRecord::builder("机", "jī", 1).frequency(3).build(),
+            HanziRecord::builder("计", "jì", 4).frequency(4).build(),
+        ];
+
+        for _ in 0..10 {
+            let onset_counts = group_by_onset(&records).unwrap();
+            assert_eq!(
+                onset_counts,
+                vec![(HanziOnset::J, 2), (HanziOnset::B, 1), (HanziOnset::M, 1)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_by_rime() {
+        let records = create_test_records();
+        let rime_counts = group_by_rime(&records).unwrap();
+
+        // "ji" twice and "ma" once
+        assert_eq!(rime_counts, vec![(HanziRime::I, 2), (HanziRime::A, 1)]);
+        assert!(group_by_rime(&[]).is_none());
+    }
+
+    #[test]
+    fn test_distinct_syllables_per_rime() {
+        // "ji" has two characters but counts as one syllable
+        let records = create_test_records();
+        assert_eq!(
+            distinct_syllables_per_rime(&records),
+            vec![(HanziRime::A, 1), (HanziRime::I, 1)]
+        );
+        assert!(distinct_syllables_per_rime(&[]).is_empty());
+
+        let records = crate::io::read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv");
+        let counts = distinct_syllables_per_rime(&records);
+        // bi pi mi di ti ni li ji qi xi yi, and the apical zi ci si zhi chi shi ri
+        let i_count = counts
+            .iter()
+            .find(|(rime, _)| *rime == HanziRime::I)
+            .map(|(_, count)| *count);
+        assert_eq!(i_count, Some(18));
+    }
+
+    #[test]
+    fn test_onset_index() {
+        let records = create_test_records();
+        let index = OnsetIndex::new(&records, false);
+
+        assert_eq!(index.get(&HanziOnset::J), ["机", "计"]);
+        assert_eq!(index.get(&HanziOnset::M), ["马"]);
+        assert!(index.get(&HanziOnset::Zh).is_empty());
+
+        let traditional = OnsetIndex::new(&records, true);
+        assert_eq!(traditional.get(&HanziOnset::J), ["機", "計"]);
+    }
+
+    #[test]
+    fn test_top_characters() {
+        let records = create_test_records();
+
+        assert_eq!(
+            top_characters(&records, 2, RankDirection::Most, false),
+            vec![("机".to_string(), 1), ("计".to_string(), 2)]
+        );
+        // The least common characters have the highest ranks
+        assert_eq!(
+            top_characters(&records, 2, RankDirection::Least, true),
+            vec![("馬".to_string(), 3), ("計".to_string(), 2)]
+        );
+        assert_eq!(
+            top_characters(&records, 10, RankDirection::Least, false).len(),
+            3
+        );
+        assert!(top_characters(&records, 0, RankDirection::Most, false).is_empty());
+    }
+
+    #[test]
+    fn test_traditional_only() {
+        let records = vec![
+            HanziRecord::builder("马", "mǎ", 3)
+                .traditional("馬")
+                .build(),
+            // 后 is also a traditional character (queen), so only 後 is traditional-only
+            HanziRecord::builder("后", "hòu", 4)
+                .traditional("後")
+                .build(),
+            HanziRecord::builder("后", "hòu", 4).build(),
+            HanziRecord::builder("的", "de", 5).build(),
+        ];
+
+        let traditional: Vec<&str> = traditional_only(&records)
+            .iter()
+            .map(|record| record.traditional.as_str())
+            .collect();
+        assert_eq!(traditional, vec!["馬", "後"]);
+        assert!(traditional_only(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_onset_tone_counts() {
+        let records = create_test_records();
+        let counts = onset_tone_counts(&records);
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&(HanziOnset::J, 1)], 1);
+        assert_eq!(counts[&(HanziOnset::J, 4)], 1);
+        assert_eq!(counts[&(HanziOnset::M, 3)], 1);
+
+        let records = crate::io::read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv");
+        let counts = onset_tone_counts(&records);
+        assert_eq!(counts[&(HanziOnset::J, 1)], 131);
+        // The sample has one record per character
+        assert_eq!(counts.values().sum::<u32>(), 5000);
+    }
+
     #[test]
     fn test_group_by_onset_empty() {
         let empty_records: Vec<HanziRecord> = vec![];
@@ -871,6 +4280,40 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_learning_order() {
+        // The sample has two j characters and one m character
+        let records = create_test_records();
+
+        let onsets = onset_learning_order(&records);
+        assert_eq!(onsets, vec![HanziOnset::J, HanziOnset::M]);
+        assert_eq!(onsets[0], group_by_onset(&records).unwrap()[0].0);
+
+        let rimes = rime_learning_order(&records);
+        assert_eq!(rimes, vec![HanziRime::I, HanziRime::A]);
+
+        assert!(onset_learning_order(&[]).is_empty());
+        assert!(rime_learning_order(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_include_empty_onsets() {
+        let onset_counts = group_by_onset(&create_test_records()).unwrap();
+
+        let counts = include_empty_onsets(&onset_counts);
+
+        assert_eq!(counts.len(), HanziOnset::all().len());
+        for onset in HanziOnset::all() {
+            assert_eq!(counts.iter().filter(|(o, _)| o == onset).count(), 1);
+        }
+        // The present onsets keep their counts and come first
+        assert_eq!(&counts[..onset_counts.len()], onset_counts.as_slice());
+        assert!(counts[onset_counts.len()..]
+            .iter()
+            .all(|(_, count)| *count == 0));
+        assert_eq!(include_empty_onsets(&[]).len(), HanziOnset::all().len());
+    }
+
     #[test]
     fn test_format_onset_output() {
         let test_data = vec![
@@ -897,6 +4340,116 @@ mod tests {
         assert!(output.is_empty());
     }
 
+    #[test]
+    fn test_format_pinyin_output_with_ranges() {
+        let records = create_test_records();
+        let ranges = frequency_range_by_pinyin(&records);
+        assert_eq!(ranges["ji"], (1, 2));
+        assert_eq!(ranges["ma"], (3, 3));
+
+        let grouped = group_by_pinyin(&records, false);
+        let output =
+            format_pinyin_output_with_ranges(&grouped, None, &PinyinFormat::default(), &ranges);
+        assert_eq!(
+            output,
+            vec![
+                "ji      :   2 机计  [rank 1–2]",
+                "ma      :   1 马  [rank 3–3]"
+            ]
+        );
+
+        // With folding, the range ends the last line of the group
+        let output =
+            format_pinyin_output_with_ranges(&grouped, Some(1), &PinyinFormat::default(), &ranges);
+        assert_eq!(output[0], "ji      :   2 机");
+        assert_eq!(output[1].trim(), "计  [rank 1–2]");
+    }
+
+    #[test]
+    fn test_format_pinyin_output_truncated() {
+        let test_data = vec![
+            (
+                "ji".to_string(),
+                vec!["机".to_string(), "计".to_string(), "记".to_string()],
+            ),
+            ("ma".to_string(), vec!["马".to_string(), "妈".to_string()]),
+        ];
+        let format = PinyinFormat::default();
+
+        let output = format_pinyin_output_truncated(&test_data, 2, &format);
+        assert_eq!(
+            output,
+            vec!["ji      :   3 机计…(+1)", "ma      :   2 马妈"]
+        );
+
+        let output = format_pinyin_output_truncated(&test_data, 0, &format);
+        assert_eq!(output[1], "ma      :   2 …(+2)");
+    }
+
+    #[test]
+    fn test_format_onset_output_pct() {
+        let test_data = vec![
+            (HanziOnset::J, 412),
+            (HanziOnset::M, 300),
+            (HanziOnset::None, 288),
+        ];
+
+        let output = format_onset_output_pct(&test_data);
+
+        assert_eq!(output.len(), 3);
+        assert_eq!(output[0], "j: 412 (41.2%)");
+        assert_eq!(output[1], "m: 300 (30.0%)");
+        assert_eq!(output[2], "none: 288 (28.8%)");
+        assert!(format_onset_output_pct(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_format_onset_output_pct_all_zero() {
+        let test_data = vec![(HanziOnset::J, 0), (HanziOnset::M, 0)];
+
+        let output = format_onset_output_pct(&test_data);
+
+        assert_eq!(output, vec!["j: 0 (0.0%)", "m: 0 (0.0%)"]);
+    }
+
+    #[test]
+    fn test_format_onset_summary() {
+        let mut records = create_test_records();
+        records.push(HanziRecord {
+            frequency: 4,
+            ..HanziRecord::builder("妈", "mā", 1).build()
+        });
+
+        let output = format_onset_summary(&records, 1, false);
+
+        // Two characters each for j and m; m comes first in chart order
+        assert_eq!(output, vec!["m: 2  马", "j: 2  机"]);
+        let m_line = output.iter().find(|line| line.starts_with("m: ")).unwrap();
+        assert!(m_line.contains('马'));
+
+        let output = format_onset_summary(&records, 5, true);
+        assert_eq!(output[1], "j: 2  機 計");
+        assert!(format_onset_summary(&[], 3, false).is_empty());
+    }
+
+    #[test]
+    fn test_format_onset_output_pct_sums_to_100() {
+        let records = create_test_records();
+        let onset_counts = group_by_onset(&records).unwrap();
+
+        let total: f64 = format_onset_output_pct(&onset_counts)
+            .iter()
+            .map(|line| {
+                let percent = line.rsplit('(').next().unwrap().trim_end_matches("%)");
+                percent.parse::<f64>().unwrap()
+            })
+            .sum();
+
+        // Each percentage is rounded to 0.1, so allow for rounding error
+        let tolerance = 0.05 * onset_counts.len() as f64;
+        assert!((total - 100.0).abs() <= tolerance, "Sum was {total}");
+    }
+
     #[test]
     fn test_format_onset_output_with_group_by_onset() {
         let records = create_test_records();
@@ -947,6 +4500,30 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_group_by_rime_and_onset() {
+        let records = create_test_records();
+
+        let grouped = group_by_rime_and_onset(&records, &HanziRime::I, true).unwrap();
+        assert_eq!(
+            grouped,
+            vec![(HanziOnset::J, vec!["機".to_string(), "計".to_string()])]
+        );
+
+        assert!(group_by_rime_and_onset(&records, &HanziRime::Ang, false).is_none());
+    }
+
+    #[test]
+    fn test_collect_by_rime() {
+        let records = create_test_records();
+
+        assert_eq!(
+            collect_by_rime(&records, &HanziRime::A, false),
+            vec![(HanziOnset::M, vec!["马".to_string()])]
+        );
+        assert!(collect_by_rime(&records, &HanziRime::Ang, false).is_empty());
+    }
+
     #[test]
     fn test_format_onset_pinyin_output() {
         let test_data = vec![
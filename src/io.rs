@@ -1,10 +1,13 @@
 //! # I/O Module
 //!
 //! This module handles file input/output operations for the Hanzi analysis library.
-//! It provides functions for reading and parsing TSV files containing Chinese character data.
+//! It provides functions for reading and parsing TSV files containing Chinese character data,
+//! and for exporting analysis results.
 
+use crate::analysis::{analyze_verbose, is_valid_syllable, set_hanzi_onsets, set_hanzi_rime};
 use crate::types::{HanziOnset, HanziRecord, HanziRime};
-use std::io::BufRead;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
 
 /// Reads a TSV file containing Hanzi data and returns a vector of HanziRecord
 ///
@@ -31,36 +34,588 @@ use std::io::BufRead;
 /// 5. Pinyin without tone marks (string)
 /// 6. Tone number (integer, 1-5)
 ///
-/// Lines with fewer than 6 fields are skipped. Invalid numbers default to 0.
+/// Leading and trailing whitespace of every field is trimmed, so stray spaces
+/// around a character or pinyin do not break exact matching; whitespace
+/// inside a field is kept. This also removes the `\r` of CRLF line endings
+/// from the last field. Lines with fewer than 6 fields, or whose simplified
+/// or traditional field is empty or whitespace-only, are skipped. Use
+/// [`read_hanzi_file_strict`] to reject such lines instead.
+///
+/// Invalid numbers default to 0. The tone field may be padded with whitespace
+/// or prefixed with text (e.g. " 3 " or "tone 3"); its trailing digits are
+/// used. For the tone field, 0 means "unknown tone" and is kept distinct from
+/// the neutral tone (5).
 pub fn read_hanzi_file(file_path: &str) -> std::io::Result<Vec<HanziRecord>> {
+    read_records(file_path, &ReadOptions::default(), &mut |_| {}, &|_| true)
+}
+
+/// Reads a TSV file containing Hanzi data, rejecting malformed lines
+///
+/// This is the strict counterpart of [`read_hanzi_file`]. Instead of skipping
+/// lines with fewer than 6 fields or with an empty or whitespace-only
+/// simplified or traditional field, it fails on the first such line.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the TSV file to read
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - Successfully parsed records
+/// * `Err(std::io::Error)` - File I/O error occurred, or a line is malformed
+///   (`ErrorKind::InvalidData`, with the 1-based line number in the message)
+pub fn read_hanzi_file_strict(file_path: &str) -> std::io::Result<Vec<HanziRecord>> {
+    read_records(
+        file_path,
+        &ReadOptions {
+            strict: true,
+            ..ReadOptions::default()
+        },
+        &mut |_| {},
+        &|_| true,
+    )
+}
+
+/// Reads a TSV file strictly and rejects syllables that are not valid Mandarin
+///
+/// The file is read as with [`read_hanzi_file_strict`], then the onset and
+/// rime of every record are derived from its pinyin and checked with
+/// [`is_valid_syllable`]. This catches typos such as "bua" that still split
+/// into a known onset and rime, as well as pinyin that cannot be analyzed at
+/// all. The returned records have their onset and rime set.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the TSV file to read
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - Successfully parsed and checked records
+/// * `Err(std::io::Error)` - File I/O error occurred, a line is malformed, or
+///   some syllables are illegal (`ErrorKind::InvalidData`, listing every
+///   illegal syllable with its 1-based line number, e.g. "line 3: buā")
+pub fn read_hanzi_file_strict_checked(file_path: &str) -> std::io::Result<Vec<HanziRecord>> {
+    let mut records = read_hanzi_file_strict(file_path)?;
+    let mut illegal = Vec::new();
+    // The strict reader keeps every line, so the record index gives the line number
+    for (index, record) in records.iter_mut().enumerate() {
+        let (onset, _, rime) = analyze_verbose(record);
+        if !is_valid_syllable(&onset, &rime) {
+            illegal.push(format!("line {}: {}", index + 1, record.pinyin));
+        }
+        record.onset = onset;
+        record.rime = rime;
+    }
+    if !illegal.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("illegal syllables: {}", illegal.join(", ")),
+        ));
+    }
+    Ok(records)
+}
+
+/// Reads a TSV file containing Hanzi data, keeping onset and rime columns
+///
+/// Files written by the convert command have two extra fields after the
+/// tone: the onset and the rime. [`read_hanzi_file`] ignores them, while this
+/// function parses them with `HanziOnset::from_str` and `HanziRime::from_str`.
+/// A missing or unparsable onset or rime (including the empty onset written
+/// for vowel-initial syllables) falls back to `None`. Other lines are handled
+/// in the same way as [`read_hanzi_file`].
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the TSV file to read
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - Successfully parsed records
+/// * `Err(std::io::Error)` - File I/O error occurred
+pub fn read_hanzi_file_full(file_path: &str) -> std::io::Result<Vec<HanziRecord>> {
+    read_records(
+        file_path,
+        &ReadOptions {
+            with_phonetics: true,
+            ..ReadOptions::default()
+        },
+        &mut |_| {},
+        &|_| true,
+    )
+}
+
+/// Reads a TSV file containing Hanzi data, tolerating invalid UTF-8
+///
+/// [`read_hanzi_file`] fails as soon as a line is not valid UTF-8. This
+/// function instead decodes each line with [`String::from_utf8_lossy`], so
+/// every invalid byte sequence is replaced with the replacement character
+/// U+FFFD (`�`) and the rest of the file is still read. A substitution in a
+/// character or pinyin field gives a record that will not match any query,
+/// so the result is best checked with the `validate` command. Other lines
+/// are handled in the same way as [`read_hanzi_file`].
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the TSV file to read
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - Successfully parsed records
+/// * `Err(std::io::Error)` - File I/O error occurred
+pub fn read_hanzi_file_lossy(file_path: &str) -> std::io::Result<Vec<HanziRecord>> {
+    read_records(
+        file_path,
+        &ReadOptions {
+            lossy: true,
+            ..ReadOptions::default()
+        },
+        &mut |_| {},
+        &|_| true,
+    )
+}
+
+/// Reads a TSV file containing Hanzi data whose neutral tone is not 5
+///
+/// The library uses 5 for the neutral tone and 0 for an unknown tone, but some
+/// data sets write the neutral tone as 0. This behaves like [`read_hanzi_file`]
+/// and then maps the tone `neutral_tone_value` to 5, so these records group
+/// and sort as neutral. With a value of 0, a missing or unparsable tone field
+/// is taken as neutral too. A value of 5 reads the file unchanged.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the TSV file to read
+/// * `neutral_tone_value` - The tone number the file uses for the neutral tone
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - Successfully parsed records
+/// * `Err(std::io::Error)` - File I/O error occurred
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use study_rust_hanzi::read_hanzi_file_with_neutral_tone;
+/// // A file writing "ma" of 吗 with tone 0
+/// let records = read_hanzi_file_with_neutral_tone("hanzi_neutral0.tsv", 0)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn read_hanzi_file_with_neutral_tone(
+    file_path: &str,
+    neutral_tone_value: u32,
+) -> std::io::Result<Vec<HanziRecord>> {
+    read_records(
+        file_path,
+        &ReadOptions {
+            neutral_tone_value: Some(neutral_tone_value),
+            ..ReadOptions::default()
+        },
+        &mut |_| {},
+        &|_| true,
+    )
+}
+
+/// Number of lines between two calls of the [`read_hanzi_file_with_progress`] callback
+pub const PROGRESS_INTERVAL: usize = 1000;
+
+/// Reads a TSV file containing Hanzi data, reporting progress while reading
+///
+/// This behaves like [`read_hanzi_file`], and in addition calls `callback`
+/// with the number of lines read so far after every [`PROGRESS_INTERVAL`]
+/// lines (1000, 2000, ...). Skipped lines are counted too. It is meant for
+/// showing progress when reading large files.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the TSV file to read
+/// * `callback` - Called with the running line count every [`PROGRESS_INTERVAL`] lines
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - Successfully parsed records
+/// * `Err(std::io::Error)` - File I/O error occurred
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use study_rust_hanzi::read_hanzi_file_with_progress;
+/// let records = read_hanzi_file_with_progress("hanzi.tsv", |lines| {
+///     eprint!("\rRead {lines} lines");
+/// });
+/// ```
+pub fn read_hanzi_file_with_progress(
+    file_path: &str,
+    mut callback: impl FnMut(usize),
+) -> std::io::Result<Vec<HanziRecord>> {
+    read_records(file_path, &ReadOptions::default(), &mut callback, &|_| true)
+}
+
+/// Reads only the records of a TSV file that pass a predicate
+///
+/// Each line is parsed as in [`read_hanzi_file`], its onset and rime are
+/// analyzed, and the record is kept only if `predicate` returns true. Records
+/// that fail are dropped right away, so the whole file is never held in
+/// memory. The returned records have their onset and rime set.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the TSV file to read
+/// * `predicate` - Decides whether an analyzed record is kept
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - The analyzed records passing the predicate
+/// * `Err(std::io::Error)` - File I/O error occurred
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use study_rust_hanzi::{read_hanzi_file_filtered, HanziOnset};
+/// // Only the palatals j, q and x
+/// let palatals = read_hanzi_file_filtered("hanzi.tsv", |record| {
+///     matches!(record.onset, HanziOnset::J | HanziOnset::Q | HanziOnset::X)
+/// });
+/// ```
+pub fn read_hanzi_file_filtered(
+    file_path: &str,
+    predicate: impl Fn(&HanziRecord) -> bool,
+) -> std::io::Result<Vec<HanziRecord>> {
+    read_records(
+        file_path,
+        &ReadOptions {
+            analyze: true,
+            ..ReadOptions::default()
+        },
+        &mut |_| {},
+        &predicate,
+    )
+}
+
+/// Reads every TSV file of a directory and concatenates their records
+///
+/// A dataset split into shards such as `part-1.tsv`, `part-2.tsv` can be read
+/// in one call. The files with a `.tsv` extension are read in order of their
+/// names, each as with [`read_hanzi_file`]; other files and subdirectories are
+/// ignored. Name order is plain string order, so shards numbered past 9 need
+/// zero padding (`part-01.tsv`) to stay in sequence.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the directory holding the shards
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - The records of all shards, in shard order
+/// * `Err(std::io::Error)` - The directory or a shard could not be read
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use study_rust_hanzi::read_hanzi_dir;
+/// let records = read_hanzi_dir("data")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn read_hanzi_dir(dir: &str) -> std::io::Result<Vec<HanziRecord>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|extension| extension == "tsv") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
     let mut records = Vec::new();
+    for path in paths {
+        let file_path = path.to_str().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("file name is not valid UTF-8: {}", path.display()),
+            )
+        })?;
+        records.extend(read_hanzi_file(file_path)?);
+    }
+    Ok(records)
+}
+
+/// Reads a JSON-lines file of Hanzi records
+///
+/// Each non-empty line holds one [`HanziRecord`] as a JSON object with the
+/// field names of the struct, e.g. as written by `serde_json::to_string`.
+/// Onsets and rimes are given by their variant names (e.g. `"Zh"`, `"Ang"`).
+/// Unlike the TSV readers, nothing is defaulted: a line that is not a complete
+/// record fails the read. Requires the `serde` feature.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the JSON-lines file to read
+///
+/// # Returns
+///
+/// * `Ok(Vec<HanziRecord>)` - Successfully parsed records
+/// * `Err(std::io::Error)` - File I/O error occurred, or a line is not a valid
+///   record (`ErrorKind::InvalidData`, with the 1-based line number in the message)
+#[cfg(feature = "serde")]
+pub fn read_hanzi_jsonl(file_path: &str) -> std::io::Result<Vec<HanziRecord>> {
     let file = std::fs::File::open(file_path)?;
     let reader = std::io::BufReader::new(file);
 
-    for line in reader.lines() {
+    let mut records = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
         let line = line?;
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() < 6 {
-            continue; // Skip lines that do not have enough fields
+        if line.trim().is_empty() {
+            continue;
         }
+        let record = serde_json::from_str(&line).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("line {}: {e}", index + 1),
+            )
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// How [`read_records`] parses the lines of a TSV file
+#[derive(Default)]
+struct ReadOptions {
+    /// Reject malformed lines instead of skipping them
+    strict: bool,
+    /// Parse the optional 7th and 8th fields as the onset and rime instead of
+    /// ignoring them
+    with_phonetics: bool,
+    /// Replace invalid UTF-8 in a line with U+FFFD instead of failing the read
+    lossy: bool,
+    /// The tone number the file uses for the neutral tone, read as 5
+    neutral_tone_value: Option<u32>,
+    /// Set the onset and rime of each record from its pinyin
+    analyze: bool,
+}
+
+/// Reads the records of a TSV file, either skipping or rejecting malformed lines
+///
+/// `options` selects how the lines are parsed. `progress` is called with the
+/// running line count every [`PROGRESS_INTERVAL`] lines. Each parsed record is
+/// passed to `keep`, and only those for which it returns true are collected.
+fn read_records(
+    file_path: &str,
+    options: &ReadOptions,
+    progress: &mut dyn FnMut(usize),
+    keep: &dyn Fn(&HanziRecord) -> bool,
+) -> std::io::Result<Vec<HanziRecord>> {
+    let mut records = Vec::new();
+    let file = std::fs::File::open(file_path)?;
+    let reader = std::io::BufReader::new(file);
+    let lines: Box<dyn Iterator<Item = std::io::Result<String>>> = if options.lossy {
+        // The `\r` of a CRLF line ending is left in place and trimmed with the last field
+        Box::new(
+            reader
+                .split(b'\n')
+                .map(|line| line.map(|bytes| String::from_utf8_lossy(&bytes).into_owned())),
+        )
+    } else {
+        Box::new(reader.lines())
+    };
+
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if (index + 1) % PROGRESS_INTERVAL == 0 {
+            progress(index + 1);
+        }
+        // Stray spaces around a field would break exact matching
+        let parts: Vec<&str> = line.split('\t').map(str::trim).collect();
+
+        let problem = if parts.len() < 6 {
+            Some(format!("expected 6 fields, found {}", parts.len()))
+        } else if parts[1].is_empty() || parts[2].is_empty() {
+            Some("empty character field".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            if options.strict {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("line {}: {problem}", index + 1),
+                ));
+            }
+            continue; // Skip malformed lines
+        }
+
         let record = HanziRecord {
             frequency: parts[0].parse().unwrap_or(0),
             simplified: parts[1].to_string(),
             traditional: parts[2].to_string(),
             pinyin: parts[3].to_string(),
             pinyin_without_tone: parts[4].to_string(),
-            tone: parts[5].parse().unwrap_or(0),
+            tone: parse_tone_field(parts[5]),
             onset: HanziOnset::None, // Set as initial value
             rime: HanziRime::None,   // Set as initial value
         };
-        records.push(record);
+        let mut record = if options.with_phonetics {
+            HanziRecord {
+                onset: parts
+                    .get(6)
+                    .and_then(|field| field.parse().ok())
+                    .unwrap_or_default(),
+                rime: parts
+                    .get(7)
+                    .and_then(|field| field.parse().ok())
+                    .unwrap_or_default(),
+                ..record
+            }
+        } else {
+            record
+        };
+        if options.neutral_tone_value == Some(record.tone) {
+            record.tone = 5;
+        }
+        if options.analyze {
+            let (onset, _, rime) = analyze_verbose(&record);
+            record.onset = onset;
+            record.rime = rime;
+        }
+        if keep(&record) {
+            records.push(record);
+        }
     }
     Ok(records)
 }
 
+/// Parses the tone field of a TSV line
+///
+/// Surrounding whitespace is ignored and the number is taken from the trailing
+/// digits, so " 3 " and "tone 3" both give 3. A field without trailing digits
+/// gives 0 (unknown tone).
+fn parse_tone_field(field: &str) -> u32 {
+    let field = field.trim();
+    let digits_start = field
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |index| index + 1);
+    field[digits_start..].parse().unwrap_or(0)
+}
+
+/// Reads a list of valid syllables, one per line
+///
+/// Lets a dialect study replace the standard Mandarin syllables with its own
+/// list, for use with [`is_known_pinyin_with_syllables`](crate::is_known_pinyin_with_syllables)
+/// and [`syllabify_with_syllables`](crate::syllabify_with_syllables). Each line
+/// is trimmed, lowercased and has "v" replaced with "ü", matching how those
+/// functions normalize their input. Empty lines are skipped.
+///
+/// # Arguments
+///
+/// * `path` - Path to the syllable list
+///
+/// # Returns
+///
+/// * `Ok(HashSet<String>)` - The syllables of the list
+/// * `Err(std::io::Error)` - File I/O error occurred
+pub fn load_syllable_list(path: &str) -> std::io::Result<HashSet<String>> {
+    let file = std::fs::File::open(path)?;
+    let mut syllables = HashSet::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let syllable = line?.trim().to_lowercase().replace('v', "ü");
+        if !syllable.is_empty() {
+            syllables.insert(syllable);
+        }
+    }
+    Ok(syllables)
+}
+
+/// Writes the onset-rime count matrix of the records as a CSV file
+///
+/// Rows are onsets and columns are rimes, both in chart order (see
+/// [`HanziOnset::all`] and [`HanziRime::all`]). Each cell holds the number of
+/// characters with that onset and rime, `0` for combinations that do not occur.
+/// The result is suitable for a spreadsheet heatmap. Onset and rime analysis is
+/// run on a copy of the records, so they need not be analyzed beforehand.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to count
+/// * `path` - Path of the CSV file to create (overwritten if it exists)
+///
+/// # Returns
+///
+/// * `Ok(())` - The file was written
+/// * `Err(std::io::Error)` - File I/O error occurred
+///
+/// # File Format
+///
+/// ```text
+/// onset,e,a,o,...,none
+/// b,0,12,4,...,0
+/// ...
+/// ```
+pub fn export_matrix_csv(records: &[HanziRecord], path: &str) -> std::io::Result<()> {
+    let mut records_copy = records.to_vec();
+    set_hanzi_onsets(&mut records_copy);
+    set_hanzi_rime(&mut records_copy);
+
+    let mut counts: HashMap<(&HanziOnset, &HanziRime), u32> = HashMap::new();
+    for record in &records_copy {
+        *counts.entry((&record.onset, &record.rime)).or_insert(0) += 1;
+    }
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    let header: Vec<&str> = HanziRime::all().iter().map(HanziRime::as_str).collect();
+    writeln!(writer, "onset,{}", header.join(","))?;
+
+    for onset in HanziOnset::all() {
+        let cells: Vec<String> = HanziRime::all()
+            .iter()
+            .map(|rime| counts.get(&(onset, rime)).copied().unwrap_or(0).to_string())
+            .collect();
+        writeln!(writer, "{},{}", onset.as_str(), cells.join(","))?;
+    }
+
+    writer.flush()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A temporary file or directory, removed on drop even if the test panics
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        /// Returns a path in the temp directory unique to this test process and call
+        fn new(name: &str) -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+            TempPath(std::env::temp_dir().join(format!(
+                "study-rust-hanzi-{}-{id}-{name}",
+                std::process::id()
+            )))
+        }
+
+        fn as_str(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            if self.0.is_dir() {
+                std::fs::remove_dir_all(&self.0).ok();
+            } else {
+                std::fs::remove_file(&self.0).ok();
+            }
+        }
+    }
+
+    /// Writes `contents` to a temporary TSV file and runs `test` with its path
+    fn with_temp_tsv<T>(contents: impl AsRef<[u8]>, test: impl FnOnce(&str) -> T) -> T {
+        let file = TempPath::new("test.tsv");
+        std::fs::write(&file.0, contents).expect("Failed to write test file");
+        test(file.as_str())
+    }
 
     #[test]
     fn test_read_hanzi_file_length() {
@@ -160,4 +715,331 @@ mod tests {
             last_record.tone
         );
     }
+
+    #[test]
+    fn test_strip_tones_matches_dataset() {
+        let records = read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv file");
+
+        for record in &records {
+            assert_eq!(
+                crate::pinyin::strip_tones(&record.pinyin),
+                record.pinyin_without_tone,
+                "Stripping '{}' should give '{}'",
+                record.pinyin,
+                record.pinyin_without_tone
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_character_field() {
+        let (lenient, strict) = with_temp_tsv(
+            "1\t的\t的\tde\tde\t5\n2\t\t一\tyī\tyi\t1\n3\t是\t \tshì\tshi\t4\n",
+            |path| (read_hanzi_file(path), read_hanzi_file_strict(path)),
+        );
+
+        // The lenient reader skips rows with an empty character field
+        let records = lenient.expect("Lenient reading should succeed");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].simplified, "的");
+
+        // The strict reader rejects the first of them
+        let error = strict.expect_err("Strict reading should fail");
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_read_hanzi_file_lossy() {
+        let mut contents = "1\t的\t的\tde\tde\t5\r\n2\t一\t一\tyī\ty"
+            .as_bytes()
+            .to_vec();
+        contents.push(0xFF); // Stray invalid byte in the toneless pinyin field
+        contents.extend_from_slice("i\t1\n".as_bytes());
+
+        let (strict, lossy) = with_temp_tsv(contents, |path| {
+            (read_hanzi_file(path), read_hanzi_file_lossy(path))
+        });
+
+        let error = strict.expect_err("Reading invalid UTF-8 should fail");
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        let records = lossy.expect("Lossy reading should succeed");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].tone, 5);
+        assert_eq!(records[1].simplified, "一");
+        assert_eq!(records[1].pinyin, "yī");
+        assert_eq!(records[1].pinyin_without_tone, "y\u{FFFD}i");
+        assert_eq!(records[1].tone, 1);
+    }
+
+    #[test]
+    fn test_read_hanzi_file_strict_checked() {
+        let result = with_temp_tsv(
+            "1\t的\t的\tde\tde\t5\n2\t一\t一\tyī\tyi\t1\n3\t不\t不\tbuā\tbua\t1\n",
+            read_hanzi_file_strict_checked,
+        );
+
+        let error = result.expect_err("The illegal syllable should be rejected");
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "illegal syllables: line 3: buā");
+    }
+
+    #[test]
+    fn test_read_hanzi_file_strict_checked_sample() {
+        let records =
+            read_hanzi_file_strict_checked("hanzi.tsv").expect("The sample should be legal");
+        assert_eq!(records.len(), 5000);
+        assert_eq!(records[9].onset, HanziOnset::T);
+    }
+
+    #[test]
+    fn test_read_hanzi_dir() {
+        let shards = TempPath::new("shards");
+        let dir = &shards.0;
+        std::fs::create_dir_all(dir).expect("Failed to create test directory");
+        // Written out of order to check that shards are read by name
+        std::fs::write(dir.join("part-2.tsv"), "3\t是\t是\tshì\tshi\t4\n")
+            .expect("Failed to write test file");
+        std::fs::write(
+            dir.join("part-1.tsv"),
+            "1\t的\t的\tde\tde\t5\n2\t一\t一\tyī\tyi\t1\n",
+        )
+        .expect("Failed to write test file");
+        std::fs::write(dir.join("notes.txt"), "not a shard\n").expect("Failed to write test file");
+
+        let records =
+            read_hanzi_dir(shards.as_str()).expect("Reading the directory should succeed");
+        let characters: Vec<&str> = records
+            .iter()
+            .map(|record| record.simplified.as_str())
+            .collect();
+        assert_eq!(characters, vec!["的", "一", "是"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_read_hanzi_jsonl_round_trip() {
+        let records = read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv file");
+        let mut records = records[..20].to_vec();
+        set_hanzi_onsets(&mut records);
+        set_hanzi_rime(&mut records);
+
+        let file = TempPath::new("test.jsonl");
+        let contents: String = records
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap() + "\n")
+            .collect();
+        std::fs::write(&file.0, contents).expect("Failed to write test file");
+
+        assert_eq!(
+            read_hanzi_jsonl(file.as_str()).expect("Reading JSON lines should succeed"),
+            records
+        );
+    }
+
+    #[test]
+    fn test_read_hanzi_file_with_neutral_tone() {
+        let (default, neutral_zero) =
+            with_temp_tsv("1\t吗\t嗎\tma\tma\t0\n2\t马\t馬\tmǎ\tma\t3\n", |path| {
+                (
+                    read_hanzi_file(path),
+                    read_hanzi_file_with_neutral_tone(path, 0),
+                )
+            });
+
+        // By default tone 0 is unknown and sorts after the neutral tone
+        let groups = crate::grouping::group_by_tone(&default.unwrap(), "ma", false).unwrap();
+        assert_eq!(groups.last().unwrap().0, 0);
+
+        let records = neutral_zero.expect("Reading should succeed");
+        assert_eq!(records[0].tone, 5);
+        assert_eq!(records[1].tone, 3);
+        let groups = crate::grouping::group_by_tone(&records, "ma", false).unwrap();
+        assert_eq!(
+            groups,
+            vec![
+                (3, "mǎ".to_string(), vec!["马".to_string()]),
+                (5, "ma".to_string(), vec!["吗".to_string()])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_hanzi_file_trims_fields() {
+        let result = with_temp_tsv(" 3 \t 马 \t馬 \t mǎ\t ma \t 3\r\n", read_hanzi_file);
+
+        let records = result.expect("Reading should succeed");
+        assert_eq!(records[0].frequency, 3);
+        assert_eq!(records[0].simplified, "马");
+        assert_eq!(records[0].traditional, "馬");
+        assert_eq!(records[0].pinyin, "mǎ");
+        assert_eq!(records[0].pinyin_without_tone, "ma");
+
+        // The padded pinyin matches a "ma" query
+        let groups =
+            crate::grouping::group_by_tone(&records, "ma", false).expect("ma should be found");
+        assert_eq!(groups[0].2, vec!["马"]);
+    }
+
+    #[test]
+    fn test_read_hanzi_file_crlf_line_endings() {
+        let (lenient, strict) = with_temp_tsv(
+            "1\t的\t的\tde\tde\t5\r\n2\t一\t一\tyī\tyi\t1\r\n3\t是\t是\tshì\tshi\t4\r\n",
+            |path| (read_hanzi_file(path), read_hanzi_file_strict(path)),
+        );
+
+        let records = lenient.expect("Reading should succeed");
+        let tones: Vec<u32> = records.iter().map(|record| record.tone).collect();
+        assert_eq!(tones, vec![5, 1, 4]);
+        assert_eq!(strict.expect("Strict reading should succeed"), records);
+    }
+
+    #[test]
+    fn test_load_syllable_list() {
+        let file = TempPath::new("syllables.txt");
+        std::fs::write(&file.0, "ma\n Sei \n\nlv\r\n").expect("Failed to write test file");
+
+        let result = load_syllable_list(file.as_str());
+
+        let syllables = result.expect("Loading should succeed");
+        assert_eq!(syllables.len(), 3);
+        assert!(syllables.contains("sei") && syllables.contains("lü"));
+
+        // Validation follows the custom list rather than standard Mandarin
+        assert!(crate::is_known_pinyin_with_syllables("sei", &syllables));
+        assert!(crate::is_known_pinyin_with_syllables("LV", &syllables));
+        assert!(!crate::is_known_pinyin_with_syllables("ni", &syllables));
+        assert!(crate::is_known_pinyin("ni"));
+        assert_eq!(
+            crate::syllabify_with_syllables("seima", &syllables),
+            vec!["sei", "ma"]
+        );
+
+        assert!(load_syllable_list("no-such-syllables.txt").is_err());
+    }
+
+    #[test]
+    fn test_read_hanzi_file_with_progress() {
+        let line_count = 2 * PROGRESS_INTERVAL + 500;
+        let content: String = (1..=line_count)
+            .map(|rank| format!("{rank}\t的\t的\tde\tde\t5\n"))
+            .collect();
+
+        let mut calls = Vec::new();
+        let result = with_temp_tsv(content, |path| {
+            read_hanzi_file_with_progress(path, |lines| calls.push(lines))
+        });
+
+        assert_eq!(result.unwrap().len(), line_count);
+        assert_eq!(calls, vec![PROGRESS_INTERVAL, 2 * PROGRESS_INTERVAL]);
+    }
+
+    #[test]
+    fn test_read_hanzi_file_filtered() {
+        let records = read_hanzi_file_filtered("hanzi.tsv", |record| record.onset == HanziOnset::M)
+            .expect("Failed to read hanzi.tsv file");
+
+        assert!(!records.is_empty());
+        assert!(records.iter().all(|record| record.onset == HanziOnset::M));
+        assert!(records.iter().any(|record| record.simplified == "马"));
+        // The rime is analyzed as well
+        assert!(records.iter().all(|record| record.rime != HanziRime::None));
+
+        // The same records as filtering after a full read
+        let mut all = read_hanzi_file("hanzi.tsv").unwrap();
+        set_hanzi_onsets(&mut all);
+        let expected = all
+            .iter()
+            .filter(|record| record.onset == HanziOnset::M)
+            .count();
+        assert_eq!(records.len(), expected);
+    }
+
+    #[test]
+    fn test_parse_tone_field() {
+        assert_eq!(parse_tone_field("3"), 3);
+        assert_eq!(parse_tone_field(" 3 "), 3);
+        assert_eq!(parse_tone_field("tone 3"), 3);
+        assert_eq!(parse_tone_field("Tone 5\r"), 5);
+        assert_eq!(parse_tone_field("tone"), 0);
+        assert_eq!(parse_tone_field(""), 0);
+    }
+
+    #[test]
+    fn test_read_hanzi_file_spaced_tone_field() {
+        let records = with_temp_tsv(
+            "1\t马\t馬\tmǎ\tma\t 3 \n2\t妈\t媽\tmā\tma\ttone 1\n",
+            read_hanzi_file,
+        );
+
+        let records = records.expect("Reading should succeed");
+        assert_eq!(records[0].tone, 3);
+        assert_eq!(records[1].tone, 1);
+    }
+
+    #[test]
+    fn test_read_hanzi_file_strict_accepts_dataset() {
+        let records = read_hanzi_file_strict("hanzi.tsv").expect("hanzi.tsv should be well-formed");
+        assert_eq!(records.len(), 5000);
+    }
+
+    #[test]
+    fn test_read_hanzi_file_full() {
+        let records = read_hanzi_file_full("tests/fixtures/hanzi_full.tsv").unwrap();
+        assert_eq!(records.len(), 5);
+
+        assert_eq!(records[0].simplified, "中");
+        assert_eq!(records[0].onset, HanziOnset::Zh);
+        assert_eq!(records[0].rime, HanziRime::Ong);
+        assert_eq!(records[1].onset, HanziOnset::X);
+        assert_eq!(records[1].rime, HanziRime::Ue);
+
+        // The empty onset of a vowel-initial syllable falls back to None
+        assert_eq!(records[2].onset, HanziOnset::None);
+        assert_eq!(records[2].rime, HanziRime::An);
+
+        // An invalid rime falls back to None
+        assert_eq!(records[3].onset, HanziOnset::D);
+        assert_eq!(records[3].rime, HanziRime::None);
+
+        // So do the onset and rime of a 6-column line
+        assert_eq!(records[4].onset, HanziOnset::None);
+        assert_eq!(records[4].rime, HanziRime::None);
+
+        // The plain reader ignores the extra columns
+        let plain = read_hanzi_file("tests/fixtures/hanzi_full.tsv").unwrap();
+        assert_eq!(plain[0].onset, HanziOnset::None);
+        assert_eq!(plain[0].rime, HanziRime::None);
+    }
+
+    #[test]
+    fn test_export_matrix_csv() {
+        let records = vec![
+            HanziRecord::builder("机", "jī", 1).build(),
+            HanziRecord::builder("计", "jì", 4).build(),
+            HanziRecord::builder("马", "mǎ", 3).build(),
+        ];
+        let file = TempPath::new("matrix.csv");
+
+        export_matrix_csv(&records, file.as_str()).expect("Failed to write CSV");
+        let csv = std::fs::read_to_string(&file.0).expect("Failed to read CSV");
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("onset,e,a,o,ei,"));
+        assert!(lines[0].ends_with(",none"));
+        assert_eq!(lines.len(), 1 + HanziOnset::all().len());
+
+        let cell = |onset: &str, rime: &str| -> String {
+            let column = lines[0].split(',').position(|name| name == rime).unwrap();
+            let row = lines
+                .iter()
+                .find(|line| line.split(',').next() == Some(onset))
+                .unwrap();
+            row.split(',').nth(column).unwrap().to_string()
+        };
+        assert_eq!(cell("j", "i"), "2");
+        assert_eq!(cell("m", "a"), "1");
+        assert_eq!(cell("b", "a"), "0");
+    }
 }
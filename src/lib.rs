@@ -7,41 +7,174 @@
 //! ## Core Data Structures
 //!
 //! - [`HanziRecord`]: Represents a single Chinese character with all its linguistic properties
+//! - [`HanziRecordBuilder`]: Builds a [`HanziRecord`] with derived default fields
 //! - [`HanziOnset`]: Enumeration of pinyin onset sounds (initial consonants)
 //! - [`HanziRime`]: Enumeration of pinyin rime sounds (vowels and final consonants)
+//! - [`ToneLookupError`]: Error returned when a tone lookup finds no characters
 //!
 //! ## Main Functions
 //!
 //! - [`read_hanzi_file`]: Reads character data from TSV files
+//! - [`read_hanzi_file_strict`]: Reads character data from TSV files, rejecting malformed lines
+//! - [`read_hanzi_file_strict_checked`]: Reads character data strictly, rejecting illegal syllables
+//! - [`read_hanzi_file_full`]: Reads character data along with onset and rime columns
+//! - [`read_hanzi_file_lossy`]: Reads character data, replacing invalid UTF-8 instead of failing
+//! - [`read_hanzi_file_with_progress`]: Reads character data, reporting the line count as it goes
+//! - [`read_hanzi_file_with_neutral_tone`]: Reads character data whose neutral tone is written as another digit
+//! - [`read_hanzi_file_filtered`]: Reads only the analyzed records passing a predicate
+//! - [`read_hanzi_dir`]: Reads and concatenates every TSV file of a directory
+//! - `read_hanzi_jsonl`: Reads records from a JSON-lines file (requires the `serde` feature)
+//! - [`load_syllable_list`]: Reads a custom list of valid syllables
+//! - [`export_matrix_csv`]: Writes the onset-rime count matrix as a CSV file
 //! - [`group_by_pinyin`]: Groups characters by pinyin pronunciation
+//! - [`group_by_pinyin_ordered`]: Groups characters by pinyin with a selectable [`TieBreak`]
 //! - [`group_by_tone`]: Groups characters by specific pinyin and tone
+//! - [`try_group_by_tone`]: Groups characters by pinyin and tone, with an error naming a missing pinyin
+//! - [`group_by_tone_by_frequency`]: Groups characters by pinyin and tone, most common first
+//! - [`distinct_pinyin`]: Lists every distinct pinyin in alphabetical order
+//! - [`occurring_syllables`]: Lists every distinct tone-marked pinyin
+//! - [`traditional_only`]: Lists the records whose traditional form is not a simplified form
+//! - [`sort_by_onset_and_rime`]: Orders pinyin groups by onset, then rime, in chart order
 //! - [`format_pinyin_output`]: Formats pinyin grouping results for display
+//! - [`format_pinyin_output_colored`]: Formats pinyin grouping results colored by frequency tier
+//! - [`format_pinyin_counts`]: Formats pinyin grouping results as a pinyin-and-count table
+//! - [`format_pinyin_output_with_ranges`]: Formats pinyin grouping results with the frequency rank range of each group
+//! - [`frequency_range_by_pinyin`]: Finds the lowest and highest frequency rank of each pinyin
+//! - [`frequency_map`]: Maps each character to its frequency rank
+//! - [`format_pinyin_output_truncated`]: Formats pinyin grouping results with at most K characters per group
+//! - [`format_pinyin_table`]: Formats pinyin grouping results as a bordered table
+//! - [`format_pinyin_csv`]: Formats pinyin grouping results as CSV
+//! - [`format_pinyin_toml`]: Formats pinyin grouping results as a TOML document
+//! - [`format_pinyin_syllable_parts`]: Formats pinyin grouping results with the onset or rime of each pinyin
+//! - [`SyllablePart`]: Part of a syllable shown in place of the characters
+//! - [`PinyinFormat`]: Column widths used when formatting pinyin groupings
+//! - [`PinyinGrouper`]: Groups characters by pinyin one record at a time
+//! - [`OnsetIndex`]: Looks up the characters of an onset without rescanning the records
 //! - [`format_tone_output`]: Formats tone grouping results for display
+//! - [`format_tone_output_with_contour`]: Formats tone grouping results labeled with tone contours
+//! - [`format_tone_output_numbered`]: Formats tone grouping results with tone numbers (e.g. `ma3`)
+//! - [`format_tone_output_paired`]: Formats tone grouping results with traditional forms in parentheses
+//! - [`format_tone_pinyin_only`]: Formats tone grouping results as tone-marked pinyin only
+//! - [`count_by_tone`]: Counts characters by tone number
+//! - [`onset_tone_counts`]: Counts the distinct characters of each onset and tone
+//! - [`format_tone_counts`]: Formats tone counts for display
+//! - [`format_tone_distribution`]: Formats tone counts as a single `Tones:` line
+//! - [`homophones`]: Lists characters sharing both pinyin and tone
+//! - [`representative_characters`]: Lists the most common character of every pinyin
+//! - [`rarest_characters`]: Lists the rarest character of every pinyin
+//! - [`top_characters`]: Lists the most or least common characters, by [`RankDirection`]
+//! - [`full_paradigm_syllables`]: Lists the pinyin that occur in all four tones
+//! - [`tone_minimal_pairs`]: Lists syllables whose onset and rime occur in several tones
+//! - [`export_chart_json`]: Exports the characters of every syllable and tone as JSON
+//! - [`group_by_rime`]: Counts characters by rime type
+//! - [`distinct_syllables_per_rime`]: Counts the distinct syllables ending in each rime
+//! - [`format_rime_output`]: Formats rime counts for display
+//! - [`group_by_rime_and_onset`]: Groups the characters of one rime by onset
+//! - [`collect_by_rime`]: Collects the characters of one rime for each onset, empty if none
+//! - [`search_syllables`]: Lists the syllables matching an onset and rime pattern with wildcards
+//! - [`SyllableQuery`]: Onset and rime pattern, either of which may be left open
+//! - [`onset_learning_order`]: Suggests a teaching order of onsets, most common first
+//! - [`rime_learning_order`]: Suggests a teaching order of rimes, most common first
+//! - [`include_empty_onsets`]: Completes onset counts with the onsets that do not occur
+//! - [`unused_onsets`]: Lists the onsets that do not occur in the records
+//! - [`merge_impact`]: Counts the characters affected by merging one onset into another
+//! - [`group_by_structure`]: Counts characters by syllable structure (CV, VC, ...)
+//! - [`format_onset_output_pct`]: Formats onset counts with each onset's share of the total
+//! - [`format_onset_summary`]: Summarizes each onset on one line with sample characters
 //!
 //! ## Linguistic Analysis
 //!
 //! - [`set_hanzi_onsets`]: Analyzes and sets onset information for characters
+//! - [`set_hanzi_onsets_with_candidates`]: Sets onsets using a caller-provided onset list
 //! - [`set_hanzi_rime`]: Analyzes and sets rime information for characters
+//! - [`refresh_phonetics`]: Recomputes onset and rime, discarding existing values
+//! - [`analyze_verbose`]: Reports the onset, matched prefix length and rime of a record
+//! - [`longest_onset_match`]: Finds the longest onset at the start of a toneless pinyin
+//! - [`parse_syllable`]: Splits a toneless syllable into onset and rime
+//! - [`parse_marked_syllable`]: Splits a tone-marked syllable into onset, rime and tone (e.g. "mǎ" → M, A, 3)
+//! - [`is_valid_syllable`]: Checks whether an onset and rime form a standard syllable
+//! - [`is_known_pinyin`]: Checks whether a toneless string is a standard syllable
+//! - [`is_known_pinyin_with_syllables`]: Checks a toneless string against a custom syllable list
+//! - [`valid_onsets_for`]: Lists the onsets that combine with a rime
+//! - [`parse_tone_query`]: Splits a pinyin query into toneless pinyin and optional tone
+//! - [`tone_contour`]: Names the pitch contour of a tone (e.g. 3 → "dipping")
+//! - [`syllable_structure`]: Classifies the consonant-vowel structure of a syllable (e.g. "an" → "VC")
+//!
+//! ## Pinyin Spelling
+//!
+//! - [`syllabify`]: Splits run-on pinyin into syllables
+//! - [`syllabify_with_syllables`]: Splits run-on pinyin using a custom syllable list
+//! - [`normalize_fullwidth`]: Converts full-width ASCII letters and digits to half-width
+//! - [`render_pinyin`]: Spells an onset, rime and tone as tone-marked pinyin
+//!
+//! ## Statistics
+//!
+//! - [`onset_entropy`]: Shannon entropy of the onset distribution
+//! - [`rime_entropy`]: Shannon entropy of the rime distribution
+//! - [`weighted_onset_counts`]: Onset histogram weighted by frequency rank
+//! - [`weighted_rime_counts`]: Rime histogram weighted by frequency rank
+//! - [`mean_frequency_by_onset`]: Mean frequency rank of the characters of each onset
+//! - [`Weighting`]: How a frequency rank is turned into a weight
+//! - [`summarize`]: Computes record, pinyin, onset, rime and tone counts in one call
+//! - [`Summary`]: The counts returned by [`summarize`]
+//! - [`frequency_rank_issues`]: Reports gaps and duplicates in the frequency ranks
+//! - [`renumber_frequency`]: Renumbers the frequency ranks sequentially from 1
 
 pub mod analysis;
 pub mod grouping;
 pub mod io;
+mod pinyin;
+pub mod stats;
 pub mod types;
 
 // Re-export the types module for public API
-pub use crate::types::{HanziOnset, HanziRecord, HanziRime};
+pub use crate::types::{HanziOnset, HanziRecord, HanziRecordBuilder, HanziRime, ToneLookupError};
 
 // Re-export the io module functions for backward compatibility
-pub use crate::io::read_hanzi_file;
+#[cfg(feature = "serde")]
+pub use crate::io::read_hanzi_jsonl;
+pub use crate::io::{
+    export_matrix_csv, load_syllable_list, read_hanzi_dir, read_hanzi_file,
+    read_hanzi_file_filtered, read_hanzi_file_full, read_hanzi_file_lossy, read_hanzi_file_strict,
+    read_hanzi_file_strict_checked, read_hanzi_file_with_neutral_tone,
+    read_hanzi_file_with_progress, PROGRESS_INTERVAL,
+};
 
 // Re-export the grouping module functions for backward compatibility
 pub use crate::grouping::{
-    format_onset_output, format_onset_pinyin_output, format_pinyin_output, format_tone_output,
-    group_by_onset, group_by_onset_and_pinyin, group_by_pinyin, group_by_tone,
+    collect_by_rime, count_by_tone, distinct_pinyin, distinct_syllables_per_rime,
+    export_chart_json, format_onset_output, format_onset_output_pct, format_onset_pinyin_output,
+    format_onset_summary, format_pinyin_counts, format_pinyin_csv, format_pinyin_output,
+    format_pinyin_output_colored, format_pinyin_output_truncated, format_pinyin_output_with_ranges,
+    format_pinyin_syllable_parts, format_pinyin_table, format_pinyin_toml, format_rime_output,
+    format_tone_counts, format_tone_distribution, format_tone_output, format_tone_output_numbered,
+    format_tone_output_paired, format_tone_output_with_contour, format_tone_pinyin_only,
+    frequency_map, frequency_range_by_pinyin, full_paradigm_syllables, group_by_onset,
+    group_by_onset_and_pinyin, group_by_pinyin, group_by_pinyin_ordered, group_by_rime,
+    group_by_rime_and_onset, group_by_structure, group_by_tone, group_by_tone_by_frequency,
+    homophones, include_empty_onsets, merge_impact, min_frequency_by_pinyin, occurring_syllables,
+    onset_learning_order, onset_tone_counts, rarest_characters, representative_characters,
+    rime_learning_order, search_syllables, sort_by_onset_and_rime, tone_minimal_pairs,
+    top_characters, traditional_only, try_group_by_tone, unused_onsets, OnsetIndex, PinyinFormat,
+    PinyinGrouper, RankDirection, SyllablePart, SyllableQuery, TieBreak,
 };
 
 // Re-export the analysis module functions for backward compatibility
-pub use crate::analysis::{set_hanzi_onsets, set_hanzi_rime};
+pub use crate::analysis::{
+    analyze_verbose, is_known_pinyin, is_known_pinyin_with_syllables, is_valid_syllable,
+    longest_onset_match, parse_marked_syllable, parse_syllable, parse_tone_query,
+    refresh_phonetics, set_hanzi_onsets, set_hanzi_onsets_with_candidates, set_hanzi_rime,
+    syllable_structure, tone_contour, valid_onsets_for,
+};
+
+// Re-export the pinyin module functions
+pub use crate::pinyin::{normalize_fullwidth, render_pinyin, syllabify, syllabify_with_syllables};
+
+// Re-export the stats module functions
+pub use crate::stats::{
+    frequency_rank_issues, mean_frequency_by_onset, onset_entropy, renumber_frequency,
+    rime_entropy, summarize, weighted_onset_counts, weighted_rime_counts, Summary, Weighting,
+};
 
 #[cfg(test)]
 mod tests {
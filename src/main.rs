@@ -8,6 +8,12 @@
 //!
 //! - **pinyin**: Groups characters by their pinyin pronunciation (without tone marks)
 //! - **onset**: Groups characters by onset (initial consonant) sounds and shows counts, or filters by specific onset to show pinyin groupings
+//! - **rime**: Shows the characters of one rime grouped by onset
+//! - **stats**: Shows the number of records and pinyin and the tone distribution
+//! - **search**: Lists the syllables matching an onset and rime, either of which may be '*'
+//! - **list**: Lists all characters grouped by pinyin, or counted by tone, onset or rime
+//! - **validate**: Reports records whose syllable cannot be split into onset and rime
+//! - **diff**: Shows characters added or removed between two data files
 //! - **generate-completion**: Creates shell completion scripts for better CLI experience
 //!
 //! ## Examples
@@ -22,9 +28,33 @@
 //! # Show traditional characters instead of simplified
 //! study-rust-hanzi pinyin --traditional
 //!
+//! # Show only the pinyin and character counts
+//! study-rust-hanzi pinyin --counts-only
+//!
+//! # Color common syllables even when piping into a pager
+//! study-rust-hanzi pinyin --color always | less -R
+//!
+//! # List only the syllables that occur in all four tones
+//! study-rust-hanzi pinyin --full-paradigm
+//!
+//! # Draw the listing as a bordered table
+//! study-rust-hanzi pinyin --format table
+//!
+//! # Show the rime of each pinyin instead of its characters
+//! study-rust-hanzi pinyin --show rime
+//!
 //! # Show all characters with pinyin "ma" grouped by tone
 //! study-rust-hanzi pinyin ma
 //!
+//! # Show only the third-tone characters of "ma" (same as "mǎ")
+//! study-rust-hanzi pinyin ma3
+//!
+//! # Show the characters of "ma" without the neutral tone
+//! study-rust-hanzi pinyin ma --no-neutral
+//!
+//! # Show only the tone-marked forms of "ma" that occur in the data
+//! study-rust-hanzi pinyin ma --pinyin-only
+//!
 //! # Show traditional characters for pinyin "nv" (converted to "nü")
 //! study-rust-hanzi pinyin nv --traditional
 //!
@@ -40,6 +70,30 @@
 //! # Show characters for onset 'j' with line folding at 30 characters
 //! study-rust-hanzi onset j --fold 30
 //!
+//! # Show characters with rime 'ang' grouped by onset
+//! study-rust-hanzi rime ang
+//!
+//! # Show the record count and the tone distribution
+//! study-rust-hanzi stats
+//!
+//! # Show every syllable with onset 'm', whatever its rime
+//! study-rust-hanzi search --onset m --rime '*'
+//!
+//! # Show character counts grouped by rime
+//! study-rust-hanzi list --group-by rime
+//!
+//! # Report records whose syllable cannot be analyzed
+//! study-rust-hanzi validate
+//!
+//! # Show characters added or removed between two versions of the data file
+//! study-rust-hanzi diff old.tsv new.tsv
+//!
+//! # Write the results to a file instead of stdout
+//! study-rust-hanzi pinyin --output results.txt
+//!
+//! # Print analysis statistics of the listing to stderr
+//! study-rust-hanzi pinyin --verbose
+//!
 //! # Generate bash completion script
 //! study-rust-hanzi generate-completion bash > completion.bash
 //! ```
@@ -50,13 +104,26 @@
 //! which should contain tab-separated values with frequency, simplified character,
 //! traditional character, pinyin with tone marks, pinyin without tone marks, and tone number.
 
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{generate, Generator, Shell};
-use std::io::{self, Write};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use study_rust_hanzi::{
-    format_onset_output, format_onset_pinyin_output, format_pinyin_output, format_tone_output,
-    group_by_onset, group_by_onset_and_pinyin, group_by_pinyin, group_by_tone, read_hanzi_file,
-    set_hanzi_onsets, set_hanzi_rime, HanziOnset,
+    collect_by_rime, count_by_tone, distinct_pinyin, format_onset_output,
+    format_onset_pinyin_output, format_pinyin_counts, format_pinyin_csv, format_pinyin_output,
+    format_pinyin_output_colored, format_pinyin_output_truncated, format_pinyin_output_with_ranges,
+    format_pinyin_syllable_parts, format_pinyin_table, format_pinyin_toml, format_rime_output,
+    format_tone_counts, format_tone_distribution, format_tone_output, format_tone_output_numbered,
+    format_tone_output_paired, format_tone_output_with_contour, format_tone_pinyin_only,
+    frequency_range_by_pinyin, full_paradigm_syllables, group_by_onset, group_by_onset_and_pinyin,
+    group_by_pinyin, group_by_rime, group_by_tone, group_by_tone_by_frequency,
+    include_empty_onsets, load_syllable_list, min_frequency_by_pinyin, normalize_fullwidth,
+    parse_tone_query, read_hanzi_file, search_syllables, set_hanzi_onsets, set_hanzi_rime,
+    sort_by_onset_and_rime, summarize, HanziOnset, HanziRecord, HanziRime, PinyinFormat,
+    SyllablePart, SyllableQuery,
 };
 
 /// Hanzi learning program
@@ -70,6 +137,9 @@ use study_rust_hanzi::{
 struct Args {
     #[command(subcommand)]
     command: Commands,
+    /// Write the results to FILE instead of stdout
+    #[arg(short, long, global = true, value_name = "FILE")]
+    output: Option<PathBuf>,
 }
 
 /// Available commands for the Hanzi learning program
@@ -83,29 +153,191 @@ struct Args {
 enum Commands {
     /// List unique pinyin with frequency and characters
     Pinyin {
-        /// The pinyin (without tone marks) to search for. Use 'v' for 'ü' (e.g., 'nv' for 'nü')
+        /// The pinyin to search for. Use 'v' for 'ü' (e.g., 'nv' for 'nü'). A trailing tone
+        /// digit or a tone mark (e.g., 'ma3' or 'mǎ') restricts the results to that tone; a
+        /// trailing '0' (e.g., 'ma0') means any tone
         /// Optional pinyin to filter results ( e.g. "ma" to show only characters with that pinyin)
         pinyin: Option<String>,
         /// Fold long lines when character count exceeds specified value (default: 50)
-        #[arg(short, long, value_name = "WIDTH", default_missing_value = "50", num_args = 0..=1)]
+        #[arg(
+            short,
+            long,
+            value_name = "WIDTH",
+            default_missing_value = "50",
+            num_args = 0..=1,
+            value_parser = parse_fold_width
+        )]
         fold: Option<usize>,
         /// Use traditional characters instead of simplified
         #[arg(short, long)]
         traditional: bool,
+        /// Print only the pinyin and character count, omitting the characters
+        #[arg(long, conflicts_with_all = ["pinyin", "fold"])]
+        counts_only: bool,
+        /// Color each pinyin by the frequency rank of its most common character
+        #[arg(
+            long,
+            value_enum,
+            value_name = "WHEN",
+            default_value_t = ColorMode::Auto,
+            conflicts_with_all = ["pinyin", "counts_only"]
+        )]
+        color: ColorMode,
+        /// With a pinyin, print only the tone-marked pinyin forms, omitting the characters
+        #[arg(long, requires = "pinyin")]
+        pinyin_only: bool,
+        /// With a pinyin, leave out neutral-tone characters so only tones 1-4 appear
+        #[arg(long, requires = "pinyin")]
+        no_neutral: bool,
+        /// With a pinyin, label each tone with its contour (e.g. 'dipping' for tone 3)
+        #[arg(long, requires = "pinyin", conflicts_with = "pinyin_only")]
+        contour: bool,
+        /// With a pinyin, show tone numbers instead of tone marks (e.g. 'ma3' for 'mǎ')
+        #[arg(long, requires = "pinyin", conflicts_with_all = ["pinyin_only", "contour"])]
+        numbered: bool,
+        /// With a pinyin, show each character with its traditional form, e.g. '马(馬)'
+        #[arg(
+            long,
+            requires = "pinyin",
+            conflicts_with_all = ["traditional", "pinyin_only", "contour", "numbered"]
+        )]
+        both: bool,
+        /// Show the characters, or the rime or onset of each pinyin instead
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = ShowField::Characters,
+            conflicts_with_all = ["pinyin", "counts_only", "fold", "color"]
+        )]
+        show: ShowField,
+        /// List only syllables with characters in each of the tones 1-4
+        #[arg(long, visible_alias = "top-tones", conflicts_with = "pinyin")]
+        full_paradigm: bool,
+        /// Print a plain list, a table with box-drawing borders, or CSV
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = ListFormat::List,
+            conflicts_with_all = ["pinyin", "counts_only", "fold", "color", "show"]
+        )]
+        format: ListFormat,
+        /// Show at most K characters per pinyin, followed by the number left out
+        #[arg(
+            long,
+            value_name = "K",
+            conflicts_with_all = ["pinyin", "counts_only", "fold", "color", "show", "format"]
+        )]
+        max_chars: Option<usize>,
+        /// Append the lowest and highest frequency rank of each pinyin, e.g. '[rank 5–601]'
+        #[arg(
+            long,
+            conflicts_with_all = ["pinyin", "counts_only", "color", "show", "format", "max_chars"]
+        )]
+        freq_range: bool,
+        /// Order the pinyin by group size, by onset then rime, alphabetically or by frequency.
+        /// With [PINYIN], only 'freq' is allowed: characters and tone groups most common first
+        #[arg(long, value_enum, default_value_t = SortOrder::Group)]
+        sort: SortOrder,
+        /// Print analysis statistics of the listing to stderr
+        #[arg(short, long, conflicts_with = "pinyin")]
+        verbose: bool,
     },
     /// Show character counts grouped by onset (initial consonant) sounds
     Onset {
         /// Optional onset to filter by (e.g., 'j', 'zh', 'none'). If provided, groups characters by pinyin within that onset
         onset: Option<String>,
         /// Fold long lines when character count exceeds specified value (default: 50)
-        #[arg(short, long, value_name = "WIDTH", default_missing_value = "50", num_args = 0..=1)]
+        #[arg(
+            short,
+            long,
+            value_name = "WIDTH",
+            default_missing_value = "50",
+            num_args = 0..=1,
+            value_parser = parse_fold_width
+        )]
         fold: Option<usize>,
         /// Use traditional characters instead of simplified
         #[arg(short, long)]
         traditional: bool,
+        /// List every onset, with a count of 0 for those that do not occur
+        #[arg(long, conflicts_with = "onset")]
+        include_empty: bool,
+    },
+    /// Show the characters of a rime grouped by onset
+    #[command(visible_alias = "by-rime")]
+    Rime {
+        /// The rime to filter by (e.g., 'ang', 'ü', 'v' for 'ü')
+        rime: String,
+        /// List the characters of each onset instead of only counting them
+        #[arg(short, long)]
+        list: bool,
+        /// Fold long lines when character count exceeds specified value (default: 50)
+        #[arg(
+            short,
+            long,
+            value_name = "WIDTH",
+            default_missing_value = "50",
+            num_args = 0..=1,
+            value_parser = parse_fold_width,
+            requires = "list"
+        )]
+        fold: Option<usize>,
+        /// Use traditional characters instead of simplified
+        #[arg(short, long)]
+        traditional: bool,
+    },
+    /// Show summary statistics of the dataset
+    Stats,
+    /// Show all onsets and rimes side by side in chart order
+    Inventory,
+    /// Search syllables by onset and rime, either of which may be a wildcard
+    Search {
+        /// The onset to match (e.g., 'm', 'zh', 'none'), or '*' for any onset
+        #[arg(long, default_value = "*")]
+        onset: String,
+        /// The rime to match (e.g., 'ang', 'ü', 'v' for 'ü'), or '*' for any rime
+        #[arg(long, default_value = "*")]
+        rime: String,
+        /// Fold long lines when character count exceeds specified value (default: 50)
+        #[arg(
+            short,
+            long,
+            value_name = "WIDTH",
+            default_missing_value = "50",
+            num_args = 0..=1,
+            value_parser = parse_fold_width
+        )]
+        fold: Option<usize>,
+        /// Use traditional characters instead of simplified
+        #[arg(short, long)]
+        traditional: bool,
+    },
+    /// List all characters grouped by pinyin, or counted by tone, onset or rime
+    List {
+        /// What to group the characters by
+        #[arg(long, value_enum, default_value_t = GroupKey::Pinyin)]
+        group_by: GroupKey,
+        /// Use traditional characters instead of simplified (pinyin grouping only)
+        #[arg(short, long)]
+        traditional: bool,
     },
     /// Convert hanzi.tsv to hanzi_2.tsv
     Convert,
+    /// Validate hanzi.tsv and report records whose syllable cannot be analyzed
+    Validate,
+    /// Show characters added, removed or with changed pinyin between two data files
+    Diff {
+        /// The older data file
+        old: PathBuf,
+        /// The newer data file
+        new: PathBuf,
+    },
+    /// Compare the syllables of hanzi.tsv with a reference list of syllables
+    Coverage {
+        /// The reference list, one toneless syllable per line ('v' may be typed for 'ü')
+        #[arg(long, value_name = "FILE")]
+        reference: PathBuf,
+    },
     /// Generate shell completion scripts
     GenerateCompletion {
         /// The shell to generate completion script for
@@ -114,6 +346,152 @@ enum Commands {
     },
 }
 
+/// What the list command groups the characters by
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GroupKey {
+    /// Pinyin without tone marks, showing the characters of each
+    Pinyin,
+    /// Tone number, showing the character count of each
+    Tone,
+    /// Onset, showing the character count of each
+    Onset,
+    /// Rime, showing the character count of each
+    Rime,
+}
+
+/// When to color the output of the pinyin command
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorMode {
+    /// Color only when stdout is a terminal
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+/// What the pinyin command shows after each pinyin
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ShowField {
+    /// The characters with that pinyin
+    Characters,
+    /// The rime class of the syllable (e.g. `Ong`)
+    Rime,
+    /// The onset class of the syllable (e.g. `Zh`)
+    Onset,
+}
+
+/// How the pinyin command orders its groups
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SortOrder {
+    /// By number of characters, largest group first
+    Group,
+    /// By onset, then rime, in pinyin chart order
+    Onset,
+    /// Alphabetically by pinyin
+    Alpha,
+    /// By the frequency rank of the most common character, most common first
+    Freq,
+}
+
+/// How the pinyin command lays out its listing
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ListFormat {
+    /// One `pinyin : count characters` line per pinyin
+    List,
+    /// A table with box-drawing borders
+    Table,
+    /// CSV with `pinyin,count,characters` columns
+    Csv,
+    /// A TOML document mapping each pinyin to its character array
+    Toml,
+}
+
+impl ColorMode {
+    /// Returns whether escape codes should be written
+    ///
+    /// `to_stdout` tells whether the output goes to stdout rather than an `--output` file;
+    /// `auto` only colors a terminal stdout.
+    fn enabled(self, to_stdout: bool) -> bool {
+        match self {
+            ColorMode::Auto => to_stdout && io::stdout().is_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Parses the `--fold` width, which must be at least 1
+fn parse_fold_width(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("the width must be at least 1".to_string()),
+        Ok(width) => Ok(width),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Options of the pinyin listing, collected from the `pinyin` command without a pinyin
+struct PinyinListOptions {
+    /// Optional width for line folding of long character lists
+    fold_size: Option<usize>,
+    /// Whether to display traditional characters instead of simplified
+    use_traditional: bool,
+    /// Whether to print only the pinyin and count, omitting the characters
+    counts_only: bool,
+    /// Whether to color each pinyin by the frequency tier of its most common character
+    colored: bool,
+    /// Whether to show the characters, or the rime or onset of each pinyin
+    show: ShowField,
+    /// Whether to keep only syllables with characters in each of the tones 1-4
+    full_paradigm: bool,
+    /// Whether to print a plain list or a bordered table
+    list_format: ListFormat,
+    /// Optional maximum number of characters shown per pinyin
+    max_chars: Option<usize>,
+    /// Whether to append the frequency rank range of each pinyin
+    freq_range: bool,
+    /// The order of the pinyin groups
+    sort: SortOrder,
+    /// Whether to print analysis statistics to stderr
+    verbose: bool,
+}
+
+/// Options of the tone listing, collected from the `pinyin` command with a pinyin
+struct ToneQueryOptions {
+    /// Whether to display traditional characters instead of simplified
+    use_traditional: bool,
+    /// Whether to print only the tone-marked pinyin forms on a single line
+    pinyin_only: bool,
+    /// Whether to leave out neutral-tone (5) characters
+    no_neutral: bool,
+    /// Whether to label each tone with its contour name
+    contour: bool,
+    /// Whether to show tone numbers instead of tone marks
+    numbered: bool,
+    /// Whether to show each simplified character with its traditional form
+    both: bool,
+    /// Whether to order characters and tone groups by frequency rank
+    by_frequency: bool,
+}
+
+/// Prints analysis statistics of the records to stderr
+///
+/// Reports the number of records read, the number of distinct pinyin and the
+/// number of records whose rime could not be classified. Stdout is left
+/// untouched, so the statistics do not mix with the listing.
+fn report_analysis(records: &[HanziRecord]) {
+    let summary = summarize(records);
+    let unclassified = summary
+        .rime_counts
+        .iter()
+        .find(|(rime, _)| *rime == HanziRime::None)
+        .map_or(0, |(_, count)| *count);
+
+    eprintln!("Records read: {}", summary.total);
+    eprintln!("Distinct pinyin: {}", summary.distinct_pinyin);
+    eprintln!("Unclassified rimes: {unclassified}");
+}
+
 /// Processes the by-pinyin command to display characters grouped by pinyin
 ///
 /// This function reads the hanzi data file, groups characters by their pinyin pronunciation
@@ -122,29 +500,104 @@ enum Commands {
 ///
 /// # Arguments
 ///
-/// * `fold_size` - Optional width for line folding. If specified, character lists longer
+/// * `options` - The listing options. If `fold_size` is specified, character lists longer
 ///   than this width will be wrapped to multiple lines for better readability
-/// * `use_traditional` - Whether to display traditional characters instead of simplified
+/// * `out` - Where to write the formatted lines
 ///
 /// # Behavior
 ///
 /// - Reads hanzi data from "hanzi.tsv" file
 /// - Groups characters by pinyin without tone marks
+/// - With `full_paradigm`, drops the groups missing any of the tones 1-4
+/// - Orders the groups by size (the default), by onset then rime, alphabetically, or by the
+///   frequency rank of their most common character
 /// - Formats output with character counts and optional line folding
+/// - With `counts_only`, prints a compact `pinyin : count` table instead
+/// - With `max_chars`, shows at most that many characters per pinyin followed by `…(+N)`
+/// - With `freq_range`, ends each group with `[rank MIN–MAX]`, its frequency rank range
+/// - With `show` set to rime or onset, prints `pinyin : Rime` (or `Onset`) lines instead
+/// - With `list_format` set to table, draws a table with box-drawing borders instead
+/// - With `list_format` set to csv, prints `pinyin,count,characters` CSV instead
+/// - With `list_format` set to toml, prints a `pinyin = ["...", ...]` TOML document instead
+/// - Otherwise colors the pinyin green (ranks 1-500) or yellow (ranks 501-2000) when
+///   `colored` is set
 /// - Handles broken pipe errors gracefully (useful for piped output)
 /// - Exits with error code 1 if the data file cannot be read
-fn process_by_pinyin(fold_size: Option<usize>, use_traditional: bool) {
+fn process_by_pinyin(options: &PinyinListOptions, out: &mut dyn Write) {
+    let PinyinListOptions {
+        fold_size,
+        use_traditional,
+        counts_only,
+        colored,
+        show,
+        full_paradigm,
+        list_format,
+        max_chars,
+        freq_range,
+        sort,
+        verbose,
+    } = *options;
+
     match read_hanzi_file("hanzi.tsv") {
         Ok(records) => {
+            if verbose {
+                report_analysis(&records);
+            }
             // Separated into testable functions
-            let grouped_data = group_by_pinyin(&records, use_traditional);
-            let output_lines = format_pinyin_output(&grouped_data, fold_size);
-
-            for line in output_lines {
-                if writeln!(std::io::stdout(), "{line}").is_err() {
-                    break; // Broken pipe handling: exit quietly when pipe is closed
+            let mut grouped_data = group_by_pinyin(&records, use_traditional);
+            if full_paradigm {
+                let syllables = full_paradigm_syllables(&records);
+                grouped_data.retain(|(pinyin, _)| syllables.binary_search(pinyin).is_ok());
+            }
+            match sort {
+                SortOrder::Group => {}
+                SortOrder::Onset => sort_by_onset_and_rime(&mut grouped_data),
+                SortOrder::Alpha => grouped_data.sort_by(|a, b| a.0.cmp(&b.0)),
+                SortOrder::Freq => {
+                    // Most common first, with the unknown rank 0 last
+                    let min_frequencies = min_frequency_by_pinyin(&records);
+                    grouped_data.sort_by_cached_key(|(pinyin, _)| {
+                        let rank = match min_frequencies.get(pinyin) {
+                            Some(0) | None => u32::MAX,
+                            Some(&rank) => rank,
+                        };
+                        (rank, pinyin.clone())
+                    });
                 }
             }
+            let format = PinyinFormat::default();
+            let output_lines = if counts_only {
+                format_pinyin_counts(&grouped_data, &format)
+            } else if let Some(max_chars) = max_chars {
+                format_pinyin_output_truncated(&grouped_data, max_chars, &format)
+            } else if freq_range {
+                format_pinyin_output_with_ranges(
+                    &grouped_data,
+                    fold_size,
+                    &format,
+                    &frequency_range_by_pinyin(&records),
+                )
+            } else if list_format == ListFormat::Table {
+                format_pinyin_table(&grouped_data)
+            } else if list_format == ListFormat::Csv {
+                format_pinyin_csv(&grouped_data)
+            } else if list_format == ListFormat::Toml {
+                format_pinyin_toml(&grouped_data)
+            } else if show == ShowField::Rime {
+                format_pinyin_syllable_parts(&grouped_data, SyllablePart::Rime, &format)
+            } else if show == ShowField::Onset {
+                format_pinyin_syllable_parts(&grouped_data, SyllablePart::Onset, &format)
+            } else {
+                format_pinyin_output_colored(
+                    &grouped_data,
+                    fold_size,
+                    &format,
+                    &min_frequency_by_pinyin(&records),
+                    colored,
+                )
+            };
+
+            write_lines(out, &output_lines);
         }
         Err(e) => {
             eprintln!("Error reading hanzi.tsv: {e}");
@@ -155,41 +608,271 @@ fn process_by_pinyin(fold_size: Option<usize>, use_traditional: bool) {
 
 /// Processes the by-tone command to display characters filtered by pinyin and grouped by tone
 ///
-/// This function takes a target pinyin (without tone marks), finds all matching characters,
-/// and groups them by their tone numbers. It automatically converts 'v' to 'ü' for easier
-/// typing of pinyin containing the ü sound.
+/// This function takes a target pinyin, finds all matching characters, and groups them by
+/// their tone numbers. It automatically converts 'v' to 'ü' for easier typing of pinyin
+/// containing the ü sound. A tone given in the query restricts the results to that tone.
 ///
 /// # Arguments
 ///
-/// * `target_pinyin` - The pinyin to search for (without tone marks). 'v' is automatically
+/// * `target_pinyin` - The pinyin to search for, with an optional trailing tone digit or
+///   tone mark (e.g., 'ma3' or 'mǎ'), or a trailing '0' for any tone. 'v' is automatically
 ///   converted to 'ü' for convenience (e.g., 'nv' becomes 'nü')
-/// * `use_traditional` - Whether to display traditional characters instead of simplified
+/// * `options` - The display options of the tone listing
+/// * `out` - Where to write the formatted lines
 ///
 /// # Behavior
 ///
-/// - Normalizes input by replacing 'v' with 'ü'
+/// - Normalizes input by converting full-width letters and digits (e.g. 'ｍａ') to
+///   half-width and replacing 'v' with 'ü'
+/// - Splits off the tone given as a trailing digit or tone mark, if any
+/// - Completes a bare retroflex onset with its implicit final, so 'zh', 'ch', 'sh'
+///   and 'r' search 'zhi', 'chi', 'shi' and 'ri'
 /// - Reads hanzi data from "hanzi.tsv" file
+/// - Drops neutral-tone (5) records when `no_neutral` is set
 /// - Filters records matching the target pinyin
-/// - Groups matching characters by tone (1, 2, 3, 4, 5 for neutral tone)
+/// - Groups matching characters by tone (1, 2, 3, 4, 5 for neutral tone); records with an
+///   unknown tone (0) form their own group, sorted last and labeled `(unknown tone)`
+/// - With `by_frequency`, sorts the characters of each tone by frequency rank and the
+///   tone groups by the rank of their most common character instead
+/// - Keeps only the group of the requested tone when the query has one
 /// - Displays results with tone marks and character lists
+/// - With `pinyin_only`, displays the tone-marked pinyin forms space-separated instead
+/// - With `contour`, labels each tone with its contour (e.g. `mǎ (dipping): 马`)
+/// - With `numbered`, shows the tone as a digit after the pinyin (e.g. `ma3: 马`)
+/// - With `both`, shows each character with its differing traditional form in
+///   parentheses (e.g. `mǎ: 马(馬) 码(碼)`)
 /// - Shows "No characters found" message if no matches
 /// - Exits with error code 1 if the data file cannot be read
-fn process_by_tone(target_pinyin: &str, use_traditional: bool) {
-    // Replace 'v' with 'ü' in pinyin input (common typing convention)
-    let normalized_pinyin = target_pinyin.replace('v', "ü");
+fn process_by_tone(target_pinyin: &str, options: &ToneQueryOptions, out: &mut dyn Write) {
+    // Convert full-width letters from Chinese input methods, then replace 'v' with 'ü'
+    // in pinyin input (common typing convention)
+    let normalized_pinyin = normalize_fullwidth(target_pinyin).replace('v', "ü");
+    let (mut toneless_pinyin, requested_tone) = parse_tone_query(&normalized_pinyin);
+    // A bare retroflex onset stands for its syllable with the implicit -i final
+    if matches!(toneless_pinyin.as_str(), "zh" | "ch" | "sh" | "r") {
+        toneless_pinyin.push('i');
+    }
 
     match read_hanzi_file("hanzi.tsv") {
-        Ok(records) => match group_by_tone(&records, &normalized_pinyin, use_traditional) {
-            Some(tone_groups) => {
-                let output_lines = format_tone_output(&tone_groups);
-                for line in output_lines {
-                    println!("{line}");
+        Ok(mut records) => {
+            if options.no_neutral {
+                records.retain(|record| record.tone != 5);
+            }
+            let group = |use_traditional: bool| {
+                let tone_groups = if options.by_frequency {
+                    group_by_tone_by_frequency(&records, &toneless_pinyin, use_traditional)
+                } else {
+                    group_by_tone(&records, &toneless_pinyin, use_traditional)
+                };
+                tone_groups
+                    .map(|mut tone_groups| {
+                        if let Some(tone) = requested_tone {
+                            tone_groups.retain(|(group_tone, _, _)| *group_tone == tone);
+                        }
+                        tone_groups
+                    })
+                    .filter(|tone_groups| !tone_groups.is_empty())
+            };
+            match group(options.use_traditional) {
+                Some(tone_groups) => {
+                    if options.pinyin_only {
+                        write_lines(out, &[format_tone_pinyin_only(&tone_groups)]);
+                    } else if options.contour {
+                        write_lines(out, &format_tone_output_with_contour(&tone_groups));
+                    } else if options.numbered {
+                        write_lines(out, &format_tone_output_numbered(&tone_groups));
+                    } else if options.both {
+                        // The traditional grouping has the same records in the same order
+                        let traditional_groups = group(true).unwrap_or_default();
+                        write_lines(
+                            out,
+                            &format_tone_output_paired(&tone_groups, &traditional_groups),
+                        );
+                    } else {
+                        write_lines(out, &format_tone_output(&tone_groups));
+                    }
+                }
+                None => {
+                    write_lines(
+                        out,
+                        &[format!(
+                            "No characters found for pinyin: {normalized_pinyin}"
+                        )],
+                    );
                 }
             }
-            None => {
-                println!("No characters found for pinyin: {normalized_pinyin}");
+        }
+        Err(e) => {
+            eprintln!("Error reading hanzi.tsv: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Processes the inventory command to display every onset and rime
+///
+/// # Arguments
+///
+/// * `out` - Where to write the formatted lines
+///
+/// # Behavior
+///
+/// - Prints a header line, then the onsets in the left column and the rimes in the
+///   right column, each in chart order and including `none`
+/// - Leaves the onset column blank once the onsets run out, since there are more rimes
+/// - Does not read the data file
+fn process_inventory(out: &mut dyn Write) {
+    let onsets = HanziOnset::all();
+    let rimes = HanziRime::all();
+    let mut lines = vec![format!("{:<8}{}", "Onset", "Rime")];
+    for row in 0..onsets.len().max(rimes.len()) {
+        let onset = onsets.get(row).map_or("", HanziOnset::as_str);
+        let rime = rimes.get(row).map_or("", HanziRime::as_str);
+        lines.push(format!("{onset:<8}{rime}").trim_end().to_string());
+    }
+    write_lines(out, &lines);
+}
+
+/// Processes the stats command to display summary statistics of the dataset
+///
+/// # Arguments
+///
+/// * `out` - Where to write the formatted lines
+///
+/// # Behavior
+///
+/// - Reads hanzi data from "hanzi.tsv" file
+/// - Prints the number of records and of distinct pinyin
+/// - Prints the tone distribution as `Tones: 1=N 2=N 3=N 4=N 5=N`, in tone order
+/// - Exits with error code 1 if the data file cannot be read
+fn process_stats(out: &mut dyn Write) {
+    match read_hanzi_file("hanzi.tsv") {
+        Ok(records) => {
+            let summary = summarize(&records);
+            // The histogram is in numeric order; the unknown tone 0 goes last
+            let mut tone_counts: Vec<(u32, u32)> = summary.tone_histogram.into_iter().collect();
+            tone_counts.sort_by_key(|&(tone, _)| tone == 0);
+            write_lines(
+                out,
+                &[
+                    format!("Records: {}", summary.total),
+                    format!("Distinct pinyin: {}", summary.distinct_pinyin),
+                    format_tone_distribution(&tone_counts),
+                ],
+            );
+        }
+        Err(e) => {
+            eprintln!("Error reading hanzi.tsv: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Processes the search command to display the syllables matching an onset and rime
+///
+/// # Arguments
+///
+/// * `onset_str` - The onset to match, or "*" for any onset
+/// * `rime_str` - The rime to match, or "*" for any rime
+/// * `fold_size` - Optional width for line folding
+/// * `use_traditional` - Whether to display traditional characters instead of simplified
+/// * `out` - Where to write the formatted lines
+///
+/// # Behavior
+///
+/// - Parses the onset and rime, treating "*" as a wildcard
+/// - Reads hanzi data from "hanzi.tsv" file
+/// - Uses `search_syllables()` to find the matching syllables
+/// - Prints `pinyin : count characters` lines in onset and rime chart order
+/// - Shows a "No characters found" message if nothing matches
+/// - Exits with error code 1 if the onset or rime is invalid or if the data file
+///   cannot be read
+fn process_search(
+    onset_str: &str,
+    rime_str: &str,
+    fold_size: Option<usize>,
+    use_traditional: bool,
+    out: &mut dyn Write,
+) {
+    let onset = match onset_str {
+        "*" => None,
+        _ => match onset_str.parse::<HanziOnset>() {
+            Ok(onset) => Some(onset),
+            Err(e) => {
+                eprintln!("Invalid onset '{onset_str}': {e}");
+                std::process::exit(1);
+            }
+        },
+    };
+    let rime = match rime_str {
+        "*" => None,
+        _ => match rime_str.parse::<HanziRime>() {
+            Ok(rime) => Some(rime),
+            Err(e) => {
+                eprintln!("Invalid rime '{rime_str}': {e}");
+                std::process::exit(1);
             }
         },
+    };
+    let query = SyllableQuery { onset, rime };
+
+    match read_hanzi_file("hanzi.tsv") {
+        Ok(records) => {
+            let syllables = search_syllables(&records, &query, use_traditional);
+            if syllables.is_empty() {
+                write_lines(
+                    out,
+                    &[format!(
+                        "No characters found for onset {onset_str} and rime {rime_str}"
+                    )],
+                );
+            } else {
+                write_lines(
+                    out,
+                    &format_pinyin_output(&syllables, fold_size, &PinyinFormat::default()),
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("Error reading hanzi.tsv: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Processes the list command to display all characters under one grouping
+///
+/// # Arguments
+///
+/// * `group_by` - What to group the characters by
+/// * `use_traditional` - Whether to display traditional characters instead of simplified
+/// * `out` - Where to write the formatted lines
+///
+/// # Behavior
+///
+/// - Reads hanzi data from "hanzi.tsv" file
+/// - `pinyin`: prints `pinyin : count characters` lines like the pinyin command
+/// - `tone`: prints `tone: count` lines in tone order (1-5, then 0 for unknown)
+/// - `onset`: prints `onset: count` lines like the onset command
+/// - `rime`: prints `rime: count` lines, most common first
+/// - Exits with error code 1 if the data file cannot be read
+fn process_list(group_by: GroupKey, use_traditional: bool, out: &mut dyn Write) {
+    match read_hanzi_file("hanzi.tsv") {
+        Ok(records) => {
+            let output_lines = match group_by {
+                GroupKey::Pinyin => format_pinyin_output(
+                    &group_by_pinyin(&records, use_traditional),
+                    None,
+                    &PinyinFormat::default(),
+                ),
+                GroupKey::Tone => format_tone_counts(&count_by_tone(&records).unwrap_or_default()),
+                GroupKey::Onset => {
+                    format_onset_output(&group_by_onset(&records).unwrap_or_default())
+                }
+                GroupKey::Rime => format_rime_output(&group_by_rime(&records).unwrap_or_default()),
+            };
+            write_lines(out, &output_lines);
+        }
         Err(e) => {
             eprintln!("Error reading hanzi.tsv: {e}");
             std::process::exit(1);
@@ -211,12 +894,14 @@ fn process_by_tone(target_pinyin: &str, use_traditional: bool) {
 /// * `cmd` - A mutable reference to the clap Command structure used to
 ///   generate completions based on the application's CLI definition
 ///
+/// * `out` - Where to write the completion script
+///
 /// # Output
 ///
-/// Prints the completion script to stdout, which can be redirected to a file
-/// or directly sourced by the shell
-fn print_completions<G: Generator>(gen: G, cmd: &mut clap::Command) {
-    generate(gen, cmd, cmd.get_name().to_string(), &mut io::stdout());
+/// Writes the completion script to `out` (stdout unless `--output` is given), which
+/// can be redirected to a file or directly sourced by the shell
+fn print_completions<G: Generator>(gen: G, cmd: &mut clap::Command, out: &mut dyn Write) {
+    generate(gen, cmd, cmd.get_name().to_string(), out);
 }
 
 /// Processes the by-onset command to display characters grouped by onset type
@@ -234,16 +919,25 @@ fn print_completions<G: Generator>(gen: G, cmd: &mut clap::Command) {
 /// * `fold_size` - Optional width for line folding when onset is specified. If provided,
 ///   long character lists will be wrapped to multiple lines for better readability
 /// * `use_traditional` - Whether to display traditional characters instead of simplified
+/// * `include_empty` - Whether to list the onsets that do not occur, with a count of 0
+/// * `out` - Where to write the formatted lines
 ///
 /// # Behavior
 ///
 /// - Reads hanzi data from "hanzi.tsv" file
-/// - If onset_filter is None: uses `group_by_onset()` to count characters by onset type
+/// - If onset_filter is None: uses `group_by_onset()` to count characters by onset type,
+///   completed with `include_empty_onsets()` when `include_empty` is set
 /// - If onset_filter is Some: uses `group_by_onset_and_pinyin()` to group by pinyin within onset
 /// - For onset filtering, supports optional line folding similar to by-pinyin command
 /// - Displays results sorted by frequency (most common first)
 /// - Exits with error code 1 if the data file cannot be read or if onset is invalid
-fn process_by_onset(onset_filter: Option<&str>, fold_size: Option<usize>, use_traditional: bool) {
+fn process_by_onset(
+    onset_filter: Option<&str>,
+    fold_size: Option<usize>,
+    use_traditional: bool,
+    include_empty: bool,
+    out: &mut dyn Write,
+) {
     match read_hanzi_file("hanzi.tsv") {
         Ok(records) => {
             if let Some(onset_str) = onset_filter {
@@ -254,14 +948,13 @@ fn process_by_onset(onset_filter: Option<&str>, fold_size: Option<usize>, use_tr
                             Some(pinyin_groups) => {
                                 let output_lines =
                                     format_onset_pinyin_output(&pinyin_groups, fold_size);
-                                for line in output_lines {
-                                    if writeln!(std::io::stdout(), "{line}").is_err() {
-                                        break; // Broken pipe handling: exit quietly when pipe is closed
-                                    }
-                                }
+                                write_lines(out, &output_lines);
                             }
                             None => {
-                                println!("No characters found for onset: {onset_str}");
+                                write_lines(
+                                    out,
+                                    &[format!("No characters found for onset: {onset_str}")],
+                                );
                             }
                         }
                     }
@@ -272,15 +965,19 @@ fn process_by_onset(onset_filter: Option<&str>, fold_size: Option<usize>, use_tr
                 }
             } else {
                 // Original behavior: group all characters by onset type
-                match group_by_onset(&records) {
+                let onset_counts = if include_empty {
+                    Some(include_empty_onsets(
+                        &group_by_onset(&records).unwrap_or_default(),
+                    ))
+                } else {
+                    group_by_onset(&records)
+                };
+                match onset_counts {
                     Some(onset_counts) => {
-                        let output_lines = format_onset_output(&onset_counts);
-                        for line in output_lines {
-                            println!("{line}");
-                        }
+                        write_lines(out, &format_onset_output(&onset_counts));
                     }
                     None => {
-                        println!("No characters found in the data file.");
+                        write_lines(out, &["No characters found in the data file.".to_string()]);
                     }
                 }
             }
@@ -292,6 +989,68 @@ fn process_by_onset(onset_filter: Option<&str>, fold_size: Option<usize>, use_tr
     }
 }
 
+/// Processes the rime command to display the characters of one rime grouped by onset
+///
+/// # Arguments
+///
+/// * `rime_str` - The rime to filter by (e.g., "ang", "ü" or "v")
+/// * `list` - Whether to list the characters of each onset instead of only counting them
+/// * `fold_size` - Optional width for line folding. If provided, long character lists
+///   will be wrapped to multiple lines for better readability
+/// * `use_traditional` - Whether to display traditional characters instead of simplified
+/// * `out` - Where to write the formatted lines
+///
+/// # Behavior
+///
+/// - Parses the rime with `HanziRime::from_str`
+/// - Reads hanzi data from "hanzi.tsv" file
+/// - Uses `collect_by_rime()` to group the characters of that rime by onset
+/// - Prints `onset : count` lines, or with `list` `onset : count characters` lines,
+///   with `none` for vowel-initial syllables
+/// - Exits with error code 1 if the rime is invalid (listing the valid rimes) or if
+///   the data file cannot be read
+fn process_by_rime(
+    rime_str: &str,
+    list: bool,
+    fold_size: Option<usize>,
+    use_traditional: bool,
+    out: &mut dyn Write,
+) {
+    let target_rime = match rime_str.parse::<HanziRime>() {
+        Ok(rime) => rime,
+        Err(e) => {
+            let valid: Vec<&str> = HanziRime::all().iter().map(HanziRime::as_str).collect();
+            eprintln!("Invalid rime '{rime_str}': {e}");
+            eprintln!("Valid rimes: {}", valid.join(", "));
+            std::process::exit(1);
+        }
+    };
+
+    match read_hanzi_file("hanzi.tsv") {
+        Ok(records) => {
+            let onset_groups = collect_by_rime(&records, &target_rime, use_traditional);
+            if onset_groups.is_empty() {
+                write_lines(out, &[format!("No characters found for rime: {rime_str}")]);
+                return;
+            }
+            let named_groups: Vec<(String, Vec<String>)> = onset_groups
+                .into_iter()
+                .map(|(onset, characters)| (onset.as_str().to_string(), characters))
+                .collect();
+            let output_lines = if list {
+                format_onset_pinyin_output(&named_groups, fold_size)
+            } else {
+                format_pinyin_counts(&named_groups, &PinyinFormat::default())
+            };
+            write_lines(out, &output_lines);
+        }
+        Err(e) => {
+            eprintln!("Error reading hanzi.tsv: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 /// Convert the hanzi.tsv file to a new format hanzi_2.tsv.
 ///
 /// This function reads the original hanzi.tsv and create a new file name hanzi_2.tsv.
@@ -344,6 +1103,228 @@ fn convert_file() {
     }
 }
 
+/// Validates the hanzi.tsv file by running onset and rime analysis on every record
+///
+/// A record whose rime cannot be determined (`HanziRime::None`) indicates a syllable
+/// the analysis does not understand. Each such record is printed with its frequency,
+/// character and pinyin, followed by a summary line with the number of offending records.
+///
+/// # Behavior
+///
+/// 1. Reads the hanzi.tsv file by the `read_hanzi_file` function.
+/// 2. Analyzes the onset and rime of each character using `set_hanzi_onsets` and `set_hanzi_rime`.
+/// 3. Prints `frequency: character pinyin` for each record with an unparsed rime.
+/// 4. Prints `Unparsed syllables: N of M records` as the last line.
+///
+/// The report is written to `out`. Exits with error code 1 if the data file cannot be read.
+fn validate_file(out: &mut dyn Write) {
+    match read_hanzi_file("hanzi.tsv") {
+        Ok(mut records) => {
+            set_hanzi_onsets(&mut records);
+            set_hanzi_rime(&mut records);
+
+            let unparsed: Vec<_> = records
+                .iter()
+                .filter(|record| record.rime == HanziRime::None)
+                .collect();
+
+            let mut output_lines: Vec<String> = unparsed
+                .iter()
+                .map(|record| {
+                    format!(
+                        "{}: {} {}",
+                        record.frequency, record.simplified, record.pinyin_without_tone
+                    )
+                })
+                .collect();
+            output_lines.push(format!(
+                "Unparsed syllables: {} of {} records",
+                unparsed.len(),
+                records.len()
+            ));
+            write_lines(out, &output_lines);
+        }
+        Err(e) => {
+            eprintln!("Error reading hanzi.tsv: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Compares two data files and shows the characters added, removed or re-read
+///
+/// Records are compared by their simplified character. Characters only in `old_path`
+/// are printed as `- 字` and characters only in `new_path` as `+ 字`, each in the order
+/// of their file. Characters in both files whose pinyin changed follow as
+/// `~ 字: old → new`, in the order of `old_path`; the readings of a character with
+/// several records are joined with `/`.
+///
+/// # Arguments
+///
+/// * `old_path` - The older data file
+/// * `new_path` - The newer data file
+/// * `out` - Where to write the formatted lines
+///
+/// Exits with error code 1 if either data file cannot be read.
+fn diff_files(old_path: &Path, new_path: &Path, out: &mut dyn Write) {
+    let read = |path: &Path| match read_hanzi_file(&path.to_string_lossy()) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Error reading {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    };
+    let old_records = read(old_path);
+    let new_records = read(new_path);
+
+    let old_characters: HashSet<&str> = old_records
+        .iter()
+        .map(|record| record.simplified.as_str())
+        .collect();
+    let new_characters: HashSet<&str> = new_records
+        .iter()
+        .map(|record| record.simplified.as_str())
+        .collect();
+
+    let removed = old_records
+        .iter()
+        .filter(|record| !new_characters.contains(record.simplified.as_str()))
+        .map(|record| format!("- {}", record.simplified));
+    let added = new_records
+        .iter()
+        .filter(|record| !old_characters.contains(record.simplified.as_str()))
+        .map(|record| format!("+ {}", record.simplified));
+
+    // The readings of each character, in file order
+    let readings = |records: &[HanziRecord]| {
+        let mut readings: HashMap<String, Vec<String>> = HashMap::new();
+        for record in records {
+            readings
+                .entry(record.simplified.clone())
+                .or_default()
+                .push(record.pinyin.clone());
+        }
+        readings
+    };
+    let old_readings = readings(&old_records);
+    let new_readings = readings(&new_records);
+    let mut reported = HashSet::new();
+    let changed = old_records.iter().filter_map(|record| {
+        let character = record.simplified.as_str();
+        let old_pinyin = &old_readings[character];
+        let new_pinyin = new_readings.get(character)?;
+        // The same readings in another order are not a change
+        let same_readings = old_pinyin.iter().collect::<BTreeSet<_>>()
+            == new_pinyin.iter().collect::<BTreeSet<_>>();
+        (!same_readings && reported.insert(character)).then(|| {
+            format!(
+                "~ {character}: {} → {}",
+                old_pinyin.join("/"),
+                new_pinyin.join("/")
+            )
+        })
+    });
+
+    write_lines(
+        out,
+        &removed.chain(added).chain(changed).collect::<Vec<_>>(),
+    );
+}
+
+/// Processes the coverage command to compare the syllables of the data with a reference list
+///
+/// # Arguments
+///
+/// * `reference_path` - The reference list of syllables
+/// * `out` - Where to write the formatted lines
+///
+/// # Behavior
+///
+/// - Reads the reference with `load_syllable_list` and hanzi data from "hanzi.tsv"
+/// - Compares the reference with the toneless syllables of `distinct_pinyin`
+/// - Prints `Only in data (N): ...` with the syllables missing from the reference,
+///   then `Only in reference (N): ...` with those missing from the data, each
+///   space-separated in alphabetical order
+/// - Exits with error code 1 if either file cannot be read
+fn process_coverage(reference_path: &Path, out: &mut dyn Write) {
+    let reference = match load_syllable_list(&reference_path.to_string_lossy()) {
+        Ok(reference) => reference,
+        Err(e) => {
+            eprintln!("Error reading {}: {e}", reference_path.display());
+            std::process::exit(1);
+        }
+    };
+    let records = match read_hanzi_file("hanzi.tsv") {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Error reading hanzi.tsv: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let data = distinct_pinyin(&records);
+    let only_in_data: Vec<&str> = data
+        .iter()
+        .filter(|syllable| !reference.contains(*syllable))
+        .map(String::as_str)
+        .collect();
+    let mut only_in_reference: Vec<&str> = reference
+        .iter()
+        .filter(|syllable| data.binary_search(syllable).is_err())
+        .map(String::as_str)
+        .collect();
+    only_in_reference.sort_unstable();
+
+    write_lines(
+        out,
+        &[
+            format!(
+                "Only in data ({}): {}",
+                only_in_data.len(),
+                only_in_data.join(" ")
+            )
+            .trim_end()
+            .to_string(),
+            format!(
+                "Only in reference ({}): {}",
+                only_in_reference.len(),
+                only_in_reference.join(" ")
+            )
+            .trim_end()
+            .to_string(),
+        ],
+    );
+}
+
+/// Opens the destination of the formatted results
+///
+/// Returns a buffered writer to `path` when given, or stdout otherwise.
+/// Exits with error code 1 if the file cannot be created.
+fn open_output(path: Option<&Path>) -> Box<dyn Write> {
+    match path {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("Error creating {}: {e}", path.display());
+                std::process::exit(1);
+            }
+        },
+        None => Box::new(io::stdout()),
+    }
+}
+
+/// Writes each line to `out`, stopping at the first write error
+///
+/// Stopping quietly keeps piped output such as `| head` from panicking when the
+/// pipe is closed.
+fn write_lines(out: &mut dyn Write, lines: &[String]) {
+    for line in lines {
+        if writeln!(out, "{line}").is_err() {
+            break; // Broken pipe handling: exit quietly when pipe is closed
+        }
+    }
+}
+
 /// Main entry point for the Hanzi learning program
 ///
 /// This function parses command-line arguments and dispatches to the appropriate
@@ -354,24 +1335,70 @@ fn convert_file() {
 /// 3. **generate-completion**: Creates shell completion scripts
 ///
 /// The function uses the clap crate for argument parsing and provides comprehensive
-/// help messages and validation for all commands and options.
+/// help messages and validation for all commands and options. Results go to the file
+/// given by the global `--output` option, or to stdout by default.
 fn main() {
     let args = Args::parse();
+    let mut out = open_output(args.output.as_deref());
 
     match args.command {
         Commands::Pinyin {
             fold,
             traditional,
             pinyin,
+            counts_only,
+            color,
+            pinyin_only,
+            no_neutral,
+            contour,
+            numbered,
+            both,
+            show,
+            full_paradigm,
+            format,
+            max_chars,
+            freq_range,
+            sort,
+            verbose,
         } => {
             match pinyin {
                 Some(p) => {
+                    if !matches!(sort, SortOrder::Group | SortOrder::Freq) {
+                        Args::command()
+                            .error(
+                                ErrorKind::ArgumentConflict,
+                                "only '--sort freq' can be used with [PINYIN]",
+                            )
+                            .exit();
+                    }
                     // If pinyin is provided, process it with the specified fold and traditional options
-                    process_by_tone(&p, traditional);
+                    let options = ToneQueryOptions {
+                        use_traditional: traditional,
+                        pinyin_only,
+                        no_neutral,
+                        contour,
+                        numbered,
+                        both,
+                        by_frequency: sort == SortOrder::Freq,
+                    };
+                    process_by_tone(&p, &options, &mut out);
                 }
                 None => {
                     // If no pinyin is provided, just process by pinyin without filtering
-                    process_by_pinyin(fold, traditional);
+                    let options = PinyinListOptions {
+                        fold_size: fold,
+                        use_traditional: traditional,
+                        counts_only,
+                        colored: color.enabled(args.output.is_none()),
+                        show,
+                        full_paradigm,
+                        list_format: format,
+                        max_chars,
+                        freq_range,
+                        sort,
+                        verbose,
+                    };
+                    process_by_pinyin(&options, &mut out);
                 }
             }
         }
@@ -379,16 +1406,60 @@ fn main() {
             onset,
             fold,
             traditional,
+            include_empty,
+        } => {
+            process_by_onset(onset.as_deref(), fold, traditional, include_empty, &mut out);
+        }
+        Commands::Rime {
+            rime,
+            list,
+            fold,
+            traditional,
+        } => {
+            process_by_rime(&rime, list, fold, traditional, &mut out);
+        }
+        Commands::Stats => {
+            process_stats(&mut out);
+        }
+        Commands::Inventory => {
+            process_inventory(&mut out);
+        }
+        Commands::Search {
+            onset,
+            rime,
+            fold,
+            traditional,
         } => {
-            process_by_onset(onset.as_deref(), fold, traditional);
+            process_search(&onset, &rime, fold, traditional, &mut out);
+        }
+        Commands::List {
+            group_by,
+            traditional,
+        } => {
+            process_list(group_by, traditional, &mut out);
         }
         Commands::Convert => {
             convert_file();
         }
+        Commands::Validate => {
+            validate_file(&mut out);
+        }
+        Commands::Diff { old, new } => {
+            diff_files(&old, &new, &mut out);
+        }
+        Commands::Coverage { reference } => {
+            process_coverage(&reference, &mut out);
+        }
         Commands::GenerateCompletion { shell } => {
             let mut cmd = Args::command();
             eprintln!("Generating completion file for {shell}...");
-            print_completions(shell, &mut cmd);
+            print_completions(shell, &mut cmd, &mut out);
         }
     }
+
+    // A failed flush only matters for a file; stdout keeps its broken pipe handling
+    if out.flush().is_err() && args.output.is_some() {
+        eprintln!("Error writing output");
+        std::process::exit(1);
+    }
 }
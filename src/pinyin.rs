@@ -0,0 +1,623 @@
+//! # Pinyin Spelling Module
+//!
+//! This module provides helper functions for working with pinyin strings,
+//! such as removing tone marks from pinyin written with diacritics.
+//!
+//! ## Functions
+//!
+//! - [`strip_tones`]: Removes tone marks from pinyin (e.g. "mǎ" → "ma")
+//! - [`marked_tone`]: Finds the tone marked on pinyin (e.g. "mǎ" → 3)
+//! - [`syllabify`]: Splits run-on pinyin into syllables (e.g. "nihao" → "ni hao")
+//! - [`syllabify_with_syllables`]: Splits run-on pinyin using a custom syllable list
+//! - [`normalize_fullwidth`]: Converts full-width ASCII to half-width (e.g. "ｍａ" → "ma")
+//! - [`render_pinyin`]: Spells an onset, rime and tone as marked pinyin (e.g. m + a + 3 → "mǎ")
+//!
+//! ## Constants
+//!
+//! - [`STANDARD_SYLLABLES`]: Every syllable of standard Mandarin pinyin, without tones
+
+use crate::types::{HanziOnset, HanziRime};
+use std::collections::HashSet;
+
+/// Every syllable of standard Mandarin pinyin, written without tone marks
+///
+/// The list follows the usual pinyin spelling rules, so "ü" is written "u"
+/// after j, q, x and y (e.g. "ju", "xue"). The nasal interjections m, n, ng,
+/// hm and hng are included as well.
+#[rustfmt::skip]
+pub(crate) const STANDARD_SYLLABLES: &[&str] = &[
+    // No onset
+    "a", "o", "e", "ai", "ei", "ao", "ou", "an", "en", "ang", "eng", "er",
+    // b, p, m, f
+    "ba", "bo", "bai", "bei", "bao", "ban", "ben", "bang", "beng", "bi", "bie", "biao", "bian",
+    "bin", "bing", "bu",
+    "pa", "po", "pai", "pei", "pao", "pou", "pan", "pen", "pang", "peng", "pi", "pie", "piao",
+    "pian", "pin", "ping", "pu",
+    "ma", "mo", "me", "mai", "mei", "mao", "mou", "man", "men", "mang", "meng", "mi", "mie",
+    "miao", "miu", "mian", "min", "ming", "mu",
+    "fa", "fo", "fei", "fou", "fan", "fen", "fang", "feng", "fu",
+    // d, t, n, l
+    "da", "de", "dai", "dei", "dao", "dou", "dan", "den", "dang", "deng", "dong", "di", "dia",
+    "die", "diao", "diu", "dian", "ding", "du", "duo", "dui", "duan", "dun",
+    "ta", "te", "tai", "tao", "tou", "tan", "tang", "teng", "tong", "ti", "tie", "tiao", "tian",
+    "ting", "tu", "tuo", "tui", "tuan", "tun",
+    "na", "ne", "nai", "nei", "nao", "nou", "nan", "nen", "nang", "neng", "nong", "ni", "nie",
+    "niao", "niu", "nian", "nin", "niang", "ning", "nu", "nuo", "nuan", "nü", "nüe",
+    "la", "lo", "le", "lai", "lei", "lao", "lou", "lan", "lang", "leng", "long", "li", "lia",
+    "lie", "liao", "liu", "lian", "lin", "liang", "ling", "lu", "luo", "luan", "lun", "lü", "lüe",
+    // g, k, h
+    "ga", "ge", "gai", "gei", "gao", "gou", "gan", "gen", "gang", "geng", "gong", "gu", "gua",
+    "guo", "guai", "gui", "guan", "gun", "guang",
+    "ka", "ke", "kai", "kei", "kao", "kou", "kan", "ken", "kang", "keng", "kong", "ku", "kua",
+    "kuo", "kuai", "kui", "kuan", "kun", "kuang",
+    "ha", "he", "hai", "hei", "hao", "hou", "han", "hen", "hang", "heng", "hong", "hu", "hua",
+    "huo", "huai", "hui", "huan", "hun", "huang",
+    // j, q, x
+    "ji", "jia", "jie", "jiao", "jiu", "jian", "jin", "jiang", "jing", "jiong", "ju", "jue",
+    "juan", "jun",
+    "qi", "qia", "qie", "qiao", "qiu", "qian", "qin", "qiang", "qing", "qiong", "qu", "que",
+    "quan", "qun",
+    "xi", "xia", "xie", "xiao", "xiu", "xian", "xin", "xiang", "xing", "xiong", "xu", "xue",
+    "xuan", "xun",
+    // zh, ch, sh, r
+    "zha", "zhe", "zhi", "zhai", "zhei", "zhao", "zhou", "zhan", "zhen", "zhang", "zheng",
+    "zhong", "zhu", "zhua", "zhuo", "zhuai", "zhui", "zhuan", "zhun", "zhuang",
+    "cha", "che", "chi", "chai", "chao", "chou", "chan", "chen", "chang", "cheng", "chong", "chu",
+    "chua", "chuo", "chuai", "chui", "chuan", "chun", "chuang",
+    "sha", "she", "shi", "shai", "shei", "shao", "shou", "shan", "shen", "shang", "sheng", "shu",
+    "shua", "shuo", "shuai", "shui", "shuan", "shun", "shuang",
+    "re", "ri", "rao", "rou", "ran", "ren", "rang", "reng", "rong", "ru", "rua", "ruo", "rui",
+    "ruan", "run",
+    // z, c, s
+    "za", "ze", "zi", "zai", "zei", "zao", "zou", "zan", "zen", "zang", "zeng", "zong", "zu",
+    "zuo", "zui", "zuan", "zun",
+    "ca", "ce", "ci", "cai", "cao", "cou", "can", "cen", "cang", "ceng", "cong", "cu", "cuo",
+    "cui", "cuan", "cun",
+    "sa", "se", "si", "sai", "sao", "sou", "san", "sen", "sang", "seng", "song", "su", "suo",
+    "sui", "suan", "sun",
+    // y, w
+    "ya", "yo", "ye", "yao", "you", "yan", "yin", "yang", "ying", "yong", "yi", "yu", "yue",
+    "yuan", "yun",
+    "wa", "wo", "wai", "wei", "wan", "wen", "wang", "weng", "wu",
+    // Nasal interjections
+    "m", "n", "ng", "hm", "hng",
+];
+
+/// Returns the base letter of a tone-marked vowel, or `None` for other characters
+fn base_letter(c: char) -> Option<char> {
+    let base = match c {
+        'ā' | 'á' | 'ǎ' | 'à' => 'a',
+        'ē' | 'é' | 'ě' | 'è' => 'e',
+        'ī' | 'í' | 'ǐ' | 'ì' => 'i',
+        'ō' | 'ó' | 'ǒ' | 'ò' => 'o',
+        'ū' | 'ú' | 'ǔ' | 'ù' => 'u',
+        'ǖ' | 'ǘ' | 'ǚ' | 'ǜ' => 'ü',
+        'ń' | 'ň' | 'ǹ' => 'n',
+        'ḿ' => 'm',
+        'Ā' | 'Á' | 'Ǎ' | 'À' => 'A',
+        'Ē' | 'É' | 'Ě' | 'È' => 'E',
+        'Ī' | 'Í' | 'Ǐ' | 'Ì' => 'I',
+        'Ō' | 'Ó' | 'Ǒ' | 'Ò' => 'O',
+        'Ū' | 'Ú' | 'Ǔ' | 'Ù' => 'U',
+        'Ǖ' | 'Ǘ' | 'Ǚ' | 'Ǜ' => 'Ü',
+        _ => return None,
+    };
+    Some(base)
+}
+
+/// Removes tone marks from a pinyin string
+///
+/// Every vowel (and syllabic nasal) carrying a tone diacritic is replaced with
+/// its plain letter. The umlaut of "ü" is kept, since it is part of the spelling
+/// rather than a tone mark. Combining tone diacritics (U+0300, U+0301, U+0304,
+/// U+030C) are removed as well.
+///
+/// # Arguments
+///
+/// * `pinyin` - Pinyin with tone marks
+///
+/// # Returns
+///
+/// The pinyin without tone marks
+pub(crate) fn strip_tones(pinyin: &str) -> String {
+    pinyin
+        .chars()
+        .filter(|c| !matches!(c, '\u{0300}' | '\u{0301}' | '\u{0304}' | '\u{030C}'))
+        .map(|c| base_letter(c).unwrap_or(c))
+        .collect()
+}
+
+/// Returns the tone number (1-4) of a tone-marked character, or `None` for other characters
+fn tone_of(c: char) -> Option<u32> {
+    let tone = match c {
+        'ā' | 'ē' | 'ī' | 'ō' | 'ū' | 'ǖ' | 'Ā' | 'Ē' | 'Ī' | 'Ō' | 'Ū' | 'Ǖ' | '\u{0304}' => {
+            1
+        }
+        'á' | 'é' | 'í' | 'ó' | 'ú' | 'ǘ' | 'ń' | 'ḿ' | 'Á' | 'É' | 'Í' | 'Ó' | 'Ú' | 'Ǘ'
+        | '\u{0301}' => 2,
+        'ǎ' | 'ě' | 'ǐ' | 'ǒ' | 'ǔ' | 'ǚ' | 'ň' | 'Ǎ' | 'Ě' | 'Ǐ' | 'Ǒ' | 'Ǔ' | 'Ǚ'
+        | '\u{030C}' => 3,
+        'à' | 'è' | 'ì' | 'ò' | 'ù' | 'ǜ' | 'ǹ' | 'À' | 'È' | 'Ì' | 'Ò' | 'Ù' | 'Ǜ'
+        | '\u{0300}' => 4,
+        _ => return None,
+    };
+    Some(tone)
+}
+
+/// Finds the tone marked on a pinyin string
+///
+/// # Arguments
+///
+/// * `pinyin` - Pinyin, possibly with a tone mark
+///
+/// # Returns
+///
+/// The tone (1-4) of the first tone mark, or `None` when the pinyin carries no
+/// tone mark (e.g. toneless input or the neutral tone)
+pub(crate) fn marked_tone(pinyin: &str) -> Option<u32> {
+    pinyin.chars().find_map(tone_of)
+}
+
+/// Converts full-width ASCII characters to their half-width forms
+///
+/// Chinese input methods may produce full-width letters and digits (U+FF01 to
+/// U+FF5E, e.g. "ｍａ３"), which do not match the half-width pinyin of the data.
+/// Each of them is replaced with the ASCII character 0xFEE0 below it; all
+/// other characters, including "ü" and tone marks, are kept as they are.
+///
+/// # Arguments
+///
+/// * `s` - Text possibly containing full-width ASCII characters
+///
+/// # Returns
+///
+/// The text with full-width ASCII characters converted to half-width
+///
+/// # Examples
+///
+/// ```
+/// use study_rust_hanzi::normalize_fullwidth;
+///
+/// assert_eq!(normalize_fullwidth("ｍａ３"), "ma3");
+/// assert_eq!(normalize_fullwidth("nǚ"), "nǚ");
+/// ```
+pub fn normalize_fullwidth(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(u32::from(c) - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Splits a run-on toneless romanization into pinyin syllables
+///
+/// The input is lowercased, "v" is read as "ü", and it is first split at
+/// whitespace and apostrophes, which always mark a syllable boundary. Each
+/// piece is then segmented with a greedy rule: at every position the longest
+/// standard Mandarin syllable is taken, falling back to shorter
+/// syllables only when the longest one leaves a remainder that cannot be
+/// segmented. So "xian" stays one syllable, and "xi'an" is needed for two.
+/// The nasal interjections (m, n, ng, hm, hng) are not used for segmenting.
+/// A piece that cannot be segmented at all is returned unchanged.
+///
+/// # Arguments
+///
+/// * `input` - Pinyin without tone marks, possibly without spaces
+///
+/// # Returns
+///
+/// The syllables in input order
+///
+/// # Examples
+///
+/// ```
+/// use study_rust_hanzi::syllabify;
+///
+/// assert_eq!(syllabify("nihaoma"), vec!["ni", "hao", "ma"]);
+/// assert_eq!(syllabify("xian"), vec!["xian"]);
+/// assert_eq!(syllabify("xi'an"), vec!["xi", "an"]);
+/// // The longest match "hang" would leave "uo", so "han" is used instead
+/// assert_eq!(syllabify("hanguo"), vec!["han", "guo"]);
+/// ```
+pub fn syllabify(input: &str) -> Vec<String> {
+    // The longest standard syllable has six letters (e.g. "zhuang")
+    const MAX_SYLLABLE_CHARS: usize = 6;
+    const NASAL_INTERJECTIONS: &[&str] = &["m", "n", "ng", "hm", "hng"];
+
+    syllabify_by(input, MAX_SYLLABLE_CHARS, &|candidate| {
+        STANDARD_SYLLABLES.contains(&candidate) && !NASAL_INTERJECTIONS.contains(&candidate)
+    })
+}
+
+/// Splits run-on romanization into the syllables of a custom syllable list
+///
+/// Works like [`syllabify`], but segments with the syllables of `syllables`
+/// instead of the standard Mandarin ones, e.g. a list loaded with
+/// [`load_syllable_list`](crate::load_syllable_list) for a dialect study. The
+/// entries are expected lowercased and with "ü" rather than "v". Every entry
+/// is used, whatever its length, including nasal syllables such as "ng" that
+/// [`syllabify`] leaves out.
+///
+/// # Arguments
+///
+/// * `input` - Pinyin without tone marks, possibly without spaces
+/// * `syllables` - The syllables to segment with
+///
+/// # Returns
+///
+/// The syllables in input order
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use study_rust_hanzi::syllabify_with_syllables;
+///
+/// let syllables: HashSet<String> = ["sei", "fa"].iter().map(|s| s.to_string()).collect();
+/// assert_eq!(syllabify_with_syllables("seifa", &syllables), vec!["sei", "fa"]);
+/// assert_eq!(syllabify_with_syllables("nihao", &syllables), vec!["nihao"]);
+/// ```
+pub fn syllabify_with_syllables(input: &str, syllables: &HashSet<String>) -> Vec<String> {
+    let max_chars = syllables
+        .iter()
+        .map(|syllable| syllable.chars().count())
+        .max()
+        .unwrap_or(0);
+    syllabify_by(input, max_chars, &|candidate| syllables.contains(candidate))
+}
+
+/// Splits run-on romanization into the syllables accepted by `is_syllable`
+///
+/// `max_chars` is the length in characters of the longest accepted syllable.
+fn syllabify_by(input: &str, max_chars: usize, is_syllable: &dyn Fn(&str) -> bool) -> Vec<String> {
+    let normalized = input.to_lowercase().replace('v', "ü");
+    let mut syllables = Vec::new();
+
+    for piece in normalized
+        .split(|c: char| c.is_whitespace() || c == '\'' || c == '’')
+        .filter(|piece| !piece.is_empty())
+    {
+        let mut failed = vec![false; piece.len() + 1];
+        let mut segments = Vec::new();
+        if segment(piece, 0, max_chars, is_syllable, &mut failed, &mut segments) {
+            syllables.extend(segments.into_iter().map(str::to_string));
+        } else {
+            syllables.push(piece.to_string());
+        }
+    }
+
+    syllables
+}
+
+/// Segments `piece[start..]` into syllables, longest match first
+///
+/// `is_syllable` decides which candidates are syllables, and `max_chars` is the
+/// length in characters of the longest one. `failed` remembers
+/// the byte positions from which no segmentation exists,
+/// so that backtracking stays linear in the length of the piece.
+fn segment<'a>(
+    piece: &'a str,
+    start: usize,
+    max_chars: usize,
+    is_syllable: &dyn Fn(&str) -> bool,
+    failed: &mut [bool],
+    segments: &mut Vec<&'a str>,
+) -> bool {
+    if start == piece.len() {
+        return true;
+    }
+    if failed[start] {
+        return false;
+    }
+
+    let rest = &piece[start..];
+    let mut ends: Vec<usize> = rest
+        .char_indices()
+        .skip(1)
+        .map(|(index, _)| index)
+        .chain(std::iter::once(rest.len()))
+        .take(max_chars)
+        .collect();
+    ends.reverse();
+
+    for end in ends {
+        let candidate = &rest[..end];
+        if is_syllable(candidate) {
+            segments.push(candidate);
+            if segment(piece, start + end, max_chars, is_syllable, failed, segments) {
+                return true;
+            }
+            segments.pop();
+        }
+    }
+
+    failed[start] = true;
+    false
+}
+
+/// Spells an onset and rime as a toneless pinyin syllable
+///
+/// "ü" is written "u" after j, q, x and y. A `None` onset or rime contributes nothing.
+fn spell_syllable(onset: &HanziOnset, rime: &HanziRime) -> String {
+    let onset_part = match onset {
+        HanziOnset::None => "",
+        _ => onset.as_str(),
+    };
+    let rime_part = match rime {
+        HanziRime::None => "",
+        _ => rime.as_str(),
+    };
+
+    let writes_u_for_umlaut = matches!(
+        onset,
+        HanziOnset::J | HanziOnset::Q | HanziOnset::X | HanziOnset::Y
+    );
+    if rime.is_umlaut() && writes_u_for_umlaut {
+        format!("{onset_part}{}", rime_part.replacen('ü', "u", 1))
+    } else {
+        format!("{onset_part}{rime_part}")
+    }
+}
+
+/// Returns `vowel` carrying the mark of `tone` (1-4), or `None` if there is no such character
+fn with_tone_mark(vowel: char, tone: u32) -> Option<char> {
+    let marked = match vowel {
+        'a' => ['ā', 'á', 'ǎ', 'à'],
+        'e' => ['ē', 'é', 'ě', 'è'],
+        'i' => ['ī', 'í', 'ǐ', 'ì'],
+        'o' => ['ō', 'ó', 'ǒ', 'ò'],
+        'u' => ['ū', 'ú', 'ǔ', 'ù'],
+        'ü' => ['ǖ', 'ǘ', 'ǚ', 'ǜ'],
+        _ => return None,
+    };
+    let index = usize::try_from(tone).ok()?.checked_sub(1)?;
+    marked.get(index).copied()
+}
+
+/// Renders an onset, rime and tone as pinyin with a tone mark
+///
+/// The syllable is spelled with the usual rules ("ü" is written "u" after j,
+/// q, x and y) and the tone mark is placed on "a" or "e" if present, on the
+/// "o" of "ou", and otherwise on the last vowel (so "iu" marks the "u" and
+/// "ui" the "i"). Syllabic nasals (m, n, ng) carry the mark on their first
+/// letter as a combining diacritic. Tone 5 (neutral) and any tone outside
+/// 1-5 leave the syllable unmarked.
+///
+/// # Arguments
+///
+/// * `onset` - The onset of the syllable
+/// * `rime` - The rime of the syllable
+/// * `tone` - The tone number (1-4, or 5 for the neutral tone)
+///
+/// # Returns
+///
+/// The tone-marked pinyin syllable
+///
+/// # Examples
+///
+/// ```
+/// use study_rust_hanzi::{render_pinyin, HanziOnset, HanziRime};
+///
+/// assert_eq!(render_pinyin(&HanziOnset::M, &HanziRime::A, 3), "mǎ");
+/// assert_eq!(render_pinyin(&HanziOnset::N, &HanziRime::V, 3), "nǚ");
+/// assert_eq!(render_pinyin(&HanziOnset::X, &HanziRime::Ve, 2), "xué");
+/// assert_eq!(render_pinyin(&HanziOnset::Zh, &HanziRime::Ong, 5), "zhong");
+/// ```
+pub fn render_pinyin(onset: &HanziOnset, rime: &HanziRime, tone: u32) -> String {
+    let syllable = spell_syllable(onset, rime);
+    if !(1..=4).contains(&tone) {
+        return syllable;
+    }
+
+    let chars: Vec<char> = syllable.chars().collect();
+    let is_vowel = |c: &char| "aeiouü".contains(*c);
+    let target = chars
+        .iter()
+        .position(|&c| c == 'a' || c == 'e')
+        .or_else(|| chars.windows(2).position(|pair| pair == ['o', 'u']))
+        .or_else(|| chars.iter().rposition(is_vowel));
+
+    match target {
+        Some(index) => chars
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| match with_tone_mark(c, tone) {
+                Some(marked) if i == index => marked,
+                _ => c,
+            })
+            .collect(),
+        None => {
+            // Syllabic nasal: put a combining tone mark after the first letter
+            let combining = ['\u{0304}', '\u{0301}', '\u{030C}', '\u{0300}'][tone as usize - 1];
+            let mut rendered = String::new();
+            for (i, c) in chars.iter().enumerate() {
+                rendered.push(*c);
+                if i == 0 {
+                    rendered.push(combining);
+                }
+            }
+            rendered
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_umlaut_round_trip() {
+        use crate::analysis::parse_syllable;
+
+        // The "u" written after j, q, x and y is read as "ü" and written back as "u"
+        assert_eq!(parse_syllable("ju"), Some((HanziOnset::J, HanziRime::V)));
+        assert_eq!(render_pinyin(&HanziOnset::J, &HanziRime::V, 5), "ju");
+        for syllable in [
+            "ju", "qu", "xu", "yu", "jue", "que", "xue", "yue", "nü", "lüe",
+        ] {
+            let (onset, rime) = parse_syllable(syllable).unwrap();
+            assert!(rime.is_umlaut(), "{syllable} should have an umlaut rime");
+            assert_eq!(render_pinyin(&onset, &rime, 5), syllable);
+        }
+
+        // Every standard syllable survives parsing and rendering
+        for syllable in STANDARD_SYLLABLES {
+            let (onset, rime) = parse_syllable(syllable).unwrap();
+            assert_eq!(render_pinyin(&onset, &rime, 5), *syllable);
+        }
+    }
+
+    #[test]
+    fn test_render_pinyin() {
+        assert_eq!(render_pinyin(&HanziOnset::M, &HanziRime::A, 3), "mǎ");
+        assert_eq!(
+            render_pinyin(&HanziOnset::Zh, &HanziRime::Uang, 4),
+            "zhuàng"
+        );
+        assert_eq!(render_pinyin(&HanziOnset::G, &HanziRime::Ou, 3), "gǒu");
+        assert_eq!(render_pinyin(&HanziOnset::L, &HanziRime::Iu, 2), "liú");
+        assert_eq!(render_pinyin(&HanziOnset::H, &HanziRime::Ui, 1), "huī");
+        assert_eq!(render_pinyin(&HanziOnset::None, &HanziRime::Er, 4), "èr");
+        assert_eq!(render_pinyin(&HanziOnset::M, &HanziRime::A, 5), "ma");
+
+        // Umlaut cases: kept after n and l, written "u" after j, q, x and y
+        assert_eq!(render_pinyin(&HanziOnset::N, &HanziRime::V, 3), "nǚ");
+        assert_eq!(render_pinyin(&HanziOnset::L, &HanziRime::Ve, 4), "lüè");
+        assert_eq!(render_pinyin(&HanziOnset::J, &HanziRime::V, 1), "jū");
+        assert_eq!(render_pinyin(&HanziOnset::Q, &HanziRime::Ve, 4), "què");
+        assert_eq!(render_pinyin(&HanziOnset::Y, &HanziRime::V, 2), "yú");
+
+        // Syllabic nasals take a combining mark
+        assert_eq!(
+            render_pinyin(&HanziOnset::None, &HanziRime::Ng, 2),
+            "n\u{0301}g"
+        );
+        assert_eq!(
+            strip_tones(&render_pinyin(&HanziOnset::None, &HanziRime::Ng, 2)),
+            "ng"
+        );
+    }
+
+    #[test]
+    fn test_normalize_fullwidth() {
+        assert_eq!(normalize_fullwidth("ｍａ"), "ma");
+        assert_eq!(normalize_fullwidth("ＺＨＯＮＧ１"), "ZHONG1");
+        assert_eq!(normalize_fullwidth("ｎｖ"), "nv");
+        assert_eq!(normalize_fullwidth("！～"), "!~");
+
+        // Half-width input and other characters are unchanged
+        assert_eq!(normalize_fullwidth("ma3"), "ma3");
+        assert_eq!(normalize_fullwidth("lǜ 中"), "lǜ 中");
+        assert_eq!(normalize_fullwidth(""), "");
+    }
+
+    #[test]
+    fn test_marked_tone() {
+        assert_eq!(marked_tone("mā"), Some(1));
+        assert_eq!(marked_tone("má"), Some(2));
+        assert_eq!(marked_tone("mǎ"), Some(3));
+        assert_eq!(marked_tone("mà"), Some(4));
+        assert_eq!(marked_tone("nǚ"), Some(3));
+        assert_eq!(marked_tone("ma"), None);
+        assert_eq!(marked_tone("nü"), None);
+        // Combining caron after a plain vowel
+        assert_eq!(marked_tone("ma\u{030C}"), Some(3));
+    }
+
+    #[test]
+    fn test_syllabify() {
+        assert_eq!(syllabify("nihao"), vec!["ni", "hao"]);
+        assert_eq!(syllabify("xian"), vec!["xian"]);
+        assert_eq!(syllabify("xi'an"), vec!["xi", "an"]);
+        assert_eq!(syllabify("Xi’an"), vec!["xi", "an"]);
+        assert_eq!(syllabify("zhongguo ren"), vec!["zhong", "guo", "ren"]);
+        assert_eq!(syllabify("tiananmen"), vec!["tian", "an", "men"]);
+        assert_eq!(syllabify("nvren"), vec!["nü", "ren"]);
+        assert_eq!(syllabify("hanguo"), vec!["han", "guo"]);
+        // Pieces that cannot be segmented are kept as they are
+        assert_eq!(syllabify("ni xq"), vec!["ni", "xq"]);
+        assert!(syllabify("").is_empty());
+    }
+
+    #[test]
+    fn test_syllabify_with_syllables() {
+        let syllables: HashSet<String> = ["ni", "hao", "nih", "ao", "nü"]
+            .iter()
+            .map(|syllable| syllable.to_string())
+            .collect();
+
+        // The longest syllable of the custom list wins
+        assert_eq!(
+            syllabify_with_syllables("nihao", &syllables),
+            vec!["nih", "ao"]
+        );
+        assert_eq!(
+            syllabify_with_syllables("NVao", &syllables),
+            vec!["nü", "ao"]
+        );
+        // Standard syllables missing from the list are not used
+        assert_eq!(syllabify_with_syllables("ma", &syllables), vec!["ma"]);
+        // With an empty list every piece is kept as it is
+        assert_eq!(
+            syllabify_with_syllables("ni hao", &HashSet::new()),
+            vec!["ni", "hao"]
+        );
+    }
+
+    #[test]
+    fn test_syllabify_with_syllables_long_and_nasal() {
+        let syllables: HashSet<String> = ["shuangg", "ng", "ma"]
+            .iter()
+            .map(|syllable| syllable.to_string())
+            .collect();
+
+        // Syllables longer than the six letters of standard Mandarin are used
+        assert_eq!(
+            syllabify_with_syllables("shuanggma", &syllables),
+            vec!["shuangg", "ma"]
+        );
+        // Listed nasal syllables are used, unlike in syllabify
+        assert_eq!(
+            syllabify_with_syllables("ngma", &syllables),
+            vec!["ng", "ma"]
+        );
+        assert_eq!(syllabify("ngma"), vec!["ngma"]);
+    }
+
+    #[test]
+    fn test_syllabify_apostrophes() {
+        // An apostrophe always ends a syllable, even where a longer syllable would match
+        assert_eq!(syllabify("Xi'an"), vec!["xi", "an"]);
+        assert_eq!(syllabify("pi'ao"), vec!["pi", "ao"]);
+        assert_eq!(syllabify("piao"), vec!["piao"]);
+        // Leading, trailing and repeated apostrophes leave no empty syllables
+        assert_eq!(syllabify("'xi''an'"), vec!["xi", "an"]);
+        // The boundary holds for pieces that cannot be segmented as well
+        assert_eq!(syllabify("xq'an"), vec!["xq", "an"]);
+
+        for input in ["Xi'an", "chang'an jie", "hai’ou", "'xi''an'"] {
+            assert!(
+                syllabify(input)
+                    .iter()
+                    .all(|syllable| !syllable.contains(['\'', '’'])),
+                "'{input}' kept an apostrophe"
+            );
+        }
+    }
+
+    #[test]
+    fn test_strip_tones() {
+        assert_eq!(strip_tones("mā"), "ma");
+        assert_eq!(strip_tones("má"), "ma");
+        assert_eq!(strip_tones("mǎ"), "ma");
+        assert_eq!(strip_tones("mà"), "ma");
+        assert_eq!(strip_tones("ma"), "ma");
+        assert_eq!(strip_tones("lǜ"), "lü");
+        assert_eq!(strip_tones("nüè"), "nüe");
+        assert_eq!(strip_tones("ń"), "n");
+        assert_eq!(strip_tones("Ōu"), "Ou");
+        // Combining macron after a plain vowel
+        assert_eq!(strip_tones("ma\u{0304}"), "ma");
+    }
+}
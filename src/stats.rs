@@ -0,0 +1,679 @@
+//! # Statistics Module
+//!
+//! This module provides summary metrics computed over the whole dataset, such as
+//! the Shannon entropy of the onset and rime distributions.
+//!
+//! ## Functions
+//!
+//! - [`onset_entropy`]: Shannon entropy of the onset distribution
+//! - [`rime_entropy`]: Shannon entropy of the rime distribution
+//! - [`weighted_onset_counts`]: Onset histogram weighted by frequency rank
+//! - [`weighted_rime_counts`]: Rime histogram weighted by frequency rank
+//! - [`mean_frequency_by_onset`]: Mean frequency rank of the characters of each onset
+//! - [`summarize`]: Computes the main counts of the dataset in one pass
+//! - [`frequency_rank_issues`]: Reports missing and duplicate frequency ranks
+//! - [`renumber_frequency`]: Renumbers the frequency ranks as 1, 2, 3, ...
+//!
+//! ## Types
+//!
+//! - [`Weighting`]: How a frequency rank is turned into a weight
+//! - [`Summary`]: The counts returned by [`summarize`]
+
+use crate::analysis::{analyze_verbose, longest_onset_match};
+use crate::grouping::{group_by_onset, group_by_rime, tone_histogram};
+use crate::types::{HanziOnset, HanziRecord, HanziRime};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Computes the Shannon entropy `-Σ p log2 p` of a count distribution, in bits
+fn entropy(counts: &[u32]) -> f64 {
+    let total: u32 = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = f64::from(count) / f64::from(total);
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Computes the Shannon entropy of the onset distribution of the records
+///
+/// The distribution is the per-onset character count from
+/// [`group_by_onset`]. A dataset using a single onset has
+/// entropy 0, and one split evenly between two onsets has entropy 1.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// The entropy in bits, or 0.0 for empty input
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{onset_entropy, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+///     HanziRecord::builder("八", "bā", 1).build(),
+/// ];
+/// assert!((onset_entropy(&records) - 1.0).abs() < 1e-9);
+/// assert_eq!(onset_entropy(&[]), 0.0);
+/// ```
+pub fn onset_entropy(records: &[HanziRecord]) -> f64 {
+    group_by_onset(records).map_or(0.0, |onset_counts| {
+        let counts: Vec<u32> = onset_counts.iter().map(|(_, count)| *count).collect();
+        entropy(&counts)
+    })
+}
+
+/// Computes the Shannon entropy of the rime distribution of the records
+///
+/// The distribution is the per-rime character count from
+/// [`group_by_rime`].
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// The entropy in bits, or 0.0 for empty input
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{rime_entropy, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+///     HanziRecord::builder("八", "bā", 1).build(),
+/// ];
+/// // Both records have the rime "a"
+/// assert_eq!(rime_entropy(&records), 0.0);
+/// ```
+pub fn rime_entropy(records: &[HanziRecord]) -> f64 {
+    group_by_rime(records).map_or(0.0, |rime_counts| {
+        let counts: Vec<u32> = rime_counts.iter().map(|(_, count)| *count).collect();
+        entropy(&counts)
+    })
+}
+
+/// How a character's frequency rank is turned into a weight
+///
+/// Lower ranks are more common characters. The unknown rank 0 gets weight 0
+/// under every weighting except [`Weighting::Uniform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weighting {
+    /// Every character counts 1, regardless of rank
+    #[default]
+    Uniform,
+    /// A character counts `1 / rank`, strongly favoring common characters
+    Inverse,
+    /// A character counts `1 / (1 + ln rank)`, decaying more slowly than `Inverse`
+    Log,
+}
+
+impl Weighting {
+    /// Returns the weight of a character with the given frequency rank
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use study_rust_hanzi::Weighting;
+    /// assert_eq!(Weighting::Uniform.weight(10), 1.0);
+    /// assert_eq!(Weighting::Inverse.weight(10), 0.1);
+    /// assert_eq!(Weighting::Log.weight(1), 1.0);
+    /// assert_eq!(Weighting::Inverse.weight(0), 0.0);
+    /// ```
+    pub fn weight(self, frequency: u32) -> f64 {
+        match self {
+            Weighting::Uniform => 1.0,
+            _ if frequency == 0 => 0.0,
+            Weighting::Inverse => 1.0 / f64::from(frequency),
+            Weighting::Log => 1.0 / (1.0 + f64::from(frequency).ln()),
+        }
+    }
+}
+
+/// Sums the weights of the characters of each onset
+///
+/// With [`Weighting::Uniform`] this is the plain count of
+/// [`group_by_onset`]; the other weightings let common
+/// characters count more than rare ones.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+/// * `weighting` - How each character's frequency rank is weighted
+///
+/// # Returns
+///
+/// A vector of onsets and their total weights, sorted by weight in descending
+/// order and then by onset chart order
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{weighted_onset_counts, HanziOnset, HanziRecord, Weighting};
+/// let records = vec![
+///     HanziRecord::builder("的", "de", 5).frequency(1).build(),
+///     HanziRecord::builder("八", "bā", 1).frequency(2).build(),
+///     HanziRecord::builder("把", "bǎ", 3).frequency(4).build(),
+/// ];
+/// let uniform = weighted_onset_counts(&records, Weighting::Uniform);
+/// assert_eq!(uniform, vec![(HanziOnset::B, 2.0), (HanziOnset::D, 1.0)]);
+/// let inverse = weighted_onset_counts(&records, Weighting::Inverse);
+/// assert_eq!(inverse, vec![(HanziOnset::D, 1.0), (HanziOnset::B, 0.75)]);
+/// ```
+pub fn weighted_onset_counts(
+    records: &[HanziRecord],
+    weighting: Weighting,
+) -> Vec<(HanziOnset, f64)> {
+    let mut onset_weights: HashMap<HanziOnset, f64> = HashMap::new();
+    for record in records {
+        let (onset, _) = longest_onset_match(&record.pinyin_without_tone);
+        *onset_weights.entry(onset).or_insert(0.0) += weighting.weight(record.frequency);
+    }
+
+    let mut result: Vec<(HanziOnset, f64)> = onset_weights.into_iter().collect();
+    result.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    result
+}
+
+/// Sums the weights of the characters of each rime
+///
+/// The rime counterpart of [`weighted_onset_counts`]: with
+/// [`Weighting::Uniform`] this is the plain count of [`group_by_rime`], and
+/// the other weightings show which finals dominate actual usage.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+/// * `weighting` - How each character's frequency rank is weighted
+///
+/// # Returns
+///
+/// A vector of rimes and their total weights, sorted by weight in descending
+/// order and then by rime chart order
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{weighted_rime_counts, HanziRecord, HanziRime, Weighting};
+/// let records = vec![
+///     HanziRecord::builder("的", "de", 5).frequency(1).build(),
+///     HanziRecord::builder("八", "bā", 1).frequency(2).build(),
+///     HanziRecord::builder("把", "bǎ", 3).frequency(4).build(),
+/// ];
+/// let uniform = weighted_rime_counts(&records, Weighting::Uniform);
+/// assert_eq!(uniform, vec![(HanziRime::A, 2.0), (HanziRime::E, 1.0)]);
+/// let inverse = weighted_rime_counts(&records, Weighting::Inverse);
+/// assert_eq!(inverse, vec![(HanziRime::E, 1.0), (HanziRime::A, 0.75)]);
+/// ```
+pub fn weighted_rime_counts(
+    records: &[HanziRecord],
+    weighting: Weighting,
+) -> Vec<(HanziRime, f64)> {
+    let mut rime_weights: HashMap<HanziRime, f64> = HashMap::new();
+    for record in records {
+        let (_, _, rime) = analyze_verbose(record);
+        *rime_weights.entry(rime).or_insert(0.0) += weighting.weight(record.frequency);
+    }
+
+    let mut result: Vec<(HanziRime, f64)> = rime_weights.into_iter().collect();
+    result.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    result
+}
+
+/// Computes the mean frequency rank of the characters of each onset
+///
+/// A low mean means the characters with that onset tend to be common, a high
+/// one that they tend to be rare, which helps estimate difficulty. Records
+/// with the unknown rank 0 are left out, and so are onsets without any ranked
+/// record.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to analyze
+///
+/// # Returns
+///
+/// A vector of onsets and their mean ranks, sorted by mean in ascending order
+/// and then by onset chart order
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{mean_frequency_by_onset, HanziOnset, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("的", "de", 5).frequency(1).build(),
+///     HanziRecord::builder("八", "bā", 1).frequency(2).build(),
+///     HanziRecord::builder("把", "bǎ", 3).frequency(4).build(),
+/// ];
+/// let means = mean_frequency_by_onset(&records);
+/// assert_eq!(means, vec![(HanziOnset::D, 1.0), (HanziOnset::B, 3.0)]);
+/// ```
+pub fn mean_frequency_by_onset(records: &[HanziRecord]) -> Vec<(HanziOnset, f64)> {
+    let mut onset_ranks: HashMap<HanziOnset, (u64, u32)> = HashMap::new();
+    for record in records.iter().filter(|record| record.frequency != 0) {
+        let (onset, _) = longest_onset_match(&record.pinyin_without_tone);
+        let (sum, count) = onset_ranks.entry(onset).or_insert((0, 0));
+        *sum += u64::from(record.frequency);
+        *count += 1;
+    }
+
+    let mut result: Vec<(HanziOnset, f64)> = onset_ranks
+        .into_iter()
+        .map(|(onset, (sum, count))| (onset, sum as f64 / f64::from(count)))
+        .collect();
+    result.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+    result
+}
+
+/// Reports missing and duplicate frequency ranks
+///
+/// The ranks of a dataset should run from 1 to the number of records without
+/// gaps or repeats; a violation usually points at a merge error. The ranks
+/// between 1 and the highest rank that no record has are reported as missing,
+/// one message per gap (e.g. "Missing ranks 12..=40"), and every rank held by
+/// several records as a duplicate. Records with the unknown rank 0 are
+/// reported as well.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to check
+///
+/// # Returns
+///
+/// One message per issue: the unknown ranks first, then the gaps and
+/// duplicate ranks in rank order; empty if the ranks are contiguous
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{frequency_rank_issues, HanziRecord};
+/// let records: Vec<HanziRecord> = [1, 2, 4, 4, 9]
+///     .iter()
+///     .map(|&rank| HanziRecord::builder("的", "de", 5).frequency(rank).build())
+///     .collect();
+/// assert_eq!(
+///     frequency_rank_issues(&records),
+///     vec!["Missing rank 3", "Duplicate rank 4 (2 records)", "Missing ranks 5..=8"]
+/// );
+/// ```
+pub fn frequency_rank_issues(records: &[HanziRecord]) -> Vec<String> {
+    let mut rank_counts: BTreeMap<u32, usize> = BTreeMap::new();
+    for record in records {
+        *rank_counts.entry(record.frequency).or_insert(0) += 1;
+    }
+
+    let mut issues = Vec::new();
+    if let Some(unknown) = rank_counts.remove(&0) {
+        issues.push(format!("Unknown rank 0 ({unknown} records)"));
+    }
+
+    // Walk the distinct ranks only, so a typo'd huge rank costs one message
+    let mut expected = 1;
+    for (&rank, &count) in &rank_counts {
+        match rank - expected {
+            0 => {}
+            1 => issues.push(format!("Missing rank {expected}")),
+            _ => issues.push(format!("Missing ranks {expected}..={}", rank - 1)),
+        }
+        if count > 1 {
+            issues.push(format!("Duplicate rank {rank} ({count} records)"));
+        }
+        expected = rank.saturating_add(1);
+    }
+    issues
+}
+
+/// Renumbers the frequency ranks of the records as 1, 2, 3, ...
+///
+/// After filtering a subset, ranks such as 5, 42 and 601 are left with gaps.
+/// This sorts the records by their current rank, keeping the order of equal
+/// ranks, and then assigns sequential ranks starting at 1. Records with the
+/// unknown rank 0 are sorted last and ranked after the others. Afterwards
+/// [`frequency_rank_issues`] reports no issues.
+///
+/// # Arguments
+///
+/// * `records` - The records to sort and renumber in place
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{renumber_frequency, HanziRecord};
+/// let mut records = vec![
+///     HanziRecord::builder("马", "mǎ", 3).frequency(601).build(),
+///     HanziRecord::builder("的", "de", 5).frequency(5).build(),
+/// ];
+/// renumber_frequency(&mut records);
+/// assert_eq!(records[0].simplified, "的");
+/// assert_eq!(records[1].frequency, 2);
+/// ```
+pub fn renumber_frequency(records: &mut [HanziRecord]) {
+    records.sort_by_key(|record| match record.frequency {
+        0 => u32::MAX,
+        rank => rank,
+    });
+    for (rank, record) in (1..).zip(records.iter_mut()) {
+        record.frequency = rank;
+    }
+}
+
+/// The main counts of a dataset, as returned by [`summarize`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// The number of records
+    pub total: usize,
+    /// The number of distinct pinyin without tone marks
+    pub distinct_pinyin: usize,
+    /// Characters per onset, sorted as in [`group_by_onset`]
+    pub onset_counts: Vec<(HanziOnset, u32)>,
+    /// Characters per rime, sorted as in [`group_by_rime`]
+    pub rime_counts: Vec<(HanziRime, u32)>,
+    /// Characters per tone number (0 for the unknown tone, 5 for the neutral tone)
+    pub tone_histogram: BTreeMap<u32, u32>,
+}
+
+/// Computes the main counts of the records in one call
+///
+/// This bundles what [`distinct_pinyin`](crate::distinct_pinyin),
+/// [`group_by_onset`] and [`group_by_rime`] compute separately, together with
+/// a histogram of tones. Onset and rime are derived from the pinyin, so the
+/// records need not be analyzed beforehand.
+///
+/// # Arguments
+///
+/// * `records` - A slice of HanziRecord to summarize
+///
+/// # Returns
+///
+/// A [`Summary`] of the records; every count is empty for empty input
+///
+/// # Examples
+///
+/// ```rust
+/// # use study_rust_hanzi::{summarize, HanziOnset, HanziRecord};
+/// let records = vec![
+///     HanziRecord::builder("马", "mǎ", 3).build(),
+///     HanziRecord::builder("码", "mǎ", 3).build(),
+///     HanziRecord::builder("八", "bā", 1).build(),
+/// ];
+/// let summary = summarize(&records);
+/// assert_eq!(summary.total, 3);
+/// assert_eq!(summary.distinct_pinyin, 2);
+/// assert_eq!(summary.onset_counts[0], (HanziOnset::M, 2));
+/// assert_eq!(summary.tone_histogram[&3], 2);
+/// ```
+pub fn summarize(records: &[HanziRecord]) -> Summary {
+    let mut pinyins: HashSet<&str> = HashSet::new();
+    let mut onset_counts: HashMap<HanziOnset, u32> = HashMap::new();
+    let mut rime_counts: HashMap<HanziRime, u32> = HashMap::new();
+
+    for record in records {
+        let (onset, _, rime) = analyze_verbose(record);
+        pinyins.insert(&record.pinyin_without_tone);
+        *onset_counts.entry(onset).or_insert(0) += 1;
+        *rime_counts.entry(rime).or_insert(0) += 1;
+    }
+
+    // Sort by count in descending order, then by chart order
+    let mut onset_counts: Vec<(HanziOnset, u32)> = onset_counts.into_iter().collect();
+    onset_counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut rime_counts: Vec<(HanziRime, u32)> = rime_counts.into_iter().collect();
+    rime_counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    Summary {
+        total: records.len(),
+        distinct_pinyin: pinyins.len(),
+        onset_counts,
+        rime_counts,
+        tone_histogram: tone_histogram(records),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_hanzi_file;
+
+    #[test]
+    fn test_onset_entropy_uniform_two_onsets() {
+        let records = vec![
+            HanziRecord::builder("马", "mǎ", 3).build(),
+            HanziRecord::builder("妈", "mā", 1).build(),
+            HanziRecord::builder("八", "bā", 1).build(),
+            HanziRecord::builder("爸", "bà", 4).build(),
+        ];
+
+        assert!((onset_entropy(&records) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rime_entropy() {
+        // Four rimes with one record each: log2(4) = 2 bits
+        let records = vec![
+            HanziRecord::builder("马", "mǎ", 3).build(),
+            HanziRecord::builder("没", "méi", 2).build(),
+            HanziRecord::builder("买", "mǎi", 3).build(),
+            HanziRecord::builder("毛", "máo", 2).build(),
+        ];
+
+        assert!((rime_entropy(&records) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weighted_onset_counts_relative_ordering() {
+        let records = read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv file");
+
+        let uniform: HashMap<_, _> = weighted_onset_counts(&records, Weighting::Uniform)
+            .into_iter()
+            .collect();
+        let log: HashMap<_, _> = weighted_onset_counts(&records, Weighting::Log)
+            .into_iter()
+            .collect();
+        let inverse: HashMap<_, _> = weighted_onset_counts(&records, Weighting::Inverse)
+            .into_iter()
+            .collect();
+
+        // Uniform weighting is the plain onset count
+        for (onset, count) in group_by_onset(&records).unwrap() {
+            assert_eq!(uniform[&onset], f64::from(count));
+        }
+
+        // Every rank >= 1 weighs at most 1 under Log and at most the Log weight under Inverse
+        for (onset, uniform_weight) in &uniform {
+            assert!(inverse[onset] <= log[onset], "{onset:?}: inverse > log");
+            assert!(log[onset] <= *uniform_weight, "{onset:?}: log > uniform");
+        }
+
+        // The single most common character (的, rank 1) lifts D under Inverse
+        let inverse_order = weighted_onset_counts(&records, Weighting::Inverse);
+        let uniform_order = weighted_onset_counts(&records, Weighting::Uniform);
+        let position = |order: &[(HanziOnset, f64)]| {
+            order.iter().position(|(onset, _)| *onset == HanziOnset::D)
+        };
+        assert!(position(&inverse_order) < position(&uniform_order));
+    }
+
+    #[test]
+    fn test_weighted_rime_counts() {
+        // 的 (rank 1) is far more common than 鸨 (rank 5000)
+        let records = vec![
+            HanziRecord::builder("的", "de", 5).frequency(1).build(),
+            HanziRecord::builder("鸨", "bǎo", 3).frequency(5000).build(),
+        ];
+        for weighting in [Weighting::Log, Weighting::Inverse] {
+            let weights = weighted_rime_counts(&records, weighting);
+            assert_eq!(weights[0].0, HanziRime::E);
+            assert_eq!(weights[1].0, HanziRime::Ao);
+            assert!(weights[0].1 > weights[1].1);
+        }
+
+        // Uniform weighting is the plain rime count
+        let records = read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv file");
+        let uniform: HashMap<_, _> = weighted_rime_counts(&records, Weighting::Uniform)
+            .into_iter()
+            .collect();
+        for (rime, count) in group_by_rime(&records).unwrap() {
+            assert_eq!(uniform[&rime], f64::from(count));
+        }
+    }
+
+    #[test]
+    fn test_mean_frequency_by_onset() {
+        let records = read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv file");
+        let means = mean_frequency_by_onset(&records);
+
+        // The 400 j-initial characters have ranks summing to 992009
+        let (_, j_mean) = means
+            .iter()
+            .find(|(onset, _)| *onset == HanziOnset::J)
+            .expect("J should occur in the data");
+        assert!((j_mean - 2480.0225).abs() < 1e-9);
+
+        assert_eq!(means.len(), HanziOnset::all().len());
+        assert!(means.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    }
+
+    #[test]
+    fn test_mean_frequency_by_onset_skips_unknown_rank() {
+        let records = vec![
+            HanziRecord::builder("机", "jī", 1).frequency(10).build(),
+            HanziRecord::builder("计", "jì", 4).frequency(30).build(),
+            HanziRecord::builder("几", "jǐ", 3).build(),
+            HanziRecord::builder("马", "mǎ", 3).build(),
+        ];
+
+        assert_eq!(
+            mean_frequency_by_onset(&records),
+            vec![(HanziOnset::J, 20.0)]
+        );
+        assert!(mean_frequency_by_onset(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_frequency_rank_issues() {
+        let records: Vec<HanziRecord> = [1, 2, 4, 5, 5, 6, 0]
+            .iter()
+            .map(|&rank| HanziRecord::builder("的", "de", 5).frequency(rank).build())
+            .collect();
+
+        assert_eq!(
+            frequency_rank_issues(&records),
+            vec![
+                "Unknown rank 0 (1 records)",
+                "Missing rank 3",
+                "Duplicate rank 5 (2 records)"
+            ]
+        );
+        assert!(frequency_rank_issues(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_frequency_rank_issues_reports_gaps_as_ranges() {
+        // A typo'd rank far beyond the others gives a single message
+        let records: Vec<HanziRecord> = [2, 3, 12, u32::MAX]
+            .iter()
+            .map(|&rank| HanziRecord::builder("的", "de", 5).frequency(rank).build())
+            .collect();
+
+        assert_eq!(
+            frequency_rank_issues(&records),
+            vec![
+                "Missing rank 1".to_string(),
+                "Missing ranks 4..=11".to_string(),
+                format!("Missing ranks 13..={}", u32::MAX - 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frequency_rank_issues_sample_is_contiguous() {
+        let records = read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv file");
+        assert!(frequency_rank_issues(&records).is_empty());
+    }
+
+    #[test]
+    fn test_renumber_frequency() {
+        let mut records: Vec<HanziRecord> = [("马", 601), ("的", 5), ("一", 42), ("是", 0)]
+            .iter()
+            .map(|&(character, rank)| {
+                HanziRecord::builder(character, "de", 5)
+                    .frequency(rank)
+                    .build()
+            })
+            .collect();
+
+        renumber_frequency(&mut records);
+
+        let ranked: Vec<(&str, u32)> = records
+            .iter()
+            .map(|record| (record.simplified.as_str(), record.frequency))
+            .collect();
+        // The unknown rank 0 is ranked last
+        assert_eq!(ranked, vec![("的", 1), ("一", 2), ("马", 3), ("是", 4)]);
+        assert!(frequency_rank_issues(&records).is_empty());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let records = vec![
+            HanziRecord::builder("马", "mǎ", 3).build(),
+            HanziRecord::builder("妈", "mā", 1).build(),
+            HanziRecord::builder("吗", "ma", 5).build(),
+            HanziRecord::builder("八", "bā", 1).build(),
+            HanziRecord::builder("北", "běi", 3).build(),
+        ];
+
+        let summary = summarize(&records);
+
+        assert_eq!(summary.total, 5);
+        assert_eq!(summary.distinct_pinyin, 3);
+        assert_eq!(
+            summary.onset_counts,
+            vec![(HanziOnset::M, 3), (HanziOnset::B, 2)]
+        );
+        assert_eq!(
+            summary.rime_counts,
+            vec![(HanziRime::A, 4), (HanziRime::Ei, 1)]
+        );
+        assert_eq!(
+            summary.tone_histogram,
+            BTreeMap::from([(1, 2), (3, 2), (5, 1)])
+        );
+        assert_eq!(summarize(&[]), Summary::default());
+    }
+
+    #[test]
+    fn test_summarize_matches_grouping_functions() {
+        let records = read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv file");
+
+        let summary = summarize(&records);
+
+        assert_eq!(summary.total, records.len());
+        assert_eq!(
+            summary.distinct_pinyin,
+            crate::distinct_pinyin(&records).len()
+        );
+        assert_eq!(summary.onset_counts, group_by_onset(&records).unwrap());
+        assert_eq!(summary.rime_counts, group_by_rime(&records).unwrap());
+        let tone_total: u32 = summary.tone_histogram.values().sum();
+        assert_eq!(tone_total as usize, records.len());
+    }
+
+    #[test]
+    fn test_entropy_empty() {
+        assert_eq!(onset_entropy(&[]), 0.0);
+        assert_eq!(rime_entropy(&[]), 0.0);
+        assert_eq!(entropy(&[]), 0.0);
+    }
+}
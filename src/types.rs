@@ -7,8 +7,10 @@
 //! ## Types
 //!
 //! - [`HanziRecord`]: Represents a single Chinese character with all its linguistic properties
+//! - [`HanziRecordBuilder`]: Builds a [`HanziRecord`] with derived default fields
 //! - [`HanziOnset`]: Enumeration of pinyin onset sounds (initial consonants)
 //! - [`HanziRime`]: Enumeration of pinyin rime sounds (vowels and final consonants)
+//! - [`ToneLookupError`]: Error returned when a tone lookup finds no characters
 
 use std::str::FromStr;
 
@@ -23,7 +25,13 @@ use std::str::FromStr;
 /// - `B`: as in "bā" (八)
 /// - `Zh`: as in "zhōng" (中)
 /// - `None`: as in "ā" (啊) - syllables starting with vowels
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// # Ordering
+///
+/// Onsets are ordered as in the pinyin onset chart (b, p, m, f, d, t, n, ...),
+/// which is the declaration order of the variants. `None` comes last.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HanziOnset {
     B,
     P,
@@ -48,10 +56,39 @@ pub enum HanziOnset {
     H,
     Y,
     W,
+    #[default]
     None,
 }
 
+/// Onset spellings in matching order
+///
+/// Every onset must come before any shorter onset that is a prefix of it
+/// ("zh" before "z", "ch" before "c", "sh" before "s"), so that the first
+/// match is the longest one.
+pub(crate) const ONSET_CANDIDATES: &[&str] = &[
+    "zh", "ch", "sh", // Multi-character onsets first
+    "b", "p", "m", "f", "d", "t", "n", "z", "c", "s", "l", "r", "j", "q", "x", "g", "k", "h", "y",
+    "w",
+];
+
 impl HanziOnset {
+    /// Returns every onset in onset chart order, ending with `None`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use study_rust_hanzi::HanziOnset;
+    ///
+    /// assert_eq!(HanziOnset::all().len(), 24);
+    /// assert_eq!(HanziOnset::all()[0], HanziOnset::B);
+    /// ```
+    pub fn all() -> &'static [HanziOnset] {
+        use HanziOnset::*;
+        &[
+            B, P, M, F, D, T, N, Z, C, S, L, Zh, Ch, Sh, R, J, Q, X, G, K, H, Y, W, None,
+        ]
+    }
+
     /// Returns the kebab-case string representation of the onset
     ///
     /// This method converts the onset to a kebab-case string format,
@@ -99,6 +136,114 @@ impl HanziOnset {
             HanziOnset::None => "none",
         }
     }
+
+    /// Returns whether the onset is a retroflex: zh, ch, sh or r
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use study_rust_hanzi::HanziOnset;
+    ///
+    /// assert!(HanziOnset::Zh.is_retroflex());
+    /// assert!(!HanziOnset::Z.is_retroflex());
+    /// ```
+    pub fn is_retroflex(&self) -> bool {
+        matches!(
+            self,
+            HanziOnset::Zh | HanziOnset::Ch | HanziOnset::Sh | HanziOnset::R
+        )
+    }
+
+    /// Returns whether the onset is a dental sibilant: z, c or s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use study_rust_hanzi::HanziOnset;
+    ///
+    /// assert!(HanziOnset::C.is_sibilant());
+    /// assert!(!HanziOnset::Ch.is_sibilant());
+    /// ```
+    pub fn is_sibilant(&self) -> bool {
+        matches!(self, HanziOnset::Z | HanziOnset::C | HanziOnset::S)
+    }
+
+    /// Returns whether the onset is aspirated: p, t, k, q, ch or c
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use study_rust_hanzi::HanziOnset;
+    ///
+    /// assert!(HanziOnset::P.is_aspirated());
+    /// assert!(!HanziOnset::B.is_aspirated());
+    /// ```
+    pub fn is_aspirated(&self) -> bool {
+        matches!(
+            self,
+            HanziOnset::P
+                | HanziOnset::T
+                | HanziOnset::K
+                | HanziOnset::Q
+                | HanziOnset::Ch
+                | HanziOnset::C
+        )
+    }
+
+    /// Splits the longest matching onset off the front of a toneless pinyin
+    ///
+    /// Onsets are checked in order of decreasing length, so "zh", "ch" and "sh"
+    /// win over "z", "c" and "s". Vowel-initial syllables and the syllabic nasal
+    /// interjections "m", "n" and "ng" have no onset and are returned whole.
+    ///
+    /// # Arguments
+    ///
+    /// * `pinyin` - Pinyin without tone marks
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the onset and the remainder of `pinyin` after it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use study_rust_hanzi::HanziOnset;
+    ///
+    /// assert_eq!(HanziOnset::strip_from("zhong"), (HanziOnset::Zh, "ong"));
+    /// assert_eq!(HanziOnset::strip_from("ao"), (HanziOnset::None, "ao"));
+    /// assert_eq!(HanziOnset::strip_from("ng"), (HanziOnset::None, "ng"));
+    /// ```
+    pub fn strip_from(pinyin: &str) -> (HanziOnset, &str) {
+        Self::strip_from_candidates(pinyin, ONSET_CANDIDATES)
+    }
+
+    /// Splits the first matching onset of `candidates` off a toneless pinyin
+    ///
+    /// Works like [`HanziOnset::strip_from`] with a caller-provided list of
+    /// onset spellings, checked in order. Spellings that are not onsets are
+    /// ignored.
+    pub(crate) fn strip_from_candidates<'a>(
+        pinyin: &'a str,
+        candidates: &[&str],
+    ) -> (HanziOnset, &'a str) {
+        // Interjection syllables consisting of a nasal only (e.g. 嗯 "ng", 呣 "m")
+        const SYLLABIC_NASALS: &[&str] = &["m", "n", "ng"];
+
+        // Syllabic nasals are the whole syllable, not an onset
+        if SYLLABIC_NASALS.contains(&pinyin) {
+            return (HanziOnset::None, pinyin);
+        }
+
+        candidates
+            .iter()
+            .find_map(|&onset_str| {
+                let rest = pinyin.strip_prefix(onset_str)?;
+                HanziOnset::from_str(onset_str)
+                    .ok()
+                    .map(|onset| (onset, rest))
+            })
+            .unwrap_or((HanziOnset::None, pinyin))
+    }
 }
 
 impl FromStr for HanziOnset {
@@ -172,7 +317,17 @@ impl FromStr for HanziOnset {
 /// - `Ang`: as in "tāng" (汤) - vowel + nasal consonant
 /// - `Iang`: as in "liáng" (良) - complex vowel + nasal
 /// - `V`: represents "ü" as in "nǚ" (女)
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// - `Ng`: syllabic nasal as in the interjection "ńg" (嗯)
+///
+/// The syllabic nasals `M`, `N` and `Ng` only appear in interjections such as
+/// "m", "n", "ng", "hm" and "hng", which have no vowel.
+///
+/// # Ordering
+///
+/// Rimes are ordered as in the pinyin rime chart (e, a, o, ei, ai, ...), which
+/// is the declaration order of the variants. `None` comes last.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HanziRime {
     E,
     A,
@@ -208,10 +363,32 @@ pub enum HanziRime {
     V,
     Ve,
     Ue,
+    M,
+    N,
+    Ng,
+    #[default]
     None,
 }
 
 impl HanziRime {
+    /// Returns every rime in rime chart order, ending with `None`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use study_rust_hanzi::HanziRime;
+    ///
+    /// assert_eq!(HanziRime::all().len(), 38);
+    /// assert_eq!(HanziRime::all()[0], HanziRime::E);
+    /// ```
+    pub fn all() -> &'static [HanziRime] {
+        use HanziRime::*;
+        &[
+            E, A, O, Ei, Ai, Ou, Ao, En, An, Ong, Eng, Ang, Er, I, Ie, Ia, Iu, Iao, In, Ian, Iong,
+            Ing, Iang, U, Uo, Ua, Ui, Uai, Un, Uan, Uang, V, Ve, Ue, M, N, Ng, None,
+        ]
+    }
+
     /// Returns the string representation of the rime
     ///
     /// This method converts the rime to its pinyin string representation.
@@ -271,9 +448,28 @@ impl HanziRime {
             HanziRime::V => "ü",
             HanziRime::Ve => "üe",
             HanziRime::Ue => "ue",
+            HanziRime::M => "m",
+            HanziRime::N => "n",
+            HanziRime::Ng => "ng",
             HanziRime::None => "none",
         }
     }
+
+    /// Returns whether the rime is spelled with "ü"
+    ///
+    /// After j, q, x and y the "ü" of these rimes is written "u".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use study_rust_hanzi::HanziRime;
+    ///
+    /// assert!(HanziRime::V.is_umlaut());
+    /// assert!(!HanziRime::U.is_umlaut());
+    /// ```
+    pub fn is_umlaut(&self) -> bool {
+        self.as_str().starts_with('ü')
+    }
 }
 
 impl FromStr for HanziRime {
@@ -347,6 +543,9 @@ impl FromStr for HanziRime {
             "ue" => Ok(HanziRime::Ue),
             "v" => Ok(HanziRime::V),   // Alternative representation for ü
             "ve" => Ok(HanziRime::Ve), // Alternative representation for üe
+            "m" => Ok(HanziRime::M),
+            "n" => Ok(HanziRime::N),
+            "ng" => Ok(HanziRime::Ng),
             "none" => Ok(HanziRime::None),
             _ => Err(format!("Invalid rime: '{s}'")),
         }
@@ -366,10 +565,34 @@ impl FromStr for HanziRime {
 /// * `traditional` - Traditional Chinese character form  
 /// * `pinyin` - Complete pinyin with tone marks (e.g., "mā")
 /// * `pinyin_without_tone` - Pinyin without tone marks (e.g., "ma")
-/// * `tone` - Tone number (1-4 for tones, 5 for neutral tone)
+/// * `tone` - Tone number (1-4 for tones, 5 for neutral tone, 0 for unknown tone)
 /// * `onset` - Initial consonant sound classification
 /// * `rime` - Vowel and final consonant sound classification
-#[derive(Debug, Clone)]
+///
+/// # Equality and Hashing
+///
+/// Two records are equal only if all fields are equal, including `onset` and `rime`.
+/// A record read from a file (onset/rime `None`) therefore differs from the same
+/// record after [`set_hanzi_onsets`](crate::set_hanzi_onsets) and
+/// [`set_hanzi_rime`](crate::set_hanzi_rime) have been applied.
+///
+/// # Default
+///
+/// `HanziRecord::default()` has empty strings, frequency 0, the unknown tone 0,
+/// and `None` onset and rime. The enums default to their `None` variants.
+///
+/// ```
+/// use study_rust_hanzi::{HanziOnset, HanziRecord};
+///
+/// let record = HanziRecord {
+///     simplified: "马".to_string(),
+///     ..HanziRecord::default()
+/// };
+/// assert_eq!(record.tone, 0);
+/// assert_eq!(record.onset, HanziOnset::None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HanziRecord {
     pub frequency: u32,
     pub simplified: String,
@@ -381,10 +604,163 @@ pub struct HanziRecord {
     pub rime: HanziRime,
 }
 
+impl HanziRecord {
+    /// Starts building a HanziRecord from the character, its pinyin and its tone
+    ///
+    /// The builder fills in sensible defaults for the remaining fields:
+    /// - `frequency` is 0
+    /// - `traditional` is the same as `simplified`
+    /// - `pinyin_without_tone` is derived from `pinyin` by removing its tone marks
+    /// - `onset` and `rime` are `None`, pending analysis by
+    ///   [`set_hanzi_onsets`](crate::set_hanzi_onsets) and [`set_hanzi_rime`](crate::set_hanzi_rime)
+    ///
+    /// # Arguments
+    ///
+    /// * `simplified` - Simplified Chinese character form
+    /// * `pinyin` - Pinyin with tone marks (e.g., "mǎ")
+    /// * `tone` - Tone number (1-4 for tones, 5 for neutral tone, 0 for unknown tone)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use study_rust_hanzi::HanziRecord;
+    ///
+    /// let record = HanziRecord::builder("马", "mǎ", 3)
+    ///     .traditional("馬")
+    ///     .frequency(8)
+    ///     .build();
+    /// assert_eq!(record.traditional, "馬");
+    /// assert_eq!(record.pinyin_without_tone, "ma");
+    /// ```
+    pub fn builder(simplified: &str, pinyin: &str, tone: u32) -> HanziRecordBuilder {
+        HanziRecordBuilder {
+            record: HanziRecord {
+                simplified: simplified.to_string(),
+                traditional: simplified.to_string(),
+                pinyin: pinyin.to_string(),
+                pinyin_without_tone: crate::pinyin::strip_tones(pinyin),
+                tone,
+                ..HanziRecord::default()
+            },
+        }
+    }
+}
+
+/// Builder for [`HanziRecord`], created by [`HanziRecord::builder`]
+#[derive(Debug, Clone)]
+pub struct HanziRecordBuilder {
+    record: HanziRecord,
+}
+
+impl HanziRecordBuilder {
+    /// Sets the frequency rank (lower numbers = more common)
+    pub fn frequency(mut self, frequency: u32) -> Self {
+        self.record.frequency = frequency;
+        self
+    }
+
+    /// Sets the traditional character form when it differs from the simplified one
+    pub fn traditional(mut self, traditional: &str) -> Self {
+        self.record.traditional = traditional.to_string();
+        self
+    }
+
+    /// Overrides the pinyin without tone marks derived from the pinyin
+    pub fn pinyin_without_tone(mut self, pinyin_without_tone: &str) -> Self {
+        self.record.pinyin_without_tone = pinyin_without_tone.to_string();
+        self
+    }
+
+    /// Finishes building and returns the HanziRecord
+    pub fn build(self) -> HanziRecord {
+        self.record
+    }
+}
+
+/// Error returned by [`try_group_by_tone`](crate::try_group_by_tone)
+///
+/// # Examples
+///
+/// ```
+/// use study_rust_hanzi::ToneLookupError;
+///
+/// let error = ToneLookupError::NotFound {
+///     pinyin: "xq".to_string(),
+/// };
+/// assert_eq!(error.to_string(), "No characters found for pinyin: xq");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToneLookupError {
+    /// No record has the queried pinyin
+    NotFound {
+        /// The pinyin that was looked up, as passed to the lookup
+        pinyin: String,
+    },
+}
+
+impl std::fmt::Display for ToneLookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToneLookupError::NotFound { pinyin } => {
+                write!(f, "No characters found for pinyin: {pinyin}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ToneLookupError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_hanzi_onset_classes() {
+        let retroflex: Vec<&HanziOnset> = HanziOnset::all()
+            .iter()
+            .filter(|onset| onset.is_retroflex())
+            .collect();
+        assert_eq!(
+            retroflex,
+            [
+                &HanziOnset::Zh,
+                &HanziOnset::Ch,
+                &HanziOnset::Sh,
+                &HanziOnset::R
+            ]
+        );
+
+        let sibilant: Vec<&HanziOnset> = HanziOnset::all()
+            .iter()
+            .filter(|onset| onset.is_sibilant())
+            .collect();
+        assert_eq!(sibilant, [&HanziOnset::Z, &HanziOnset::C, &HanziOnset::S]);
+
+        // Each aspirated onset pairs with an unaspirated one: b-p, d-t, g-k, j-q, zh-ch, z-c
+        for (plain, aspirated) in [
+            (HanziOnset::B, HanziOnset::P),
+            (HanziOnset::D, HanziOnset::T),
+            (HanziOnset::G, HanziOnset::K),
+            (HanziOnset::J, HanziOnset::Q),
+            (HanziOnset::Zh, HanziOnset::Ch),
+            (HanziOnset::Z, HanziOnset::C),
+        ] {
+            assert!(!plain.is_aspirated(), "{plain:?} should be unaspirated");
+            assert!(
+                aspirated.is_aspirated(),
+                "{aspirated:?} should be aspirated"
+            );
+        }
+        assert!(!HanziOnset::None.is_aspirated());
+        assert_eq!(
+            HanziOnset::all()
+                .iter()
+                .filter(|onset| onset.is_aspirated())
+                .count(),
+            6
+        );
+    }
+
     #[test]
     fn test_hanzi_onset_as_str() {
         // Test single character onsets
@@ -449,6 +825,31 @@ mod tests {
         assert_eq!(result.unwrap_err(), "Invalid onset: 'invalid'");
     }
 
+    #[test]
+    fn test_onset_candidates_longest_first() {
+        for (index, candidate) in ONSET_CANDIDATES.iter().enumerate() {
+            for shorter in &ONSET_CANDIDATES[..index] {
+                assert!(
+                    !candidate.starts_with(shorter),
+                    "'{candidate}' must come before its prefix '{shorter}'"
+                );
+            }
+        }
+        // Every onset except None has a candidate
+        assert_eq!(ONSET_CANDIDATES.len(), HanziOnset::all().len() - 1);
+    }
+
+    #[test]
+    fn test_onset_strip_from() {
+        assert_eq!(HanziOnset::strip_from("zhong"), (HanziOnset::Zh, "ong"));
+        assert_eq!(HanziOnset::strip_from("zong"), (HanziOnset::Z, "ong"));
+        assert_eq!(HanziOnset::strip_from("ao"), (HanziOnset::None, "ao"));
+        assert_eq!(HanziOnset::strip_from("nü"), (HanziOnset::N, "ü"));
+        assert_eq!(HanziOnset::strip_from("n"), (HanziOnset::None, "n"));
+        assert_eq!(HanziOnset::strip_from("hng"), (HanziOnset::H, "ng"));
+        assert_eq!(HanziOnset::strip_from(""), (HanziOnset::None, ""));
+    }
+
     #[test]
     fn test_hanzi_rime_as_str() {
         // Test simple vowel rimes
@@ -497,6 +898,11 @@ mod tests {
         assert_eq!(HanziRime::Ve.as_str(), "üe");
         assert_eq!(HanziRime::Ue.as_str(), "ue");
 
+        // Test syllabic nasal rimes
+        assert_eq!(HanziRime::M.as_str(), "m");
+        assert_eq!(HanziRime::N.as_str(), "n");
+        assert_eq!(HanziRime::Ng.as_str(), "ng");
+
         // Test None case
         assert_eq!(HanziRime::None.as_str(), "none");
     }
@@ -551,6 +957,11 @@ mod tests {
         assert_eq!(HanziRime::from_str("ve"), Ok(HanziRime::Ve));
         assert_eq!(HanziRime::from_str("ue"), Ok(HanziRime::Ue));
 
+        // Test syllabic nasal rimes
+        assert_eq!(HanziRime::from_str("m"), Ok(HanziRime::M));
+        assert_eq!(HanziRime::from_str("n"), Ok(HanziRime::N));
+        assert_eq!(HanziRime::from_str("ng"), Ok(HanziRime::Ng));
+
         // Test case insensitivity
         assert_eq!(HanziRime::from_str("ANG"), Ok(HanziRime::Ang));
         assert_eq!(HanziRime::from_str("Iang"), Ok(HanziRime::Iang));
@@ -570,4 +981,90 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Invalid rime: 'invalid'");
     }
+
+    #[test]
+    fn test_rime_is_umlaut() {
+        assert!(HanziRime::V.is_umlaut());
+        assert!(HanziRime::Ve.is_umlaut());
+        assert!(!HanziRime::U.is_umlaut());
+        assert!(!HanziRime::Ue.is_umlaut());
+
+        let umlauts: Vec<&HanziRime> = HanziRime::all()
+            .iter()
+            .filter(|rime| rime.is_umlaut())
+            .collect();
+        assert_eq!(umlauts, vec![&HanziRime::V, &HanziRime::Ve]);
+    }
+
+    #[test]
+    fn test_all_variants_are_sorted() {
+        assert!(HanziOnset::all().windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(HanziRime::all().windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(HanziOnset::all().last(), Some(&HanziOnset::None));
+        assert_eq!(HanziRime::all().last(), Some(&HanziRime::None));
+    }
+
+    #[test]
+    fn test_hanzi_record_default() {
+        let record = HanziRecord::default();
+
+        assert_eq!(record.frequency, 0);
+        assert_eq!(record.simplified, "");
+        assert_eq!(record.traditional, "");
+        assert_eq!(record.pinyin, "");
+        assert_eq!(record.pinyin_without_tone, "");
+        assert_eq!(record.tone, 0);
+        assert_eq!(record.onset, HanziOnset::None);
+        assert_eq!(record.rime, HanziRime::None);
+        assert_eq!(HanziOnset::default(), HanziOnset::None);
+        assert_eq!(HanziRime::default(), HanziRime::None);
+    }
+
+    #[test]
+    fn test_hanzi_record_builder_defaults() {
+        let record = HanziRecord::builder("女", "nǚ", 3).build();
+
+        assert_eq!(record.frequency, 0);
+        assert_eq!(record.simplified, "女");
+        assert_eq!(record.traditional, "女"); // Defaults to simplified
+        assert_eq!(record.pinyin, "nǚ");
+        assert_eq!(record.pinyin_without_tone, "nü"); // Derived by stripping tones
+        assert_eq!(record.tone, 3);
+        assert_eq!(record.onset, HanziOnset::None);
+        assert_eq!(record.rime, HanziRime::None);
+    }
+
+    #[test]
+    fn test_hanzi_record_builder_overrides() {
+        let record = HanziRecord::builder("马", "mǎ", 3)
+            .traditional("馬")
+            .frequency(8)
+            .build();
+
+        assert_eq!(record.frequency, 8);
+        assert_eq!(record.simplified, "马");
+        assert_eq!(record.traditional, "馬");
+        assert_eq!(record.pinyin_without_tone, "ma");
+    }
+
+    #[test]
+    fn test_hanzi_record_dedup_in_hash_set() {
+        use std::collections::HashSet;
+
+        let ma = HanziRecord::builder("马", "mǎ", 3).frequency(8).build();
+        let ji = HanziRecord::builder("机", "jī", 1).frequency(6).build();
+
+        let set: HashSet<HanziRecord> = vec![ma.clone(), ji.clone(), ma.clone()]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&ma));
+        assert!(set.contains(&ji));
+
+        // onset/rime participate in equality
+        let mut analyzed = ma.clone();
+        analyzed.onset = HanziOnset::M;
+        analyzed.rime = HanziRime::A;
+        assert_ne!(ma, analyzed);
+    }
 }
@@ -90,6 +90,354 @@ fn test_by_pinyin_fold_option() {
     );
 }
 
+/// Returns true if the text contains any CJK unified ideograph
+fn contains_cjk(text: &str) -> bool {
+    text.chars().any(|c| {
+        ('\u{3400}'..='\u{4DBF}').contains(&c)
+            || ('\u{4E00}'..='\u{9FFF}').contains(&c)
+            || ('\u{20000}'..='\u{2FFFF}').contains(&c)
+    })
+}
+
+#[test]
+fn test_by_pinyin_counts_only() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--counts-only"])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    assert!(!stdout.is_empty(), "Output should not be empty");
+    assert!(
+        !contains_cjk(&stdout),
+        "Counts-only output should not contain any characters"
+    );
+
+    // Each line should be "pinyin : count"
+    for line in stdout.lines() {
+        let parts: Vec<&str> = line.split(':').collect();
+        assert_eq!(parts.len(), 2, "Each line should have exactly one ':'");
+        assert!(
+            parts[1].trim().parse::<u32>().is_ok(),
+            "Count should be a valid number"
+        );
+    }
+}
+
+#[test]
+fn test_by_pinyin_counts_only_rejects_pinyin_and_fold() {
+    let with_pinyin = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma", "--counts-only"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        !with_pinyin.status.success(),
+        "--counts-only with a pinyin should be rejected"
+    );
+
+    let with_fold = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--counts-only", "--fold", "20"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        !with_fold.status.success(),
+        "--counts-only with --fold should be rejected"
+    );
+}
+
+#[test]
+fn test_by_pinyin_color_never_and_always() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let never_output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--color", "never"])
+        .output()
+        .expect("Failed to execute command");
+    let always_output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--color", "always"])
+        .output()
+        .expect("Failed to execute command");
+
+    let never_stdout = String::from_utf8(never_output.stdout).expect("Invalid UTF-8");
+    let always_stdout = String::from_utf8(always_output.stdout).expect("Invalid UTF-8");
+
+    assert!(!never_stdout.is_empty());
+    assert!(
+        !never_stdout.contains('\x1b'),
+        "--color never should not emit escape codes"
+    );
+    assert!(
+        always_stdout.contains("\x1b[32m"),
+        "--color always should color common syllables"
+    );
+}
+
+#[test]
+fn test_by_pinyin_color_rejects_pinyin_and_counts_only() {
+    let with_pinyin = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma", "--color", "always"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        !with_pinyin.status.success(),
+        "--color with a pinyin should be rejected"
+    );
+
+    let with_counts_only = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--counts-only", "--color", "always"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        !with_counts_only.status.success(),
+        "--color with --counts-only should be rejected"
+    );
+}
+
+#[test]
+fn test_by_pinyin_show_onset() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--show", "onset"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(!stdout.is_empty(), "Output should not be empty");
+    assert!(
+        !contains_cjk(&stdout),
+        "--show onset should not print characters"
+    );
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("zhong") && line.ends_with(": Zh")),
+        "zhong should be shown with onset Zh"
+    );
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("an ") && line.ends_with(": None")),
+        "an should be shown with no onset"
+    );
+}
+
+#[test]
+fn test_by_pinyin_full_paradigm() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--full-paradigm", "--counts-only"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    // Collect the tones of every syllable straight from the data file
+    let data = std::fs::read_to_string("hanzi.tsv").expect("Failed to read hanzi.tsv");
+    let mut tones: std::collections::HashMap<&str, std::collections::HashSet<&str>> =
+        std::collections::HashMap::new();
+    for line in data.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() >= 6 {
+            tones.entry(fields[4]).or_default().insert(fields[5]);
+        }
+    }
+
+    let listed: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.split(':').next().unwrap().trim())
+        .collect();
+    assert!(!listed.is_empty(), "Some syllables should be listed");
+    assert!(
+        listed.len() < tones.len(),
+        "Syllables missing a tone should be dropped"
+    );
+    for syllable in listed {
+        for tone in ["1", "2", "3", "4"] {
+            assert!(
+                tones[syllable].contains(tone),
+                "'{syllable}' is listed but has no tone {tone}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_by_pinyin_format_table() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--format", "table"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines.first().unwrap().starts_with("┌─"));
+    assert!(lines.last().unwrap().starts_with("└─"));
+    assert!(lines[1].contains("Pinyin"));
+}
+
+#[test]
+fn test_by_pinyin_freq_range() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--freq-range"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(!stdout.is_empty(), "Output should not be empty");
+    for line in stdout.lines() {
+        let range = line
+            .rsplit_once("  [rank ")
+            .and_then(|(_, rest)| rest.strip_suffix(']'))
+            .unwrap_or_else(|| panic!("Line should end with a rank range: '{line}'"));
+        let (min, max) = range.split_once('–').expect("Range should use an en dash");
+        let min: u32 = min.parse().expect("Minimum should be a number");
+        let max: u32 = max.parse().expect("Maximum should be a number");
+        assert!(min <= max, "Invalid range in '{line}'");
+    }
+}
+
+#[test]
+fn test_by_pinyin_sort_onset() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--sort", "onset"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let pinyins: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(pinyins[0], "ba", "The listing should start with b + a");
+
+    // Every b-initial syllable precedes every p-initial one
+    let last_b = pinyins.iter().rposition(|p| p.starts_with('b')).unwrap();
+    let first_p = pinyins.iter().position(|p| p.starts_with('p')).unwrap();
+    assert!(
+        last_b < first_p,
+        "b syllables should come before p syllables"
+    );
+}
+
+#[test]
+fn test_by_pinyin_format_csv() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--format", "csv"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "pinyin,count,characters");
+    for line in &lines[1..] {
+        let fields: Vec<&str> = line.splitn(3, ',').collect();
+        assert_eq!(fields.len(), 3, "Line should have 3 fields: '{line}'");
+        assert!(fields[1].parse::<usize>().is_ok());
+        assert!(fields[2].starts_with('"') && fields[2].ends_with('"'));
+    }
+}
+
+#[test]
+fn test_by_pinyin_format_toml() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--format", "toml"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let document: toml::Table = stdout.parse().expect("Output should be valid TOML");
+    let ma = document["ma"].as_array().expect("ma should be an array");
+    assert!(ma.iter().any(|character| character.as_str() == Some("马")));
+    assert!(document.contains_key("nü"), "nü should be a quoted key");
+}
+
+#[test]
+fn test_by_tone_sort_freq_starts_with_most_common_character() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let records = study_rust_hanzi::read_hanzi_file("hanzi.tsv").expect("Failed to read hanzi.tsv");
+    let most_common = records
+        .iter()
+        .filter(|record| record.pinyin_without_tone == "ji" && record.frequency > 0)
+        .min_by_key(|record| record.frequency)
+        .expect("ji should occur in the data");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ji", "--sort", "freq"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let first_line = stdout.lines().next().expect("Output should not be empty");
+    let (pinyin, characters) = first_line
+        .split_once(": ")
+        .expect("Line should contain ': '");
+    assert_eq!(pinyin, most_common.pinyin);
+    assert!(
+        characters.starts_with(most_common.simplified.as_str()),
+        "The first group should start with the most common character: '{first_line}'"
+    );
+
+    // Other orders are rejected for a single pinyin
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ji", "--sort", "alpha"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success(), "--sort alpha should be rejected");
+}
+
 #[test]
 fn test_by_tone_output_format() {
     if !Path::new("hanzi.tsv").exists() {
@@ -125,6 +473,39 @@ fn test_by_tone_output_format() {
     );
 }
 
+#[test]
+fn test_by_tone_pinyin_only() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma", "--pinyin-only"])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    // A single line of tone-marked pinyin without characters
+    assert_eq!(stdout.lines().count(), 1, "Output should be a single line");
+    assert!(!contains_cjk(&stdout), "Output should contain only pinyin");
+    assert!(stdout.contains("mǎ"), "Output should contain tone marks");
+}
+
+#[test]
+fn test_by_pinyin_pinyin_only_requires_pinyin() {
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--pinyin-only"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        !output.status.success(),
+        "--pinyin-only without a pinyin should be rejected"
+    );
+}
+
 #[test]
 fn test_by_tone_traditional_vs_simplified() {
     if !Path::new("hanzi.tsv").exists() {
@@ -200,31 +581,751 @@ fn test_by_tone_tone_ordering() {
 }
 
 #[test]
-fn test_by_tone_v_to_u_replacement() {
+fn test_by_tone_query_with_tone() {
     if !Path::new("hanzi.tsv").exists() {
         eprintln!("Skipping test: hanzi.tsv not found");
         return;
     }
-    // Test that 'v' in command line input gets replaced with 'ü'
-    let output = Command::new("cargo")
-        .args(["run", "--", "pinyin", "nv"])
-        .output()
-        .expect("Failed to execute command");
 
-    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let run = |query: &str| {
+        let output = Command::new("cargo")
+            .args(["run", "--", "pinyin", query])
+            .output()
+            .expect("Failed to execute command");
+        String::from_utf8(output.stdout).expect("Invalid UTF-8")
+    };
 
-    // Should find characters for 'nü' when searching for 'nv'
-    // If no characters found, it should show the normalized pinyin in the message
-    if stdout.contains("No characters found") {
-        assert!(
-            stdout.contains("nü"),
-            "Error message should show normalized pinyin 'nü'"
-        );
-    } else {
-        // If characters are found, the output should not be empty
-        assert!(
-            !stdout.is_empty(),
-            "Should have output when characters are found"
-        );
+    // Without a tone, every tone is listed
+    let all_tones = run("ma");
+    assert!(
+        all_tones.lines().count() > 1,
+        "ma should list several tones"
+    );
+    assert!(all_tones.lines().any(|line| line.starts_with("mǎ:")));
+
+    // A tone digit or a tone mark restricts the output to that tone
+    for query in ["ma3", "mǎ"] {
+        let stdout = run(query);
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 1, "{query} should list one tone");
+        assert!(lines[0].starts_with("mǎ:"), "{query} should list tone 3");
+    }
+}
+
+#[test]
+fn test_by_tone_query_with_wildcard_tone() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
     }
+
+    let run = |query: &str| {
+        let output = Command::new("cargo")
+            .args(["run", "--", "pinyin", query])
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success(), "{query} should succeed");
+        String::from_utf8(output.stdout).expect("Invalid UTF-8")
+    };
+
+    // A trailing 0 means any tone
+    let wildcard = run("ma0");
+    assert_eq!(wildcard, run("ma"), "ma0 should list every tone group");
+    assert!(
+        wildcard.lines().count() > 1,
+        "ma0 should list several tones"
+    );
+}
+
+#[test]
+fn test_by_tone_no_neutral() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    // "ma" has neutral-tone characters (吗, 嘛) in the sample
+    let with_neutral = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma"])
+        .output()
+        .expect("Failed to execute command");
+    let with_neutral = String::from_utf8(with_neutral.stdout).expect("Invalid UTF-8");
+    assert!(with_neutral.lines().any(|line| line.starts_with("ma:")));
+
+    let without_neutral = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma", "--no-neutral"])
+        .output()
+        .expect("Failed to execute command");
+    let without_neutral = String::from_utf8(without_neutral.stdout).expect("Invalid UTF-8");
+    assert!(
+        !without_neutral.lines().any(|line| line.starts_with("ma:")),
+        "--no-neutral should exclude the neutral tone"
+    );
+    assert!(!without_neutral.contains('吗'));
+    assert_eq!(
+        without_neutral.lines().count(),
+        4,
+        "Tones 1-4 should remain"
+    );
+}
+
+#[test]
+fn test_by_tone_contour_labels() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma", "--contour"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout
+        .lines()
+        .any(|line| line.starts_with("mā (high level): ")));
+    assert!(stdout
+        .lines()
+        .any(|line| line.starts_with("mǎ (dipping): ")));
+}
+
+#[test]
+fn test_by_tone_numbered() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma", "--numbered"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout.lines().any(|line| line.starts_with("ma3: ")));
+    assert!(!stdout.lines().any(|line| line.starts_with("mǎ: ")));
+}
+
+#[test]
+fn test_by_tone_both_forms() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma", "--both"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let dipping = stdout
+        .lines()
+        .find(|line| line.starts_with("mǎ: "))
+        .expect("Output should contain the mǎ group");
+    assert!(dipping.contains("马(馬)"), "马 should be paired with 馬");
+}
+
+#[test]
+fn test_by_tone_bare_retroflex_onset() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let run = |query: &str| {
+        let output = Command::new("cargo")
+            .args(["run", "--", "pinyin", query])
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success(), "Command should succeed");
+        String::from_utf8(output.stdout).expect("Invalid UTF-8")
+    };
+
+    // zh searches zhi
+    let zh = run("zh");
+    assert!(zh.lines().any(|line| line.starts_with("zhī: ")));
+    assert_eq!(zh, run("zhi"));
+
+    // sh searches shi
+    let sh = run("sh");
+    assert!(sh.lines().any(|line| line.starts_with("shì: ")));
+    assert_eq!(sh, run("shi"));
+}
+
+#[test]
+fn test_by_tone_fullwidth_query() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let run = |query: &str| {
+        let output = Command::new("cargo")
+            .args(["run", "--", "pinyin", query])
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success(), "Command should succeed");
+        String::from_utf8(output.stdout).expect("Invalid UTF-8")
+    };
+
+    // Full-width "ｍａ" finds the same characters as half-width "ma"
+    let half_width = run("ma");
+    assert!(contains_cjk(&half_width));
+    assert_eq!(run("ｍａ"), half_width);
+}
+
+#[test]
+fn test_by_tone_v_to_u_replacement() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+    // Test that 'v' in command line input gets replaced with 'ü'
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "nv"])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    // Should find characters for 'nü' when searching for 'nv'
+    // If no characters found, it should show the normalized pinyin in the message
+    if stdout.contains("No characters found") {
+        assert!(
+            stdout.contains("nü"),
+            "Error message should show normalized pinyin 'nü'"
+        );
+    } else {
+        // If characters are found, the output should not be empty
+        assert!(
+            !stdout.is_empty(),
+            "Should have output when characters are found"
+        );
+    }
+}
+
+#[test]
+fn test_by_pinyin_max_chars_truncates_groups() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--max-chars", "3"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "--max-chars should succeed");
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    // "ji" has far more than 3 characters, so it is truncated
+    let ji_line = stdout
+        .lines()
+        .find(|line| line.starts_with("ji "))
+        .expect("ji should be listed");
+    let (header, rest) = ji_line.split_at(ji_line.find(':').unwrap() + 1);
+    let mut fields = rest.split_whitespace();
+    let count: usize = fields
+        .next()
+        .unwrap()
+        .parse()
+        .expect("Count should be a number");
+    let characters = fields.next().unwrap();
+    assert!(header.starts_with("ji"));
+    assert!(count > 3, "The count should be the full total");
+
+    let (shown, marker) = characters
+        .split_once('…')
+        .expect("Overflow marker expected");
+    assert_eq!(shown.chars().count(), 3);
+    assert_eq!(marker, format!("(+{})", count - 3));
+
+    // No line shows more than 3 characters before the marker
+    for line in stdout.lines() {
+        let characters = line.split_whitespace().nth(3).unwrap_or("");
+        let shown = characters.split('…').next().unwrap();
+        assert!(shown.chars().count() <= 3, "Too many characters: '{line}'");
+    }
+}
+
+#[test]
+fn test_by_onset_include_empty_lists_all_onsets() {
+    // A tiny dataset with only two onsets, in a directory of its own
+    let dir = std::env::temp_dir().join(format!(
+        "study-rust-hanzi-include-empty-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).expect("Failed to create test directory");
+    std::fs::write(
+        dir.join("hanzi.tsv"),
+        "1\t的\t的\tde\tde\t5\n2\t一\t一\tyī\tyi\t1\n3\t地\t地\tdì\tdi\t4\n",
+    )
+    .expect("Failed to write test file");
+
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    let output = Command::new("cargo")
+        .args(["run", "--manifest-path"])
+        .arg(&manifest)
+        .args(["--", "onset", "--include-empty"])
+        .current_dir(&dir)
+        .output()
+        .expect("Failed to execute command");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "--include-empty should succeed");
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    // All 23 consonant onsets and none are listed
+    assert_eq!(lines.len(), 24);
+    assert_eq!(lines[0], "d: 2");
+    assert_eq!(lines[1], "y: 1");
+    assert_eq!(lines[2], "b: 0");
+    assert_eq!(lines[23], "none: 0");
+}
+
+#[test]
+fn test_stats_prints_tone_distribution() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "stats"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "stats should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout.lines().any(|line| line == "Records: 5000"));
+    let tones = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Tones: "))
+        .expect("Output should contain a Tones: line");
+    let counts: Vec<(u32, u32)> = tones
+        .split(' ')
+        .map(|pair| {
+            let (tone, count) = pair.split_once('=').expect("Pair should be tone=count");
+            (tone.parse().unwrap(), count.parse().unwrap())
+        })
+        .collect();
+    let tone_numbers: Vec<u32> = counts.iter().map(|(tone, _)| *tone).collect();
+    assert_eq!(tone_numbers, vec![1, 2, 3, 4, 5]);
+    assert_eq!(counts.iter().map(|(_, count)| count).sum::<u32>(), 5000);
+}
+
+#[test]
+fn test_inventory_lists_onsets_and_rimes() {
+    let output = Command::new("cargo")
+        .args(["run", "--", "inventory"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "inventory should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let words: Vec<&str> = stdout.split_whitespace().collect();
+    assert!(words.contains(&"zh"), "Output should list the onset zh");
+    assert!(words.contains(&"ang"), "Output should list the rime ang");
+}
+
+#[test]
+fn test_search_with_wildcard_rime() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "search", "--onset", "m", "--rime", "*"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "search should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let pinyins: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert!(pinyins.iter().all(|pinyin| pinyin.starts_with('m')));
+    assert!(pinyins.contains(&"ma") && pinyins.contains(&"ming"));
+
+    // An invalid onset is rejected
+    let output = Command::new("cargo")
+        .args(["run", "--", "search", "--onset", "xx"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success(), "An invalid onset should fail");
+}
+
+/// Runs `list --group-by <key>` and returns its stdout
+fn run_list(group_by: &str) -> String {
+    let output = Command::new("cargo")
+        .args(["run", "--", "list", "--group-by", group_by])
+        .output()
+        .expect("Failed to execute command");
+    assert!(
+        output.status.success(),
+        "list --group-by {group_by} should succeed"
+    );
+    String::from_utf8(output.stdout).expect("Invalid UTF-8")
+}
+
+#[test]
+fn test_list_group_by_pinyin() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    // Same as the pinyin listing without color
+    let expected = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--color", "never"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(
+        run_list("pinyin"),
+        String::from_utf8(expected.stdout).expect("Invalid UTF-8")
+    );
+}
+
+#[test]
+fn test_list_group_by_tone() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let stdout = run_list("tone");
+    let tones: Vec<&str> = stdout
+        .lines()
+        .map(|line| {
+            line.split_once(": ")
+                .expect("Line should be 'tone: count'")
+                .0
+        })
+        .collect();
+    assert_eq!(&tones[..4], ["1", "2", "3", "4"]);
+    let total: u32 = stdout
+        .lines()
+        .map(|line| line.split_once(": ").unwrap().1.parse::<u32>().unwrap())
+        .sum();
+    assert_eq!(total, 5000, "Every record should be counted once");
+}
+
+#[test]
+fn test_list_group_by_onset() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    // Same as the onset command without an onset
+    let expected = Command::new("cargo")
+        .args(["run", "--", "onset"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(
+        run_list("onset"),
+        String::from_utf8(expected.stdout).expect("Invalid UTF-8")
+    );
+}
+
+#[test]
+fn test_list_group_by_rime() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let stdout = run_list("rime");
+    assert!(stdout.lines().any(|line| line.starts_with("ang: ")));
+    assert!(stdout.lines().any(|line| line.starts_with("ü: ")));
+    let counts: Vec<u32> = stdout
+        .lines()
+        .map(|line| line.rsplit_once(": ").unwrap().1.parse().unwrap())
+        .collect();
+    assert!(
+        counts.windows(2).all(|pair| pair[0] >= pair[1]),
+        "Rimes should be sorted by count"
+    );
+}
+
+#[test]
+fn test_by_rime_groups_by_onset() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "by-rime", "a", "--list"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "by-rime a --list should succeed");
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    // Every line is an onset followed by a count and characters
+    assert!(!stdout.is_empty(), "Output should not be empty");
+    for line in stdout.lines() {
+        assert!(
+            line.contains(" : "),
+            "Line should be an onset group: '{line}'"
+        );
+        assert!(
+            contains_cjk(line),
+            "Line should contain characters: '{line}'"
+        );
+    }
+    assert!(
+        stdout.lines().any(|line| line.starts_with("b ")),
+        "ba syllables should be listed under onset b"
+    );
+}
+
+#[test]
+fn test_by_rime_counts_without_list() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "rime", "ang"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "rime ang should succeed");
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    // Only the onsets and their counts, no characters
+    assert!(!stdout.is_empty(), "Output should not be empty");
+    for line in stdout.lines() {
+        let (onset, count) = line
+            .split_once(" : ")
+            .unwrap_or_else(|| panic!("Line should be an onset count: '{line}'"));
+        assert!(!onset.trim().is_empty());
+        assert!(count.trim().parse::<usize>().is_ok(), "Bad count: '{line}'");
+    }
+}
+
+#[test]
+fn test_by_rime_rejects_invalid_rime() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "by-rime", "xyz"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "An invalid rime should fail");
+    let stderr = String::from_utf8(output.stderr).expect("Invalid UTF-8");
+    assert!(stderr.contains("Invalid rime 'xyz'"));
+}
+
+#[test]
+fn test_validate_prints_summary() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "validate"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "validate should succeed");
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    // The last line is the summary with the number of unparsed records
+    let summary = stdout.lines().last().expect("Output should not be empty");
+    assert!(
+        summary.starts_with("Unparsed syllables: "),
+        "Last line should be the summary, got '{summary}'"
+    );
+    assert!(summary.ends_with(" records"));
+}
+
+#[test]
+fn test_output_option_writes_to_file() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output_path = std::env::temp_dir().join(format!(
+        "study-rust-hanzi-output-{}.txt",
+        std::process::id()
+    ));
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma", "--output"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout.is_empty(), "Nothing should be written to stdout");
+
+    let written = std::fs::read_to_string(&output_path).expect("Failed to read output file");
+    std::fs::remove_file(&output_path).ok();
+
+    let expected = Command::new("cargo")
+        .args(["run", "--", "pinyin", "ma"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(
+        written,
+        String::from_utf8(expected.stdout).expect("Invalid UTF-8"),
+        "The file should hold the same lines as stdout"
+    );
+    assert!(
+        written.contains("mǎ"),
+        "Output should contain tone-marked pinyin"
+    );
+}
+
+#[test]
+fn test_verbose_reports_statistics_to_stderr() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--verbose"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    let stderr = String::from_utf8(output.stderr).expect("Invalid UTF-8");
+    assert!(
+        stderr.lines().any(|line| line == "Records read: 5000"),
+        "stderr should report the record count"
+    );
+    assert!(stderr.contains("Distinct pinyin: "));
+    assert!(stderr.contains("Unclassified rimes: "));
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(
+        !stdout.contains("Records read"),
+        "Statistics should not be written to stdout"
+    );
+    assert!(contains_cjk(&stdout), "The listing should still be printed");
+}
+
+#[test]
+fn test_diff_shows_added_and_removed_characters() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "diff",
+            "tests/fixtures/diff_old.tsv",
+            "tests/fixtures/diff_new.tsv",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    // The fixtures share 的 and 一; 是 was replaced by 不
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert_eq!(stdout, "- 是\n+ 不\n");
+}
+
+#[test]
+fn test_coverage_against_reference_list() {
+    if !Path::new("hanzi.tsv").exists() {
+        eprintln!("Skipping test: hanzi.tsv not found");
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "coverage",
+            "--reference",
+            "tests/fixtures/syllables_reference.txt",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "coverage should succeed");
+
+    // The reference has MA, nv (for nü), hao and the made-up xyz
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let only_in_data: Vec<&str> = lines[0]
+        .strip_prefix("Only in data (")
+        .expect("First line should list the syllables only in the data")
+        .split_whitespace()
+        .skip(1)
+        .collect();
+    assert!(only_in_data.contains(&"ji"));
+    assert!(!only_in_data.contains(&"ma"));
+    assert!(!only_in_data.contains(&"nü"));
+    assert_eq!(lines[1], "Only in reference (1): xyz");
+}
+
+#[test]
+fn test_diff_shows_changed_pinyin() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "diff",
+            "tests/fixtures/diff_old.tsv",
+            "tests/fixtures/diff_pinyin_new.tsv",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    // 一 is read yì instead of yī; 的 and 是 are unchanged
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert_eq!(stdout, "~ 一: yī → yì\n");
+}
+
+#[test]
+fn test_diff_ignores_reordered_readings() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "diff",
+            "tests/fixtures/diff_multi_old.tsv",
+            "tests/fixtures/diff_multi_new.tsv",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "Command should succeed");
+
+    // 长 keeps both readings cháng and zhǎng, only their order differs
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_fold_zero_is_rejected() {
+    let output = Command::new("cargo")
+        .args(["run", "--", "pinyin", "--fold", "0"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "--fold 0 should be rejected");
+    let stderr = String::from_utf8(output.stderr).expect("Invalid UTF-8");
+    assert!(stderr.contains("--fold"), "The error should name the flag");
 }